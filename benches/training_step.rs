@@ -0,0 +1,59 @@
+// Times `Network::train_step` with a reused workspace and counts the heap
+// allocations each step makes once the buffers are warm. Run with
+// `cargo bench --bench training_step`.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use cranium_rs::dataset::*;
+use cranium_rs::function::*;
+use cranium_rs::network::*;
+use cranium_rs::workspace::*;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const WARMUP_STEPS: usize = 5;
+const STEPS: usize = 200;
+
+fn main() {
+    let rows = 64;
+    let features: Vec<Vec<f32>> = (0..rows).map(|i| (0..32).map(|j| ((i * 31 + j * 7) as f32 * 0.1).sin()).collect()).collect();
+    let classes: Vec<Vec<f32>> = (0..rows).map(|i| (0..10).map(|j| if i % 10 == j { 1.0 } else { 0.0 }).collect()).collect();
+    let features = create_dataset(rows, 32, features);
+    let classes = create_dataset(rows, 10, classes);
+
+    let mut network = create_network(32, 2, vec![64, 32], vec![Some(relu), Some(tanh)], 10, Some(softmax));
+    let mut config = create_training_config(LossFunction::CrossEntropy, rows, 0.05, 0.0, 0.001, 0.9, 1, false, false);
+    let mut workspace = create_workspace(&network);
+    for _ in 0..WARMUP_STEPS {
+        network.train_step(&mut workspace, &features, &classes, None, &mut config).unwrap();
+    }
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let mut loss = 0.0;
+    for _ in 0..STEPS {
+        loss = network.train_step(&mut workspace, &features, &classes, None, &mut config).unwrap();
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+
+    println!("training_step: {} steps of {} rows, {:?} per step, final loss {}", STEPS, rows, elapsed / STEPS as u32, loss);
+    println!("training_step: {} allocations per step", allocations as f64 / STEPS as f64);
+    assert_eq!(allocations, 0, "steady-state training steps should not allocate");
+}
@@ -0,0 +1,199 @@
+use crate::matrix::*;
+use crate::prelude::*;
+use crate::function::*;
+use crate::layer::*;
+
+// Added to the scores of masked positions before the softmax.
+const MASK_VALUE: f32 = -1e9;
+
+pub struct Attention {
+    pub output: Matrix,
+    pub weights: Matrix
+}
+
+pub struct AttentionGradients {
+    pub d_query: Matrix,
+    pub d_key: Matrix,
+    pub d_value: Matrix
+}
+
+// A mask has one row per query and one column per key: 1.0 lets the query
+// attend to the key, 0.0 blocks it.
+pub fn causal_mask(size: usize) -> Matrix {
+    let mut mask = Matrix::create_zero_matrix(size, size);
+    for i in 0..size {
+        for j in 0..=i {
+            mask.set(i, j, 1.0);
+        }
+    }
+
+    mask
+}
+
+pub fn positional_encoding(seq_len: usize, model_size: usize) -> Matrix {
+    let mut encoding = Matrix::create_zero_matrix(seq_len, model_size);
+    for pos in 0..seq_len {
+        for i in 0..model_size {
+            let exponent = (2 * (i / 2)) as f32 / model_size as f32;
+            let angle = pos as f32 / 10000_f32.powf(exponent);
+            let val = if i % 2 == 0 { angle.sin() } else { angle.cos() };
+            encoding.set(pos, i, val);
+        }
+    }
+
+    encoding
+}
+
+pub fn add_positional_encoding(input: &Matrix) -> Matrix {
+    input.add(&positional_encoding(input.rows, input.cols))
+}
+
+pub fn scaled_dot_product_attention(query: &Matrix, key: &Matrix, value: &Matrix, mask: Option<&Matrix>) -> Attention {
+    assert!(query.cols == key.cols);
+    assert!(key.rows == value.rows);
+    let scale = 1.0 / (query.cols as f32).sqrt();
+    let mut scores = query.multiply(&key.transpose());
+    scores.scalar_multiply(scale);
+    if let Some(mask) = mask {
+        assert!(mask.rows == scores.rows && mask.cols == scores.cols);
+        for i in 0..scores.rows {
+            for j in 0..scores.cols {
+                if mask.get(i, j) == 0.0 {
+                    scores.set(i, j, MASK_VALUE);
+                }
+            }
+        }
+    }
+
+    let weights = apply_activation(Some(softmax), scores);
+    let output = weights.multiply(value);
+    Attention {output, weights}
+}
+
+pub fn scaled_dot_product_attention_backward(
+    query: &Matrix,
+    key: &Matrix,
+    value: &Matrix,
+    weights: &Matrix,
+    d_output: &Matrix) -> AttentionGradients {
+
+    assert!(d_output.rows == weights.rows && d_output.cols == value.cols);
+    let scale = 1.0 / (query.cols as f32).sqrt();
+    let d_value = weights.transpose().multiply(d_output);
+    let d_weights = d_output.multiply(&value.transpose());
+
+    // Softmax backward, row by row: dS = A * (dA - sum(dA * A)).
+    let mut d_scores = Matrix::create_zero_matrix(weights.rows, weights.cols);
+    for i in 0..weights.rows {
+        let mut dot = 0.0;
+        for j in 0..weights.cols {
+            dot += d_weights.get(i, j) * weights.get(i, j);
+        }
+        for j in 0..weights.cols {
+            let val = weights.get(i, j) * (d_weights.get(i, j) - dot) * scale;
+            d_scores.set(i, j, val);
+        }
+    }
+
+    let d_query = d_scores.multiply(key);
+    let d_key = d_scores.transpose().multiply(query);
+    AttentionGradients {d_query, d_key, d_value}
+}
+
+struct Head {
+    query: Matrix,
+    key: Matrix,
+    value: Matrix,
+    weights: Matrix
+}
+
+pub struct MultiHeadAttention {
+    pub num_heads: usize,
+    pub model_size: usize,
+    pub head_size: usize,
+    pub query: Linear,
+    pub key: Linear,
+    pub value: Linear,
+    pub output: Linear,
+    heads: Vec<Head>
+}
+
+pub fn create_multi_head_attention(model_size: usize, num_heads: usize) -> MultiHeadAttention {
+    assert!(num_heads > 0 && model_size.is_multiple_of(num_heads));
+    MultiHeadAttention {
+        num_heads,
+        model_size,
+        head_size: model_size / num_heads,
+        query: create_linear(model_size, model_size),
+        key: create_linear(model_size, model_size),
+        value: create_linear(model_size, model_size),
+        output: create_linear(model_size, model_size),
+        heads: Vec::new()
+    }
+}
+
+impl MultiHeadAttention {
+    pub fn forward(&mut self, input: &Matrix, mask: Option<&Matrix>) -> Matrix {
+        self.attend(input, input, input, mask)
+    }
+
+    pub fn attend(&mut self, query: &Matrix, key: &Matrix, value: &Matrix, mask: Option<&Matrix>) -> Matrix {
+        assert!(query.cols == self.model_size && key.cols == self.model_size && value.cols == self.model_size);
+        let q = self.query.forward(query);
+        let k = self.key.forward(key);
+        let v = self.value.forward(value);
+
+        self.heads.clear();
+        let mut concat = Matrix::create_zero_matrix(query.rows, self.model_size);
+        for h in 0..self.num_heads {
+            let (start, end) = (h * self.head_size, (h + 1) * self.head_size);
+            let (head_q, head_k, head_v) = (q.slice_cols(start, end), k.slice_cols(start, end), v.slice_cols(start, end));
+            let attention = scaled_dot_product_attention(&head_q, &head_k, &head_v, mask);
+            concat.set_cols(start, &attention.output);
+            self.heads.push(Head {query: head_q, key: head_k, value: head_v, weights: attention.weights});
+        }
+
+        self.output.forward(&concat)
+    }
+
+    // Returns the gradients w.r.t. the query, key and value inputs of the last call to `attend`.
+    pub fn backward_attend(&mut self, d_output: &Matrix) -> AttentionGradients {
+        assert!(self.heads.len() == self.num_heads);
+        let d_concat = self.output.backward(d_output);
+        let mut d_q = Matrix::create_zero_matrix(self.query.input.rows, self.model_size);
+        let mut d_k = Matrix::create_zero_matrix(self.key.input.rows, self.model_size);
+        let mut d_v = Matrix::create_zero_matrix(self.value.input.rows, self.model_size);
+        for (h, head) in self.heads.iter().enumerate() {
+            let (start, end) = (h * self.head_size, (h + 1) * self.head_size);
+            let grads = scaled_dot_product_attention_backward(&head.query, &head.key, &head.value, &head.weights, &d_concat.slice_cols(start, end));
+            d_q.set_cols(start, &grads.d_query);
+            d_k.set_cols(start, &grads.d_key);
+            d_v.set_cols(start, &grads.d_value);
+        }
+
+        AttentionGradients {
+            d_query: self.query.backward(&d_q),
+            d_key: self.key.backward(&d_k),
+            d_value: self.value.backward(&d_v)
+        }
+    }
+
+    pub fn backward(&mut self, d_output: &Matrix) -> Matrix {
+        let grads = self.backward_attend(d_output);
+        grads.d_query.add(&grads.d_key).add(&grads.d_value)
+    }
+
+    pub fn update(&mut self, learning_rate: f32) {
+        self.query.update(learning_rate);
+        self.key.update(learning_rate);
+        self.value.update(learning_rate);
+        self.output.update(learning_rate);
+    }
+
+    pub fn zero_gradients(&mut self) {
+        self.query.zero_gradients();
+        self.key.zero_gradients();
+        self.value.zero_gradients();
+        self.output.zero_gradients();
+    }
+}
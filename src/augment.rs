@@ -0,0 +1,190 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Beta, Distribution, Normal};
+use crate::dataset::*;
+use crate::prelude::*;
+
+// How a feature row is laid out as an image: `channels` planes of
+// `height` rows by `width` columns, one plane after another.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImageShape {
+    pub channels: usize,
+    pub height: usize,
+    pub width: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Transform {
+    // Adds zero-mean noise with the given standard deviation to every feature.
+    GaussianNoise(f32),
+    // Zeroes each feature independently with the given probability.
+    RandomMask(f32),
+    // Blends every row, features and targets alike, with another row of the
+    // batch using a weight drawn from Beta(alpha, alpha).
+    Mixup(f32),
+    // Takes a random `height` x `width` window and stretches it back to the
+    // full image with nearest-neighbour sampling.
+    RandomCrop { shape: ImageShape, height: usize, width: usize },
+    // Mirrors the image left to right with the given probability.
+    HorizontalFlip { shape: ImageShape, probability: f32 },
+    // Translates the image by up to `max_shift` pixels along each axis,
+    // filling the uncovered border with zeros.
+    RandomShift { shape: ImageShape, max_shift: usize },
+}
+
+// A seeded sequence of transforms applied to training batches. Outside of
+// training the pipeline is inert, so evaluation always sees the raw rows.
+pub struct Augmentation {
+    pub transforms: Vec<Transform>,
+    rng: StdRng,
+    training: bool,
+}
+
+pub fn create_augmentation(transforms: Vec<Transform>, seed: u64) -> Result<Augmentation> {
+    for transform in transforms.iter() {
+        check_transform(transform)?;
+    }
+    Ok(Augmentation {transforms, rng: StdRng::seed_from_u64(seed), training: false})
+}
+
+fn check_transform(transform: &Transform) -> Result<()> {
+    match transform {
+        Transform::GaussianNoise(std_dev) if !(std_dev.is_finite() && *std_dev >= 0.0) => {
+            Err(Error::InvalidConfig(format!("noise standard deviation {} must be finite and non-negative", std_dev)))
+        },
+        Transform::Mixup(alpha) if !(alpha.is_finite() && *alpha > 0.0) => {
+            Err(Error::InvalidConfig(format!("mixup alpha {} must be finite and positive", alpha)))
+        },
+        Transform::RandomCrop { shape, height, width }
+            if *height == 0 || *height > shape.height || *width == 0 || *width > shape.width => {
+            Err(Error::InvalidConfig(format!("a {}x{} crop does not fit a {}x{} image", height, width, shape.height, shape.width)))
+        },
+        _ => Ok(()),
+    }
+}
+
+impl ImageShape {
+    pub fn size(&self) -> usize {
+        self.channels * self.height * self.width
+    }
+
+    fn index(&self, channel: usize, y: usize, x: usize) -> usize {
+        (channel * self.height + y) * self.width + x
+    }
+}
+
+// Builds each pixel of the output from `source(y, x)`, which gives the input
+// pixel to copy or None for a zero.
+fn remap<T: Float, F>(row: &mut [T], shape: &ImageShape, source: F)
+where F: Fn(usize, usize) -> Option<(usize, usize)> {
+    assert!(row.len() == shape.size());
+    let original = row.to_vec();
+    for channel in 0..shape.channels {
+        for y in 0..shape.height {
+            for x in 0..shape.width {
+                row[shape.index(channel, y, x)] = match source(y, x) {
+                    Some((sy, sx)) => original[shape.index(channel, sy, sx)],
+                    None => T::ZERO,
+                };
+            }
+        }
+    }
+}
+
+impl Augmentation {
+    // Checks every transform, and that the image transforms describe rows of
+    // exactly `num_features` values.
+    pub fn check(&self, num_features: usize) -> Result<()> {
+        for transform in self.transforms.iter() {
+            check_transform(transform)?;
+            let shape = match transform {
+                Transform::RandomCrop { shape, .. } => shape,
+                Transform::HorizontalFlip { shape, .. } => shape,
+                Transform::RandomShift { shape, .. } => shape,
+                _ => continue,
+            };
+            if shape.size() != num_features {
+                return Err(Error::ShapeMismatch {expected: (1, num_features), found: (1, shape.size())});
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set_training(&mut self, training: bool) {
+        self.training = training;
+    }
+
+    pub fn is_training(&self) -> bool {
+        self.training
+    }
+
+    // Applies every transform in order. Does nothing outside of training.
+    pub fn apply<T: Float>(&mut self, features: &mut DataSet<T>, targets: &mut DataSet<T>) {
+        if !self.training {
+            return;
+        }
+        assert!(features.rows == targets.rows);
+        for transform in self.transforms.iter() {
+            match transform {
+                Transform::GaussianNoise(std_dev) => {
+                    let normal = Normal::new(0.0, *std_dev).expect("noise standard deviation must be finite and non-negative");
+                    for value in features.data.iter_mut().flatten() {
+                        *value += T::from_f32(normal.sample(&mut self.rng));
+                    }
+                },
+                Transform::RandomMask(probability) => {
+                    for value in features.data.iter_mut().flatten() {
+                        if self.rng.gen::<f32>() < *probability {
+                            *value = T::ZERO;
+                        }
+                    }
+                },
+                Transform::Mixup(alpha) => {
+                    let beta = Beta::new(*alpha, *alpha).expect("mixup alpha must be positive");
+                    let partners = permutation(features.rows, &mut self.rng);
+                    let original_features = features.data.clone();
+                    let original_targets = targets.data.clone();
+                    for (i, &j) in partners.iter().enumerate() {
+                        let lambda = T::from_f32(beta.sample(&mut self.rng));
+                        for (value, &other) in features.data[i].iter_mut().zip(original_features[j].iter()) {
+                            *value = lambda * *value + (T::ONE - lambda) * other;
+                        }
+                        for (value, &other) in targets.data[i].iter_mut().zip(original_targets[j].iter()) {
+                            *value = lambda * *value + (T::ONE - lambda) * other;
+                        }
+                    }
+                },
+                Transform::RandomCrop { shape, height, width } => {
+                    for row in features.data.iter_mut() {
+                        let top = self.rng.gen_range(0..=shape.height - height);
+                        let left = self.rng.gen_range(0..=shape.width - width);
+                        remap(row, shape, |y, x| Some((top + y * height / shape.height, left + x * width / shape.width)));
+                    }
+                },
+                Transform::HorizontalFlip { shape, probability } => {
+                    for row in features.data.iter_mut() {
+                        if self.rng.gen::<f32>() < *probability {
+                            remap(row, shape, |y, x| Some((y, shape.width - 1 - x)));
+                        }
+                    }
+                },
+                Transform::RandomShift { shape, max_shift } => {
+                    let max_shift = *max_shift as isize;
+                    for row in features.data.iter_mut() {
+                        let dy = self.rng.gen_range(-max_shift..=max_shift);
+                        let dx = self.rng.gen_range(-max_shift..=max_shift);
+                        remap(row, shape, |y, x| {
+                            let sy = y as isize - dy;
+                            let sx = x as isize - dx;
+                            if sy < 0 || sx < 0 || sy >= shape.height as isize || sx >= shape.width as isize {
+                                None
+                            } else {
+                                Some((sy as usize, sx as usize))
+                            }
+                        });
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use crate::matrix::*;
+use crate::prelude::*;
+use crate::function::*;
+
+enum Op {
+    Leaf,
+    Multiply(usize, usize),
+    Add(usize, usize),
+    AddToEachRow(usize, usize),
+    Hadamard(usize, usize),
+    Transpose(usize),
+    Scale(usize, f32),
+    Activation(usize, Activation),
+    CustomActivation(usize, fn(f32) -> f32),
+    Sum(usize),
+    CrossEntropy(usize, Matrix),
+    MeanSquaredError(usize, Matrix)
+}
+
+struct Entry {
+    op: Op,
+    value: Matrix
+}
+
+// Records every operation applied to its variables so that `backward`
+// can replay them in reverse and compute gradients automatically.
+pub struct Tape {
+    entries: RefCell<Vec<Entry>>
+}
+
+#[derive(Clone, Copy)]
+pub struct Var<'t> {
+    tape: &'t Tape,
+    index: usize
+}
+
+pub struct Gradients {
+    grads: Vec<Option<Matrix>>
+}
+
+pub fn create_tape() -> Tape {
+    Tape {entries: RefCell::new(Vec::new())}
+}
+
+impl Tape {
+    pub fn var(&self, value: Matrix) -> Var<'_> {
+        self.push(Op::Leaf, value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.borrow_mut().clear();
+    }
+
+    fn push(&self, op: Op, value: Matrix) -> Var<'_> {
+        let mut entries = self.entries.borrow_mut();
+        entries.push(Entry {op, value});
+        Var {tape: self, index: entries.len() - 1}
+    }
+
+    fn value(&self, index: usize) -> Matrix {
+        self.entries.borrow()[index].value.copy()
+    }
+}
+
+impl<'t> Var<'t> {
+    pub fn value(&self) -> Matrix {
+        self.tape.value(self.index)
+    }
+
+    pub fn multiply(&self, other: &Var<'t>) -> Var<'t> {
+        let value = self.with_values(other, |a, b| a.multiply(b));
+        self.tape.push(Op::Multiply(self.index, other.index), value)
+    }
+
+    pub fn add(&self, other: &Var<'t>) -> Var<'t> {
+        let value = self.with_values(other, |a, b| a.add(b));
+        self.tape.push(Op::Add(self.index, other.index), value)
+    }
+
+    pub fn add_to_each_row(&self, row: &Var<'t>) -> Var<'t> {
+        let value = self.with_values(row, |a, b| a.add_to_each_row(b));
+        self.tape.push(Op::AddToEachRow(self.index, row.index), value)
+    }
+
+    pub fn hadamard(&self, other: &Var<'t>) -> Var<'t> {
+        let value = self.with_values(other, |a, b| a.hadamard(b));
+        self.tape.push(Op::Hadamard(self.index, other.index), value)
+    }
+
+    pub fn transpose(&self) -> Var<'t> {
+        let value = self.value().transpose();
+        self.tape.push(Op::Transpose(self.index), value)
+    }
+
+    pub fn scalar_multiply(&self, k: f32) -> Var<'t> {
+        let mut value = self.value();
+        value.scalar_multiply(k);
+        self.tape.push(Op::Scale(self.index, k), value)
+    }
+
+    // Only the built-in activations have a known derivative; use
+    // `activate_with` for any other.
+    pub fn activate(&self, activation: Activation) -> Result<Var<'t>> {
+        if find_function_name(activation).is_none() {
+            return Err(Error::InvalidConfig("a custom activation needs a derivative, see activate_with".to_string()));
+        }
+        Ok(self.builtin(activation))
+    }
+
+    // Applies any activation. The backward pass multiplies by `derivative`,
+    // which takes the activated value like `sigmoid_deriv`.
+    pub fn activate_with(&self, activation: Activation, derivative: fn(f32) -> f32) -> Var<'t> {
+        let value = apply_activation(Some(activation), self.value());
+        self.tape.push(Op::CustomActivation(self.index, derivative), value)
+    }
+
+    pub fn sigmoid(&self) -> Var<'t> {
+        self.builtin(sigmoid)
+    }
+
+    pub fn relu(&self) -> Var<'t> {
+        self.builtin(relu)
+    }
+
+    pub fn tanh(&self) -> Var<'t> {
+        self.builtin(tanh)
+    }
+
+    pub fn softmax(&self) -> Var<'t> {
+        self.builtin(softmax)
+    }
+
+    fn builtin(&self, activation: Activation) -> Var<'t> {
+        let value = apply_activation(Some(activation), self.value());
+        self.tape.push(Op::Activation(self.index, activation), value)
+    }
+
+    pub fn sum(&self) -> Var<'t> {
+        let total: f32 = self.value().data.iter().flatten().sum();
+        self.tape.push(Op::Sum(self.index), Matrix::create_matrix(1, 1, vec![vec![total]]))
+    }
+
+    // Mean over rows of -sum(target * ln(prediction)).
+    pub fn cross_entropy(&self, target: &Matrix) -> Var<'t> {
+        let prediction = self.value();
+        assert!(prediction.rows == target.rows && prediction.cols == target.cols);
+        let mut total = 0.0;
+        for i in 0..prediction.rows {
+            for j in 0..prediction.cols {
+                total += target.get(i, j) * prediction.get(i, j).max(f32::MIN_POSITIVE).ln();
+            }
+        }
+        let loss = -total / prediction.rows as f32;
+        self.tape.push(Op::CrossEntropy(self.index, target.copy()), Matrix::create_matrix(1, 1, vec![vec![loss]]))
+    }
+
+    // Mean over rows of 0.5 * sum((prediction - target)^2).
+    pub fn mean_squared_error(&self, target: &Matrix) -> Var<'t> {
+        let prediction = self.value();
+        assert!(prediction.rows == target.rows && prediction.cols == target.cols);
+        let mut total = 0.0;
+        for i in 0..prediction.rows {
+            for j in 0..prediction.cols {
+                let diff = prediction.get(i, j) - target.get(i, j);
+                total += diff * diff;
+            }
+        }
+        let loss = 0.5 * total / prediction.rows as f32;
+        self.tape.push(Op::MeanSquaredError(self.index, target.copy()), Matrix::create_matrix(1, 1, vec![vec![loss]]))
+    }
+
+    // Computes the gradient of this variable w.r.t. every variable recorded before it.
+    pub fn backward(&self) -> Gradients {
+        let entries = self.tape.entries.borrow();
+        let mut grads: Vec<Option<Matrix>> = (0..entries.len()).map(|_| None).collect();
+        let value = &entries[self.index].value;
+        grads[self.index] = Some(Matrix::create_matrix(value.rows, value.cols, vec![vec![1.0; value.cols]; value.rows]));
+
+        for index in (0..=self.index).rev() {
+            let grad = match &grads[index] {
+                Some(grad) => grad.copy(),
+                None => continue,
+            };
+            let value = &entries[index].value;
+            let parent_grads: Vec<(usize, Matrix)> = match &entries[index].op {
+                Op::Leaf => Vec::new(),
+                Op::Multiply(a, b) => vec![
+                    (*a, grad.multiply(&entries[*b].value.transpose())),
+                    (*b, entries[*a].value.transpose().multiply(&grad)),
+                ],
+                Op::Add(a, b) => vec![(*a, grad.copy()), (*b, grad)],
+                Op::AddToEachRow(a, b) => {
+                    let mut row = Matrix::create_zero_matrix(1, grad.cols);
+                    for i in 0..grad.rows {
+                        for j in 0..grad.cols {
+                            row.data[0][j] += grad.get(i, j);
+                        }
+                    }
+                    vec![(*a, grad), (*b, row)]
+                },
+                Op::Hadamard(a, b) => vec![
+                    (*a, grad.hadamard(&entries[*b].value)),
+                    (*b, grad.hadamard(&entries[*a].value)),
+                ],
+                Op::Transpose(a) => vec![(*a, grad.transpose())],
+                Op::Scale(a, k) => {
+                    let mut grad = grad;
+                    grad.scalar_multiply(*k);
+                    vec![(*a, grad)]
+                },
+                Op::Activation(a, activation) => vec![(*a, activation_backward(Some(*activation), value, &grad))],
+                Op::CustomActivation(a, derivative) => {
+                    let mut grad = grad;
+                    for i in 0..grad.rows {
+                        for j in 0..grad.cols {
+                            grad.data[i][j] *= derivative(value.get(i, j));
+                        }
+                    }
+                    vec![(*a, grad)]
+                },
+                Op::Sum(a) => {
+                    let input = &entries[*a].value;
+                    let g = grad.get(0, 0);
+                    vec![(*a, Matrix::create_matrix(input.rows, input.cols, vec![vec![g; input.cols]; input.rows]))]
+                },
+                Op::CrossEntropy(a, target) => {
+                    let prediction = &entries[*a].value;
+                    let scale = -grad.get(0, 0) / prediction.rows as f32;
+                    let mut d = Matrix::create_zero_matrix(prediction.rows, prediction.cols);
+                    for i in 0..prediction.rows {
+                        for j in 0..prediction.cols {
+                            d.set(i, j, scale * target.get(i, j) / prediction.get(i, j).max(f32::MIN_POSITIVE));
+                        }
+                    }
+                    vec![(*a, d)]
+                },
+                Op::MeanSquaredError(a, target) => {
+                    let prediction = &entries[*a].value;
+                    let scale = grad.get(0, 0) / prediction.rows as f32;
+                    let mut d = Matrix::create_zero_matrix(prediction.rows, prediction.cols);
+                    for i in 0..prediction.rows {
+                        for j in 0..prediction.cols {
+                            d.set(i, j, scale * (prediction.get(i, j) - target.get(i, j)));
+                        }
+                    }
+                    vec![(*a, d)]
+                },
+            };
+
+            for (parent, parent_grad) in parent_grads {
+                grads[parent] = Some(match grads[parent].take() {
+                    Some(acc) => acc.add(&parent_grad),
+                    None => parent_grad,
+                });
+            }
+        }
+
+        Gradients {grads}
+    }
+
+    fn with_values<F>(&self, other: &Var<'t>, func: F) -> Matrix
+    where F: FnOnce(&Matrix, &Matrix) -> Matrix {
+        let entries = self.tape.entries.borrow();
+        func(&entries[self.index].value, &entries[other.index].value)
+    }
+}
+
+impl Gradients {
+    // Variables that do not influence the output, including any recorded
+    // after it, get a gradient of zero.
+    pub fn get(&self, var: &Var) -> Matrix {
+        match self.grads.get(var.index) {
+            Some(Some(grad)) => grad.copy(),
+            _ => {
+                let value = var.value();
+                Matrix::create_zero_matrix(value.rows, value.cols)
+            }
+        }
+    }
+}
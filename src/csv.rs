@@ -0,0 +1,226 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use crate::dataset::*;
+use crate::prelude::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Column {
+    Index(usize),
+    Name(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MissingValues {
+    Error,
+    SkipRow,
+    Fill(f32),
+    ColumnMean,
+}
+
+#[derive(Clone, Debug)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    pub has_header: bool,
+    // Columns to keep, in output order. Empty keeps every column.
+    pub columns: Vec<Column>,
+    pub missing: MissingValues,
+    // Field values, besides the empty string, that count as missing.
+    pub missing_markers: Vec<String>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: ',',
+            has_header: false,
+            columns: Vec::new(),
+            missing: MissingValues::Error,
+            missing_markers: vec!["NA".to_string(), "NaN".to_string(), "?".to_string()],
+        }
+    }
+}
+
+// Row and column numbers in errors are 1-based and count the header line.
+impl DataSet {
+    pub fn from_csv<P: AsRef<Path>>(path: P, options: &CsvOptions) -> Result<DataSet> {
+        DataSet::read_csv(File::open(path)?, options)
+    }
+
+    pub fn read_csv<R: Read>(reader: R, options: &CsvOptions) -> Result<DataSet> {
+        let mut lines = BufReader::new(reader).lines().enumerate();
+        let mut header: Vec<String> = Vec::new();
+        if options.has_header {
+            if let Some((_, line)) = lines.next() {
+                header = split_fields(&line?, options.delimiter);
+            }
+        }
+
+        let mut selected: Vec<usize> = Vec::new();
+        for column in options.columns.iter() {
+            selected.push(match column {
+                Column::Index(index) => *index,
+                Column::Name(name) => match header.iter().position(|h| h == name) {
+                    Some(index) => index,
+                    None => return Err(Error::Parse {row: 1, column: 0, message: format!("no column named {:?}", name)}),
+                },
+            });
+        }
+
+        let mut width: Option<usize> = if options.has_header && !header.is_empty() { Some(header.len()) } else { None };
+        let mut data: Vec<Vec<Option<f32>>> = Vec::new();
+        for (line_idx, line) in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let row = line_idx + 1;
+            let fields = split_fields(&line, options.delimiter);
+            let expected = *width.get_or_insert(fields.len());
+            if fields.len() != expected {
+                return Err(Error::Parse {row, column: 0, message: format!("expected {} fields, found {}", expected, fields.len())});
+            }
+            if selected.is_empty() {
+                selected = (0..expected).collect();
+            }
+
+            let mut values: Vec<Option<f32>> = Vec::new();
+            for &index in selected.iter() {
+                let field = match fields.get(index) {
+                    Some(field) => field,
+                    None => return Err(Error::Parse {row, column: index + 1, message: format!("row has only {} fields", fields.len())}),
+                };
+                if field.is_empty() || options.missing_markers.iter().any(|m| m == field) {
+                    values.push(None);
+                } else {
+                    match field.parse::<f32>() {
+                        Ok(value) => values.push(Some(value)),
+                        Err(_) => return Err(Error::Parse {row, column: index + 1, message: format!("{:?} is not a number", field)}),
+                    }
+                }
+            }
+
+            if values.iter().any(|v| v.is_none()) {
+                match options.missing {
+                    MissingValues::Error => {
+                        let position = values.iter().position(|v| v.is_none()).unwrap();
+                        return Err(Error::Parse {row, column: selected[position] + 1, message: "missing value".to_string()});
+                    },
+                    MissingValues::SkipRow => continue,
+                    _ => {},
+                }
+            }
+            data.push(values);
+        }
+        if data.is_empty() {
+            return Err(Error::EmptyDataset);
+        }
+
+        let cols = selected.len();
+        let fill: Vec<f32> = match options.missing {
+            MissingValues::Fill(value) => vec![value; cols],
+            _ => (0..cols).map(|j| {
+                let present: Vec<f32> = data.iter().filter_map(|row| row[j]).collect();
+                if present.is_empty() { 0.0 } else { present.iter().sum::<f32>() / present.len() as f32 }
+            }).collect(),
+        };
+
+        let data: Vec<Vec<f32>> = data.into_iter()
+            .map(|row| row.into_iter().enumerate().map(|(j, v)| v.unwrap_or(fill[j])).collect())
+            .collect();
+        Ok(create_dataset(data.len(), cols, data))
+    }
+
+    // Reads a CSV file and splits it into a feature set and a target set.
+    // The target columns keep the order given in `targets`.
+    pub fn from_csv_split<P: AsRef<Path>>(path: P, options: &CsvOptions, targets: &[Column]) -> Result<(DataSet, DataSet)> {
+        DataSet::read_csv_split(File::open(path)?, options, targets)
+    }
+
+    pub fn read_csv_split<R: Read>(reader: R, options: &CsvOptions, targets: &[Column]) -> Result<(DataSet, DataSet)> {
+        let mut text = String::new();
+        BufReader::new(reader).read_to_string(&mut text)?;
+
+        let header: Vec<String> = match text.lines().next() {
+            Some(line) if options.has_header => split_fields(line, options.delimiter),
+            _ => Vec::new(),
+        };
+        let width = text.lines().find(|line| !line.trim().is_empty()).map(|line| split_fields(line, options.delimiter).len()).unwrap_or(0);
+        let resolve = |column: &Column| -> Result<usize> {
+            match column {
+                Column::Index(index) => Ok(*index),
+                Column::Name(name) => header.iter().position(|h| h == name)
+                    .ok_or_else(|| Error::Parse {row: 1, column: 0, message: format!("no column named {:?}", name)}),
+            }
+        };
+
+        let target_indices = targets.iter().map(resolve).collect::<Result<Vec<usize>>>()?;
+        let feature_indices: Vec<usize> = if options.columns.is_empty() {
+            (0..width).filter(|index| !target_indices.contains(index)).collect()
+        } else {
+            options.columns.iter().map(resolve).collect::<Result<Vec<usize>>>()?
+                .into_iter().filter(|index| !target_indices.contains(index)).collect()
+        };
+
+        let mut all = options.clone();
+        all.columns = feature_indices.iter().chain(target_indices.iter()).map(|&index| Column::Index(index)).collect();
+        let combined = DataSet::read_csv(text.as_bytes(), &all)?;
+
+        let num_features = feature_indices.len();
+        let features = combined.data.iter().map(|row| row[..num_features].to_vec()).collect();
+        let classes = combined.data.iter().map(|row| row[num_features..].to_vec()).collect();
+        Ok((
+            create_dataset(combined.rows, num_features, features),
+            create_dataset(combined.rows, target_indices.len(), classes),
+        ))
+    }
+
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P, delimiter: char, header: Option<&[String]>) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_csv(&mut writer, delimiter, header)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn write_csv<W: Write>(&self, writer: &mut W, delimiter: char, header: Option<&[String]>) -> Result<()> {
+        let separator = delimiter.to_string();
+        if let Some(header) = header {
+            if header.len() != self.cols {
+                return Err(Error::ShapeMismatch {expected: (1, self.cols), found: (1, header.len())});
+            }
+            writeln!(writer, "{}", header.join(&separator))?;
+        }
+        for row in self.data.iter() {
+            let fields: Vec<String> = row.iter().map(|x| x.to_string()).collect();
+            writeln!(writer, "{}", fields.join(&separator))?;
+        }
+
+        Ok(())
+    }
+}
+
+// Splits on `delimiter` outside double quotes. The quotes are dropped, and
+// a doubled quote inside a quoted field stands for one quote.
+pub(crate) fn split_fields(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '"' {
+            if quoted && chars.peek() == Some(&'"') {
+                field.push('"');
+                chars.next();
+            } else {
+                quoted = !quoted;
+            }
+        } else if c == delimiter && !quoted {
+            fields.push(field.trim().to_string());
+            field.clear();
+        } else {
+            field.push(c);
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
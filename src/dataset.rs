@@ -1,12 +1,13 @@
+use rand::distributions::WeightedIndex;
 use rand::seq::SliceRandom;
 use rand::Rng;
 use crate::prelude::*;
 
-#[derive(Debug, Clone)]
-pub struct DataSet {
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataSet<T = f32> {
     pub rows: usize,
     pub cols: usize,
-    pub data: Vec<Vec<f32>>,
+    pub data: Vec<Vec<T>>,
 }
 
 #[derive(Debug, Clone)]
@@ -26,7 +27,37 @@ pub fn create_dataset(rows: usize, cols: usize, data: Vec<Vec<f32>>) -> DataSet
     DataSet {rows, cols, data}
 }
 
-pub fn create_batches(dataset: &DataSet, num_batches: usize) -> Vec<Batch> {
+pub fn one_hot(labels: &[usize], num_classes: usize) -> DataSet {
+    let mut data = vec![vec![0.0; num_classes]; labels.len()];
+    for (row, &label) in data.iter_mut().zip(labels.iter()) {
+        assert!(label < num_classes);
+        row[label] = 1.0;
+    }
+
+    create_dataset(labels.len(), num_classes, data)
+}
+
+// Index of the largest entry of a class row.
+pub fn class_index<T: Float>(row: &[T]) -> usize {
+    let mut max = 0;
+    for j in 1..row.len() {
+        if row[j] > row[max] {
+            max = j;
+        }
+    }
+    max
+}
+
+pub fn class_indices<T: Float>(classes: &DataSet<T>) -> Vec<usize> {
+    classes.data.iter().map(|row| class_index(row)).collect()
+}
+
+pub fn select_rows<T: Float>(dataset: &DataSet<T>, indices: &[usize]) -> DataSet<T> {
+    let data = indices.iter().map(|&i| dataset.data[i].clone()).collect();
+    DataSet {rows: indices.len(), cols: dataset.cols, data}
+}
+
+pub fn create_batches(dataset: &DataSet, num_batches: usize) -> Vec<Batch<'_>> {
     let rows = dataset.rows;
     let mut remainder = rows % num_batches;
     let mut offset = 0;
@@ -41,9 +72,7 @@ pub fn create_batches(dataset: &DataSet, num_batches: usize) -> Vec<Batch> {
         batches.push(Batch{offset, size, dataset});
         offset += size;
     
-        if remainder > 0 {
-            remainder -= 1;
-        }
+        remainder = remainder.saturating_sub(1);
     }
     
     batches
@@ -52,26 +81,222 @@ pub fn create_batches(dataset: &DataSet, num_batches: usize) -> Vec<Batch> {
 pub fn split_rows<'a>(batch: &'a Batch<'a>) -> Vec<Row<'a>> {
     let mut rows: Vec<Row> = Vec::new();
     
-    for i in 0..batch.dataset.rows {
+    for i in batch.offset..batch.offset + batch.size {
         rows.push(Row{row_idx: i, batch});
     }
 
     rows
 }
 
+impl<'a> Batch<'a> {
+    // The batch's rows, borrowed from the dataset.
+    pub fn rows(&self) -> &'a [Vec<f32>] {
+        &self.dataset.data[self.offset..self.offset + self.size]
+    }
+
+    pub fn to_dataset(&self) -> DataSet {
+        create_dataset(self.size, self.dataset.cols, self.rows().to_vec())
+    }
+}
+
+// The row indices 0..rows in a uniformly random order.
+pub fn permutation<R: Rng + ?Sized>(rows: usize, rng: &mut R) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..rows).collect();
+    indices.shuffle(rng);
+    indices
+}
+
 pub fn shuffle_together(data_a: &mut DataSet, data_b: &mut DataSet) {
+    shuffle_together_with(data_a, data_b, &mut rand::thread_rng());
+}
+
+pub fn shuffle_together_with<R: Rng + ?Sized>(data_a: &mut DataSet, data_b: &mut DataSet, rng: &mut R) {
     assert!(data_a.rows == data_b.rows);
 
-    let mut rng = rand::thread_rng();
-    let mut permutation: Vec<usize> = vec![0; data_a.rows];
-    for i in 0..data_a.rows {
-        permutation[i] = rng.gen_range(0..=data_a.rows-1);
+    let order = permutation(data_a.rows, rng);
+    let mut rows_a: Vec<Option<Vec<f32>>> = data_a.data.drain(..).map(Some).collect();
+    let mut rows_b: Vec<Option<Vec<f32>>> = data_b.data.drain(..).map(Some).collect();
+    for &i in order.iter() {
+        data_a.data.push(rows_a[i].take().unwrap());
+        data_b.data.push(rows_b[i].take().unwrap());
     }
-    permutation.shuffle(&mut rng);
-    
-    for i in 0..data_a.rows {
-        let j = permutation[i];
-        data_a.data.swap(i, j);
-        data_b.data.swap(i, j);
+}
+
+// Weights inversely proportional to class frequency, scaled so that every
+// row has weight 1 when the classes are balanced. Absent classes get 0.
+pub fn balanced_class_weights<T: Float>(classes: &DataSet<T>) -> Vec<f32> {
+    let mut counts = vec![0usize; classes.cols];
+    for class in class_indices(classes) {
+        counts[class] += 1;
+    }
+    let present = counts.iter().filter(|&&c| c > 0).count();
+    counts.iter().map(|&count| {
+        if count == 0 { 0.0 } else { classes.rows as f32 / (present * count) as f32 }
+    }).collect()
+}
+
+// The weight of each row's class.
+pub fn class_sample_weights<T: Float>(classes: &DataSet<T>, class_weights: &[f32]) -> Vec<f32> {
+    assert!(class_weights.len() == classes.cols);
+    class_indices(classes).into_iter().map(|class| class_weights[class]).collect()
+}
+
+// `count` row indices drawn with replacement, each with probability
+// proportional to its weight. The weights must be finite, non-negative and
+// not all zero.
+pub fn weighted_sample<R: Rng + ?Sized>(weights: &[f32], count: usize, rng: &mut R) -> Result<Vec<usize>> {
+    if weights.iter().any(|w| !w.is_finite()) {
+        return Err(Error::InvalidConfig("sampling weights must be finite".to_string()));
     }
-}
\ No newline at end of file
+    let distribution = WeightedIndex::new(weights)
+        .map_err(|e| Error::InvalidConfig(format!("unusable sampling weights: {}", e)))?;
+    Ok((0..count).map(|_| rng.sample(&distribution)).collect())
+}
+
+// `count` row indices drawn with replacement so that every class present
+// in `classes` is equally likely, oversampling the rare ones.
+pub fn balanced_sample<T: Float, R: Rng + ?Sized>(classes: &DataSet<T>, count: usize, rng: &mut R) -> Result<Vec<usize>> {
+    weighted_sample(&class_sample_weights(classes, &balanced_class_weights(classes)), count, rng)
+}
+
+// How the rows of an in-memory data set are ordered each epoch. The
+// weighted samplers draw as many rows as the data set holds.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Sampler {
+    Sequential,
+    Shuffled,
+    Weighted(Vec<f32>),
+    Balanced,
+}
+
+impl Sampler {
+    pub fn order<T: Float, R: Rng + ?Sized>(&self, targets: &DataSet<T>, rng: &mut R) -> Result<Vec<usize>> {
+        match self {
+            Sampler::Sequential => Ok((0..targets.rows).collect()),
+            Sampler::Shuffled => Ok(permutation(targets.rows, rng)),
+            Sampler::Weighted(weights) => {
+                if weights.len() != targets.rows {
+                    return Err(Error::ShapeMismatch {expected: (targets.rows, 1), found: (weights.len(), 1)});
+                }
+                weighted_sample(weights, targets.rows, rng)
+            },
+            Sampler::Balanced => balanced_sample(targets, targets.rows, rng),
+        }
+    }
+}
+
+pub fn num_batches(rows: usize, batch_size: usize, drop_last: bool) -> usize {
+    assert!(batch_size > 0);
+    if drop_last { rows / batch_size } else { rows.div_ceil(batch_size) }
+}
+
+// Borrowed rows of one mini-batch, in the order they were drawn.
+pub struct BatchView<'a> {
+    pub indices: &'a [usize],
+    features: &'a DataSet,
+    targets: &'a DataSet,
+}
+
+impl<'a> BatchView<'a> {
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn feature_row(&self, i: usize) -> &'a [f32] {
+        &self.features.data[self.indices[i]]
+    }
+
+    pub fn target_row(&self, i: usize) -> &'a [f32] {
+        &self.targets.data[self.indices[i]]
+    }
+
+    // Copies the batch's feature rows into a new data set.
+    pub fn features(&self) -> DataSet {
+        select_rows(self.features, self.indices)
+    }
+
+    pub fn targets(&self) -> DataSet {
+        select_rows(self.targets, self.indices)
+    }
+}
+
+// Walks features and targets together in mini-batches following one
+// epoch's row order. With `drop_last` a final short batch is skipped.
+pub struct BatchIter<'a> {
+    features: &'a DataSet,
+    targets: &'a DataSet,
+    order: Vec<usize>,
+    batch_size: usize,
+    drop_last: bool,
+    position: usize,
+}
+
+pub fn create_batch_iter<'a>(features: &'a DataSet, targets: &'a DataSet, batch_size: usize, drop_last: bool) -> BatchIter<'a> {
+    create_ordered_batch_iter(features, targets, batch_size, drop_last, (0..features.rows).collect())
+}
+
+// Draws a fresh permutation, so call once per epoch.
+pub fn create_shuffled_batch_iter<'a, R: Rng + ?Sized>(
+    features: &'a DataSet,
+    targets: &'a DataSet,
+    batch_size: usize,
+    drop_last: bool,
+    rng: &mut R) -> BatchIter<'a> {
+
+    create_ordered_batch_iter(features, targets, batch_size, drop_last, permutation(features.rows, rng))
+}
+
+pub fn create_ordered_batch_iter<'a>(
+    features: &'a DataSet,
+    targets: &'a DataSet,
+    batch_size: usize,
+    drop_last: bool,
+    order: Vec<usize>) -> BatchIter<'a> {
+
+    assert!(features.rows == targets.rows);
+    assert!(batch_size > 0);
+    assert!(order.iter().all(|&i| i < features.rows));
+    BatchIter {features, targets, order, batch_size, drop_last, position: 0}
+}
+
+impl<'a> BatchIter<'a> {
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    pub fn num_batches(&self) -> usize {
+        num_batches(self.order.len(), self.batch_size, self.drop_last)
+    }
+
+    // The next batch as borrowed rows. Views borrow the iterator's row order,
+    // so they come from this method rather than through `Iterator`.
+    pub fn next_view(&mut self) -> Option<BatchView<'_>> {
+        let (start, end) = self.next_range()?;
+        Some(BatchView {indices: &self.order[start..end], features: self.features, targets: self.targets})
+    }
+
+    fn next_range(&mut self) -> Option<(usize, usize)> {
+        let start = self.position;
+        let end = (start + self.batch_size).min(self.order.len());
+        if start >= end || (self.drop_last && end - start < self.batch_size) {
+            return None;
+        }
+        self.position = end;
+        Some((start, end))
+    }
+}
+
+// Owned (features, targets) pairs, one per batch.
+impl<'a> Iterator for BatchIter<'a> {
+    type Item = (DataSet, DataSet);
+
+    fn next(&mut self) -> Option<(DataSet, DataSet)> {
+        let (start, end) = self.next_range()?;
+        let indices = &self.order[start..end];
+        Some((select_rows(self.features, indices), select_rows(self.targets, indices)))
+    }
+}
@@ -3,4 +3,21 @@
 pub enum Error {
     #[error("Generic {0}")]
     Generic(String),
-}
\ No newline at end of file
+    #[error("Training diverged at epoch {epoch}, batch {batch}: {reason}")]
+    Diverged { epoch: usize, batch: usize, reason: String },
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Parse error at row {row}, column {column}: {message}")]
+    Parse { row: usize, column: usize, message: String },
+    // Shapes are (rows, cols).
+    #[error("Shape mismatch: expected {}x{}, found {}x{}", expected.0, expected.1, found.0, found.1)]
+    ShapeMismatch { expected: (usize, usize), found: (usize, usize) },
+    #[error("Dataset is empty")]
+    EmptyDataset,
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+    #[error("Matrix is singular")]
+    Singular,
+    #[error("Matrix is not positive definite")]
+    NotPositiveDefinite,
+}
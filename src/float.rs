@@ -0,0 +1,82 @@
+use std::fmt::{Debug, Display};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use crate::function::*;
+
+// The element type of a `Matrix` and of everything built from one. Implemented
+// for f32, the default everywhere, and f64 for gradient checks and models that
+// need the extra precision.
+//
+// Matrices, linear algebra, `Network` training and inference, the streaming
+// sources and the .npy/.npz writers accept either type. The file readers
+// (CSV, IDX, LIBSVM and `DataSet::from_npy`/`from_npz`), `BatchIter`,
+// preprocessing, validation, graph networks, the autograd tape and the
+// attention and transformer blocks are f32 only; convert their results with
+// `cast` or `NpyArray::to_dataset_as`.
+pub trait Float:
+    Copy + Debug + Display + Default + PartialEq + PartialOrd + Send + Sync + 'static
+    + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self> + Neg<Output = Self>
+    + AddAssign + SubAssign + MulAssign + DivAssign + Sum {
+
+    const ZERO: Self;
+    const ONE: Self;
+    const INFINITY: Self;
+    const NEG_INFINITY: Self;
+    const MIN_POSITIVE: Self;
+    const EPSILON: Self;
+
+    fn from_f32(value: f32) -> Self;
+    fn from_f64(value: f64) -> Self;
+    fn from_usize(value: usize) -> Self;
+    fn to_f32(self) -> f32;
+    fn to_f64(self) -> f64;
+
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn tanh(self) -> Self;
+    fn abs(self) -> Self;
+    fn max(self, other: Self) -> Self;
+    fn min(self, other: Self) -> Self;
+    fn clamp(self, min: Self, max: Self) -> Self;
+    fn is_finite(self) -> bool;
+
+    // The built-in activations for this type by name, ending with linear.
+    fn activations() -> [(&'static str, Activation<Self>); 5];
+}
+
+macro_rules! impl_float {
+    ($t:ident, $activations:expr) => {
+        impl Float for $t {
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+            const INFINITY: Self = $t::INFINITY;
+            const NEG_INFINITY: Self = $t::NEG_INFINITY;
+            const MIN_POSITIVE: Self = $t::MIN_POSITIVE;
+            const EPSILON: Self = $t::EPSILON;
+
+            fn from_f32(value: f32) -> Self { value as $t }
+            fn from_f64(value: f64) -> Self { value as $t }
+            fn from_usize(value: usize) -> Self { value as $t }
+            fn to_f32(self) -> f32 { self as f32 }
+            fn to_f64(self) -> f64 { self as f64 }
+
+            fn exp(self) -> Self { $t::exp(self) }
+            fn ln(self) -> Self { $t::ln(self) }
+            fn sqrt(self) -> Self { $t::sqrt(self) }
+            fn tanh(self) -> Self { $t::tanh(self) }
+            fn abs(self) -> Self { $t::abs(self) }
+            fn max(self, other: Self) -> Self { $t::max(self, other) }
+            fn min(self, other: Self) -> Self { $t::min(self, other) }
+            fn clamp(self, min: Self, max: Self) -> Self { $t::clamp(self, min, max) }
+            fn is_finite(self) -> bool { $t::is_finite(self) }
+
+            fn activations() -> [(&'static str, Activation<Self>); 5] {
+                $activations
+            }
+        }
+    };
+}
+
+impl_float!(f32, [("sigmoid", sigmoid), ("relu", relu), ("tanh", tanh), ("softmax", softmax), ("linear", linear)]);
+impl_float!(f64, [("sigmoid", double::sigmoid), ("relu", double::relu), ("tanh", double::tanh), ("softmax", double::softmax), ("linear", double::linear)]);
@@ -2,53 +2,57 @@ use rand::Rng;
 use crate::matrix::*;
 use crate::prelude::*;
 
-pub type Activation = fn(Rc<RefCell<Matrix>>);
+pub type Activation<T = f32> = fn(Rc<RefCell<Matrix<T>>>);
 
-pub fn sigmoid_func(input: f32) -> f32 {
-    1.0 / (1.0 + (-1.0 * input).exp())
+pub fn sigmoid_func<T: Float>(input: T) -> T {
+    T::ONE / (T::ONE + (-input).exp())
 }
 
-pub fn sigmoid_deriv(sigmoid_input: f32) -> f32 {
-    sigmoid_input * (1.0 - sigmoid_input)
+pub fn sigmoid_deriv<T: Float>(sigmoid_input: T) -> T {
+    sigmoid_input * (T::ONE - sigmoid_input)
 }
 
-pub fn relu_func(input: f32) -> f32 {
-    input.max(0.0)
+pub fn relu_func<T: Float>(input: T) -> T {
+    input.max(T::ZERO)
 }
 
-pub fn relu_deriv(relu_input: f32) -> f32 {
-    if relu_input > 0.0 { 1.0 } else { 0.0 }
+pub fn relu_deriv<T: Float>(relu_input: T) -> T {
+    if relu_input > T::ZERO { T::ONE } else { T::ZERO }
 }
 
 pub fn sigmoid(input: Rc<RefCell<Matrix>>) {
-    input.borrow_mut().transform(|x| sigmoid_func(x));
+    input.borrow_mut().transform(sigmoid_func);
 }
 
-pub fn tanh_func(input: f32) -> f32 {
+pub fn tanh_func<T: Float>(input: T) -> T {
     input.tanh()
 }
 
-pub fn tanh_deriv(tanh_input: f32) -> f32 {
-    1.0 - (tanh_input * tanh_input)
+pub fn tanh_deriv<T: Float>(tanh_input: T) -> T {
+    T::ONE - (tanh_input * tanh_input)
 }
 
 pub fn relu(input: Rc<RefCell<Matrix>>) {
-    input.borrow_mut().transform(|x| relu_func(x));
+    input.borrow_mut().transform(relu_func);
 }
 
 pub fn tanh(input: Rc<RefCell<Matrix>>) {
-    input.borrow_mut().transform(|x| tanh_func(x));
+    input.borrow_mut().transform(tanh_func);
 }
 
 pub fn softmax(input: Rc<RefCell<Matrix>>) {
-    let mut matrix = input.borrow_mut();
+    softmax_rows(&mut input.borrow_mut());
+}
+
+fn softmax_rows<T: Float>(matrix: &mut Matrix<T>) {
     for i in 0..matrix.rows {
-        let mut summed = 0.0;
+        let max = matrix.data[i].iter().cloned().fold(T::NEG_INFINITY, T::max);
+        let mut summed = T::ZERO;
         for j in 0..matrix.cols {
-            summed += matrix.get(i, j).exp();
+            summed += (matrix.get(i, j) - max).exp();
         }
         for j in 0..matrix.cols {
-            let val = matrix.get(i, j).exp() / summed;
+            let val = (matrix.get(i, j) - max).exp() / summed;
             matrix.set(i, j, val);
         }
     }
@@ -56,12 +60,36 @@ pub fn softmax(input: Rc<RefCell<Matrix>>) {
 
 pub fn linear(input: Rc<RefCell<Matrix>>) {}
 
-pub fn linear_deriv(_linear_input: f32) -> f32 {
-    1.0
+// The same activations for f64 networks. Each float type has its own
+// functions because activations are told apart by address.
+pub mod double {
+    use super::*;
+
+    pub fn sigmoid(input: Rc<RefCell<Matrix<f64>>>) {
+        input.borrow_mut().transform(sigmoid_func);
+    }
+
+    pub fn relu(input: Rc<RefCell<Matrix<f64>>>) {
+        input.borrow_mut().transform(relu_func);
+    }
+
+    pub fn tanh(input: Rc<RefCell<Matrix<f64>>>) {
+        input.borrow_mut().transform(tanh_func);
+    }
+
+    pub fn softmax(input: Rc<RefCell<Matrix<f64>>>) {
+        softmax_rows(&mut input.borrow_mut());
+    }
+
+    pub fn linear(input: Rc<RefCell<Matrix<f64>>>) {}
+}
+
+pub fn linear_deriv<T: Float>(_linear_input: T) -> T {
+    T::ONE
 }
 
 pub fn box_muller(x: f32) -> f32 {
-    const EPSILON: f32 = f32::MIN;
+    const EPSILON: f32 = f32::MIN_POSITIVE;
     const TWO_PI: f32 = 2.0 * std::f32::consts::PI;
     static mut Z0: f32 = 0.0;
     static mut Z1: f32 = 0.0;
@@ -75,8 +103,8 @@ pub fn box_muller(x: f32) -> f32 {
         let mut u1;
         let mut u2;
         loop {
-            u1 = rand::random::<f32>() * (1.0 / f32::MAX);
-            u2 = rand::random::<f32>() * (1.0 / f32::MAX);
+            u1 = rand::random::<f32>();
+            u2 = rand::random::<f32>();
             if u1 > EPSILON {
                 break;
             }
@@ -88,31 +116,96 @@ pub fn box_muller(x: f32) -> f32 {
     }
 }
 
-pub fn get_function_name(func: Activation) -> String {
+// The name of a built-in activation, or None for a custom one. Every
+// name lookup goes through this and `find_function_by_name`, which share
+// the table in `Float::activations`.
+pub fn find_function_name<T: Float>(func: Activation<T>) -> Option<&'static str> {
+    T::activations().iter()
+        .find(|(_, activation)| std::ptr::fn_addr_eq(func, *activation))
+        .map(|(name, _)| *name)
+}
+
+pub fn find_function_by_name<T: Float>(name: &str) -> Option<Activation<T>> {
+    T::activations().iter()
+        .find(|(activation_name, _)| *activation_name == name)
+        .map(|(_, activation)| *activation)
+}
+
+pub fn get_function_name<T: Float>(func: Activation<T>) -> String {
+    function_name(func).to_string()
+}
+
+// Like `get_function_name`, without allocating. Custom activations are "linear".
+pub fn function_name<T: Float>(func: Activation<T>) -> &'static str {
+    find_function_name(func).unwrap_or("linear")
+}
+
+pub fn get_function_by_name<T: Float>(name: &str) -> Activation<T> {
+    let activations = T::activations();
+    find_function_by_name(name).unwrap_or(activations[activations.len() - 1].1)
+}
+
+// Derivatives take the activated value. Softmax maps to the linear derivative
+// because its gradient is folded into the cross-entropy error.
+pub fn activation_derivative<T: Float>(func: Option<Activation<T>>) -> fn(T) -> T {
+    match func.map(function_name) {
+        Some("sigmoid") => sigmoid_deriv,
+        Some("relu") => relu_deriv,
+        Some("tanh") => tanh_deriv,
+        _ => linear_deriv,
+    }
+}
+
+pub fn apply_activation<T: Float>(func: Option<Activation<T>>, input: Matrix<T>) -> Matrix<T> {
     match func {
-        sigmoid => "sigmoid".to_string(),
-        relu => "relu".to_string(),
-        tanh => "tanh".to_string(),
-        softmax => "softmax".to_string(),
-        _ => "linear".to_string(),
+        None => input,
+        Some(activation) => {
+            let cell = Rc::new(RefCell::new(input));
+            activation(cell.clone());
+            Rc::try_unwrap(cell).map(RefCell::into_inner).unwrap_or_else(|cell| cell.borrow().copy())
+        }
     }
 }
 
-pub fn get_function_by_name(name: &str) -> Activation {
-    match name {
-        "sigmoid" => sigmoid,
-        "relu" => relu,
-        "tanh" => tanh,
-        "softmax" => softmax,
-        _ => linear,
+// Like `apply_activation`, but works on a borrowed matrix. The built-in
+// activations run directly on it; custom ones still go through an `Rc`.
+pub fn activate_in_place<T: Float>(func: Option<Activation<T>>, matrix: &mut Matrix<T>) {
+    let Some(activation) = func else {
+        return;
+    };
+    match find_function_name(activation) {
+        Some("sigmoid") => matrix.transform(sigmoid_func),
+        Some("relu") => matrix.transform(relu_func),
+        Some("tanh") => matrix.transform(tanh_func),
+        Some("softmax") => softmax_rows(matrix),
+        Some(_) => {},
+        None => {
+            let empty = Matrix {rows: 0, cols: 0, data: Vec::new()};
+            let cell = Rc::new(RefCell::new(std::mem::replace(matrix, empty)));
+            activation(cell.clone());
+            *matrix = cell.replace(Matrix {rows: 0, cols: 0, data: Vec::new()});
+        }
     }
 }
 
-pub fn activation_derivative(func: Option<Activation>) -> fn(f32) -> f32 {
-    match func.unwrap() {
-        sigmoid => sigmoid_deriv,
-        relu => relu_deriv,
-        tanh => tanh_deriv,
-        _ => linear_deriv,
+// Gradient w.r.t. the pre-activation given the activated output and the gradient w.r.t. it.
+pub fn activation_backward<T: Float>(activation: Option<Activation<T>>, output: &Matrix<T>, d_output: &Matrix<T>) -> Matrix<T> {
+    let mut d_pre = d_output.copy();
+    if activation.map(function_name) == Some("softmax") {
+        for i in 0..output.rows {
+            let dot: T = (0..output.cols).map(|j| d_output.get(i, j) * output.get(i, j)).sum();
+            for j in 0..output.cols {
+                d_pre.set(i, j, output.get(i, j) * (d_output.get(i, j) - dot));
+            }
+        }
+    } else {
+        let derivative = activation_derivative(activation);
+        for i in 0..output.rows {
+            for j in 0..output.cols {
+                d_pre.set(i, j, d_output.get(i, j) * derivative(output.get(i, j)));
+            }
+        }
     }
+
+    d_pre
 }
@@ -0,0 +1,270 @@
+use crate::matrix::*;
+use crate::prelude::*;
+use crate::function::*;
+use crate::layer::*;
+
+pub type NodeId = usize;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Merge {
+    Add,
+    Concatenate,
+    Multiply
+}
+
+#[derive(Clone)]
+pub enum NodeSpec {
+    Input(usize),
+    Dense(usize, Option<Activation>),
+    Merge(Merge)
+}
+
+pub enum NodeKind {
+    Input,
+    Dense(Box<Linear>, Option<Activation>),
+    Merge(Merge)
+}
+
+pub struct Node {
+    pub kind: NodeKind,
+    pub inputs: Vec<NodeId>,
+    pub size: usize,
+    pub output: Matrix
+}
+
+pub struct GraphBuilder {
+    specs: Vec<(NodeSpec, Vec<NodeId>)>
+}
+
+pub struct GraphNetwork {
+    nodes: Vec<Node>,
+    order: Vec<NodeId>,
+    inputs: Vec<NodeId>,
+    output: NodeId
+}
+
+pub fn create_graph_builder() -> GraphBuilder {
+    GraphBuilder {specs: Vec::new()}
+}
+
+impl GraphBuilder {
+    pub fn add_node(&mut self, spec: NodeSpec, inputs: Vec<NodeId>) -> NodeId {
+        self.specs.push((spec, inputs));
+        self.specs.len() - 1
+    }
+
+    pub fn input(&mut self, size: usize) -> NodeId {
+        self.add_node(NodeSpec::Input(size), Vec::new())
+    }
+
+    pub fn dense(&mut self, input: NodeId, size: usize, activation: Option<Activation>) -> NodeId {
+        self.add_node(NodeSpec::Dense(size, activation), vec![input])
+    }
+
+    pub fn merge(&mut self, merge: Merge, inputs: Vec<NodeId>) -> NodeId {
+        self.add_node(NodeSpec::Merge(merge), inputs)
+    }
+
+    // Sorts the nodes topologically and checks that every edge joins compatible shapes.
+    pub fn build(self, output: NodeId) -> Result<GraphNetwork> {
+        let num_nodes = self.specs.len();
+        if output >= num_nodes {
+            return Err(Error::InvalidConfig(format!("output node {} does not exist", output)));
+        }
+
+        let mut consumers: Vec<Vec<NodeId>> = vec![Vec::new(); num_nodes];
+        let mut pending: Vec<usize> = vec![0; num_nodes];
+        for (id, (spec, inputs)) in self.specs.iter().enumerate() {
+            let expected = match spec {
+                NodeSpec::Input(_) => inputs.is_empty(),
+                NodeSpec::Dense(_, _) => inputs.len() == 1,
+                NodeSpec::Merge(_) => inputs.len() >= 2,
+            };
+            if !expected {
+                return Err(Error::InvalidConfig(format!("node {} has {} inputs", id, inputs.len())));
+            }
+            for &input in inputs.iter() {
+                if input >= num_nodes {
+                    return Err(Error::InvalidConfig(format!("node {} reads from missing node {}", id, input)));
+                }
+                consumers[input].push(id);
+                pending[id] += 1;
+            }
+        }
+
+        let mut order: Vec<NodeId> = Vec::new();
+        let mut ready: Vec<NodeId> = (0..num_nodes).filter(|&id| pending[id] == 0).rev().collect();
+        while let Some(id) = ready.pop() {
+            order.push(id);
+            for &consumer in consumers[id].iter().rev() {
+                pending[consumer] -= 1;
+                if pending[consumer] == 0 {
+                    ready.push(consumer);
+                }
+            }
+        }
+        if order.len() != num_nodes {
+            return Err(Error::InvalidConfig("graph contains a cycle".to_string()));
+        }
+
+        let mut sizes: Vec<usize> = vec![0; num_nodes];
+        for &id in order.iter() {
+            let (spec, inputs) = &self.specs[id];
+            sizes[id] = match spec {
+                NodeSpec::Input(size) | NodeSpec::Dense(size, _) if *size == 0 => {
+                    return Err(Error::InvalidConfig(format!("node {} has size 0", id)));
+                },
+                NodeSpec::Input(size) | NodeSpec::Dense(size, _) => *size,
+                NodeSpec::Merge(Merge::Concatenate) => inputs.iter().map(|&input| sizes[input]).sum(),
+                NodeSpec::Merge(merge) => {
+                    let size = sizes[inputs[0]];
+                    if let Some(&bad) = inputs.iter().find(|&&input| sizes[input] != size) {
+                        return Err(Error::InvalidConfig(format!(
+                            "{:?} node {} expects inputs of size {} but node {} has size {}",
+                            merge, id, size, bad, sizes[bad])));
+                    }
+                    size
+                }
+            };
+        }
+
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut graph_inputs: Vec<NodeId> = Vec::new();
+        for (id, (spec, inputs)) in self.specs.into_iter().enumerate() {
+            let kind = match spec {
+                NodeSpec::Input(_) => {
+                    graph_inputs.push(id);
+                    NodeKind::Input
+                },
+                NodeSpec::Dense(size, activation) => NodeKind::Dense(Box::new(create_linear(sizes[inputs[0]], size)), activation),
+                NodeSpec::Merge(merge) => NodeKind::Merge(merge),
+            };
+            nodes.push(Node {kind, inputs, size: sizes[id], output: Matrix::create_zero_matrix(1, sizes[id])});
+        }
+
+        Ok(GraphNetwork {nodes, order, inputs: graph_inputs, output})
+    }
+}
+
+impl GraphNetwork {
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub fn input_nodes(&self) -> &[NodeId] {
+        &self.inputs
+    }
+
+    pub fn output_node(&self) -> NodeId {
+        self.output
+    }
+
+    // `inputs` are given in the order the input nodes were added to the builder.
+    pub fn forward_pass(&mut self, inputs: &[&Matrix]) -> Matrix {
+        assert!(inputs.len() == self.inputs.len());
+        let rows = inputs[0].rows;
+        for (k, &id) in self.inputs.iter().enumerate() {
+            assert!(inputs[k].rows == rows && inputs[k].cols == self.nodes[id].size);
+            self.nodes[id].output = inputs[k].copy();
+        }
+
+        for idx in 0..self.order.len() {
+            let id = self.order[idx];
+            let output = {
+                let node = &self.nodes[id];
+                let first = &self.nodes[node.inputs.first().copied().unwrap_or(id)].output;
+                match &node.kind {
+                    NodeKind::Input => continue,
+                    NodeKind::Dense(_, _) => first.copy(),
+                    NodeKind::Merge(Merge::Add) => node.inputs[1..].iter().fold(first.copy(), |acc, &input| acc.add(&self.nodes[input].output)),
+                    NodeKind::Merge(Merge::Multiply) => node.inputs[1..].iter().fold(first.copy(), |acc, &input| acc.hadamard(&self.nodes[input].output)),
+                    NodeKind::Merge(Merge::Concatenate) => node.inputs[1..].iter().fold(first.copy(), |acc, &input| acc.concat_cols(&self.nodes[input].output)),
+                }
+            };
+            let node = &mut self.nodes[id];
+            node.output = match &mut node.kind {
+                NodeKind::Dense(linear, activation) => apply_activation(*activation, linear.forward(&output)),
+                _ => output,
+            };
+        }
+
+        self.nodes[self.output].output.copy()
+    }
+
+    // Back-propagates the gradient of the loss w.r.t. the output node and
+    // accumulates parameter gradients. Returns the gradients w.r.t. each graph input.
+    pub fn backward_pass(&mut self, d_output: &Matrix) -> Vec<Matrix> {
+        let out = &self.nodes[self.output].output;
+        assert!(d_output.rows == out.rows && d_output.cols == out.cols);
+        let mut grads: Vec<Option<Matrix>> = (0..self.nodes.len()).map(|_| None).collect();
+        grads[self.output] = Some(d_output.copy());
+
+        for idx in (0..self.order.len()).rev() {
+            let id = self.order[idx];
+            let grad = match grads[id].take() {
+                Some(grad) => grad,
+                None => continue,
+            };
+            let input_grads: Vec<Matrix> = {
+                let node = &mut self.nodes[id];
+                match &mut node.kind {
+                    NodeKind::Input => {
+                        grads[id] = Some(grad);
+                        continue;
+                    },
+                    NodeKind::Dense(linear, activation) => {
+                        let d_pre = activation_backward(*activation, &node.output, &grad);
+                        vec![linear.backward(&d_pre)]
+                    },
+                    NodeKind::Merge(Merge::Add) => node.inputs.iter().map(|_| grad.copy()).collect(),
+                    NodeKind::Merge(Merge::Multiply) => {
+                        let inputs = node.inputs.clone();
+                        inputs.iter().enumerate().map(|(k, _)| {
+                            inputs.iter().enumerate()
+                                .filter(|&(other, _)| other != k)
+                                .fold(grad.copy(), |acc, (_, &input)| acc.hadamard(&self.nodes[input].output))
+                        }).collect()
+                    },
+                    NodeKind::Merge(Merge::Concatenate) => {
+                        let mut start = 0;
+                        let inputs = node.inputs.clone();
+                        inputs.iter().map(|&input| {
+                            let size = self.nodes[input].size;
+                            start += size;
+                            grad.slice_cols(start - size, start)
+                        }).collect()
+                    },
+                }
+            };
+
+            let inputs = self.nodes[id].inputs.clone();
+            for (input, input_grad) in inputs.into_iter().zip(input_grads) {
+                grads[input] = Some(match grads[input].take() {
+                    Some(acc) => acc.add(&input_grad),
+                    None => input_grad,
+                });
+            }
+        }
+
+        self.inputs.iter().map(|&id| match grads[id].take() {
+            Some(grad) => grad,
+            None => Matrix::create_zero_matrix(d_output.rows, self.nodes[id].size),
+        }).collect()
+    }
+
+    pub fn update(&mut self, learning_rate: f32) {
+        for node in self.nodes.iter_mut() {
+            if let NodeKind::Dense(linear, _) = &mut node.kind {
+                linear.update(learning_rate);
+            }
+        }
+    }
+
+    pub fn zero_gradients(&mut self) {
+        for node in self.nodes.iter_mut() {
+            if let NodeKind::Dense(linear, _) = &mut node.kind {
+                linear.zero_gradients();
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use crate::dataset::*;
+use crate::prelude::*;
+
+// An IDX file as stored on disk: its dimensions, outermost first, and the
+// values in row-major order.
+pub struct IdxArray {
+    pub dims: Vec<usize>,
+    pub data: Vec<f32>,
+}
+
+pub fn read_idx<R: Read>(reader: R) -> Result<IdxArray> {
+    let mut reader = BufReader::new(reader);
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if magic[0] != 0 || magic[1] != 0 {
+        return Err(Error::Parse {row: 0, column: 0, message: "not an IDX file".to_string()});
+    }
+    let element_size: usize = match magic[2] {
+        0x08 | 0x09 => 1,
+        0x0B => 2,
+        0x0C | 0x0D => 4,
+        0x0E => 8,
+        code => return Err(Error::Parse {row: 0, column: 2, message: format!("unknown IDX type 0x{:02x}", code)}),
+    };
+
+    let mut dims: Vec<usize> = Vec::new();
+    for _ in 0..magic[3] {
+        let mut dim = [0u8; 4];
+        reader.read_exact(&mut dim)?;
+        dims.push(u32::from_be_bytes(dim) as usize);
+    }
+
+    // The header is untrusted: size the buffer from what the file actually holds.
+    let size = dims.iter().try_fold(element_size, |size, &dim| size.checked_mul(dim))
+        .ok_or_else(|| Error::Parse {row: 0, column: 0, message: format!("dimensions {:?} are too large", dims)})?;
+    let mut bytes = Vec::new();
+    reader.take(size as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != size {
+        return Err(Error::Parse {row: 0, column: 0, message: format!("dimensions {:?} need {} bytes of data, found {}", dims, size, bytes.len())});
+    }
+    let data = bytes.chunks_exact(element_size).map(|b| match magic[2] {
+        0x08 => b[0] as f32,
+        0x09 => b[0] as i8 as f32,
+        0x0B => i16::from_be_bytes([b[0], b[1]]) as f32,
+        0x0C => i32::from_be_bytes([b[0], b[1], b[2], b[3]]) as f32,
+        0x0D => f32::from_be_bytes([b[0], b[1], b[2], b[3]]),
+        _ => f64::from_be_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f32,
+    }).collect();
+
+    Ok(IdxArray {dims, data})
+}
+
+impl DataSet {
+    // One row per image, flattened row-major. With `normalize` unsigned byte
+    // pixels are scaled from [0, 255] to [0, 1].
+    pub fn from_idx_images<P: AsRef<Path>>(path: P, normalize: bool) -> Result<DataSet> {
+        let array = read_idx(File::open(path)?)?;
+        if array.dims.len() < 2 {
+            return Err(Error::Parse {row: 0, column: 0, message: format!("expected at least 2 dimensions, found {}", array.dims.len())});
+        }
+
+        let rows = array.dims[0];
+        let cols: usize = array.dims[1..].iter().product();
+        let scale = if normalize { 1.0 / 255.0 } else { 1.0 };
+        let data = array.data.chunks(cols.max(1)).map(|row| row.iter().map(|x| x * scale).collect()).collect();
+        Ok(create_dataset(rows, cols, data))
+    }
+
+    // One-hot class rows. `num_classes` defaults to the largest label plus one.
+    pub fn from_idx_labels<P: AsRef<Path>>(path: P, num_classes: Option<usize>) -> Result<DataSet> {
+        let array = read_idx(File::open(path)?)?;
+        if array.dims.len() != 1 {
+            return Err(Error::Parse {row: 0, column: 0, message: format!("expected 1 dimension, found {}", array.dims.len())});
+        }
+
+        // Rows in errors are 1-based, as in the CSV and LIBSVM readers.
+        if let Some(row) = array.data.iter().position(|&x| x < 0.0 || x.fract() != 0.0) {
+            return Err(Error::Parse {row: row + 1, column: 1, message: format!("label {} is not a class index", array.data[row])});
+        }
+        let labels: Vec<usize> = array.data.iter().map(|&x| x as usize).collect();
+        let num_classes = num_classes.unwrap_or(labels.iter().max().map_or(0, |max| max + 1));
+        if let Some(row) = labels.iter().position(|&label| label >= num_classes) {
+            return Err(Error::Parse {row: row + 1, column: 1, message: format!("label {} is not below {}", labels[row], num_classes)});
+        }
+
+        Ok(one_hot(&labels, num_classes))
+    }
+}
@@ -0,0 +1,162 @@
+use crate::dataset::*;
+use crate::function::*;
+use crate::layer::*;
+use crate::matrix::*;
+use crate::network::*;
+use crate::prelude::*;
+
+struct InferenceLayer<T> {
+    weights: Matrix<T>,
+    bias: Matrix<T>,
+    activation: Option<Activation<T>>,
+}
+
+// A read-only copy of a trained `Network`'s weights. Unlike the network it
+// holds no activations of its own, so it is `Send + Sync` and one model can
+// serve many threads at once, each with its own `Scratch`.
+pub struct InferenceModel<T = f32> {
+    layers: Vec<InferenceLayer<T>>,
+}
+
+// The per-caller layer outputs for `InferenceModel::predict`. Reusing one
+// for inputs with the same number of rows avoids all allocation.
+pub struct Scratch<T = f32> {
+    outputs: Vec<Matrix<T>>,
+}
+
+pub fn create_scratch<T: Float>(model: &InferenceModel<T>, rows: usize) -> Scratch<T> {
+    let outputs = model.layers.iter().map(|layer| Matrix::create_zero_matrix(rows, layer.weights.cols)).collect();
+    Scratch {outputs}
+}
+
+impl<T: Float> Network<T> {
+    pub fn to_inference_model(&self) -> InferenceModel<T> {
+        let layers = self.connections.iter().map(|connection| InferenceLayer {
+            weights: connection.weights.copy(),
+            bias: connection.bias.copy(),
+            activation: connection.to.activation,
+        }).collect();
+        InferenceModel {layers}
+    }
+
+    // The network's output for every row of `input`. Unlike `forward_pass`
+    // this leaves the layers untouched, so `get_output` and `predict` still
+    // see the last forward pass.
+    pub fn infer(&self, input: &Matrix<T>) -> Result<Matrix<T>> {
+        self.check_input(input)?;
+        self.infer_rows(&input.data)
+    }
+
+    // `infer` on a slice of feature rows, e.g. part of a larger data set.
+    pub fn infer_rows(&self, rows: &[Vec<T>]) -> Result<Matrix<T>> {
+        if rows.is_empty() {
+            return Err(Error::EmptyDataset);
+        }
+        if let Some(row) = rows.iter().find(|row| row.len() != self.layers[0].size) {
+            return Err(Error::ShapeMismatch {expected: (1, self.layers[0].size), found: (1, row.len())});
+        }
+        let mut output = layer_output(&self.connections[0], rows);
+        for connection in self.connections.iter().skip(1) {
+            output = layer_output(connection, &output.data);
+        }
+        Ok(output)
+    }
+
+    // Like `infer`, `chunk_size` rows at a time, so that the hidden layer
+    // outputs of a large data set never have to fit in memory at once.
+    pub fn infer_chunked(&self, input: &DataSet<T>, chunk_size: usize) -> Result<Matrix<T>> {
+        self.check_chunks(input, chunk_size)?;
+        let mut data = Vec::with_capacity(input.rows);
+        for chunk in input.data.chunks(chunk_size) {
+            data.extend(self.infer_rows(chunk)?.data);
+        }
+        Ok(Matrix::create_matrix(input.rows, self.layers[self.num_layers-1].size, data))
+    }
+
+    // The argmax of each output row.
+    pub fn predict_classes(&self, input: &Matrix<T>) -> Result<Vec<usize>> {
+        Ok(class_indices(&self.infer(input)?))
+    }
+
+    pub fn predict_classes_chunked(&self, input: &DataSet<T>, chunk_size: usize) -> Result<Vec<usize>> {
+        self.check_chunks(input, chunk_size)?;
+        let mut classes = Vec::with_capacity(input.rows);
+        for chunk in input.data.chunks(chunk_size) {
+            classes.extend(class_indices(&self.infer_rows(chunk)?));
+        }
+        Ok(classes)
+    }
+
+    // Class probabilities: the output itself when the output layer is a
+    // softmax or sigmoid, otherwise a softmax over each output row.
+    pub fn predict_proba(&self, input: &Matrix<T>) -> Result<Matrix<T>> {
+        let mut output = self.infer(input)?;
+        let activation = self.layers[self.num_layers-1].activation;
+        if !matches!(activation.map(function_name), Some("softmax") | Some("sigmoid")) {
+            activate_in_place(Some(get_function_by_name::<T>("softmax")), &mut output);
+        }
+        Ok(output)
+    }
+
+    fn check_chunks(&self, input: &DataSet<T>, chunk_size: usize) -> Result<()> {
+        if chunk_size == 0 {
+            return Err(Error::InvalidConfig("chunk size must be at least 1".to_string()));
+        }
+        self.check_input(input)
+    }
+}
+
+fn layer_output<T: Float>(connection: &Connection<T>, rows: &[Vec<T>]) -> Matrix<T> {
+    let weights = &connection.weights;
+    let mut output = Matrix::create_zero_matrix(rows.len(), weights.cols);
+    for (row, out) in rows.iter().zip(output.data.iter_mut()) {
+        assert!(row.len() == weights.rows);
+        for (x, weight_row) in row.iter().zip(weights.data.iter()) {
+            for (o, &w) in out.iter_mut().zip(weight_row.iter()) {
+                *o += *x * w;
+            }
+        }
+    }
+    connection.bias.add_to_each_row_of(&mut output);
+    activate_in_place(connection.to.activation, &mut output);
+    output
+}
+
+impl<T: Float> InferenceModel<T> {
+    pub fn num_inputs(&self) -> usize {
+        self.layers[0].weights.rows
+    }
+
+    pub fn num_outputs(&self) -> usize {
+        self.layers[self.layers.len() - 1].weights.cols
+    }
+
+    // The network's output for every row of `input`, written into and
+    // borrowed from `scratch`. The scratch is resized if `input` has a
+    // different shape than it was made for.
+    pub fn predict<'a>(&self, input: &Matrix<T>, scratch: &'a mut Scratch<T>) -> Result<&'a Matrix<T>> {
+        if input.rows == 0 {
+            return Err(Error::EmptyDataset);
+        }
+        if input.cols != self.num_inputs() {
+            return Err(Error::ShapeMismatch {expected: (input.rows, self.num_inputs()), found: (input.rows, input.cols)});
+        }
+        let fits = scratch.outputs.len() == self.layers.len()
+            && scratch.outputs.iter().zip(self.layers.iter())
+                .all(|(output, layer)| (output.rows, output.cols) == (input.rows, layer.weights.cols));
+        if !fits {
+            *scratch = create_scratch(self, input.rows);
+        }
+
+        for (i, layer) in self.layers.iter().enumerate() {
+            let (previous, rest) = scratch.outputs.split_at_mut(i);
+            let output = &mut rest[0];
+            let from = if i == 0 { input } else { &previous[i - 1] };
+            from.multiply_into(&layer.weights, output);
+            layer.bias.add_to_each_row_of(output);
+            activate_in_place(layer.activation, output);
+        }
+
+        Ok(&scratch.outputs[self.layers.len() - 1])
+    }
+}
@@ -10,37 +10,37 @@ pub enum LayerType {
 }
 
 #[derive(Clone)]
-pub struct Layer {
+pub struct Layer<T = f32> {
     pub layer_type: LayerType,
     pub size: usize,
-    pub activation: Option<Activation>,
-    pub input: Rc<RefCell<Matrix>>
+    pub activation: Option<Activation<T>>,
+    pub input: Rc<RefCell<Matrix<T>>>
 }
 
-pub struct Connection {
-    pub from: Layer,
-    pub to: Layer,
-    pub weights: Matrix,
-    pub bias: Matrix
+pub struct Connection<T = f32> {
+    pub from: Layer<T>,
+    pub to: Layer<T>,
+    pub weights: Matrix<T>,
+    pub bias: Matrix<T>
 }
 
-pub fn create_layer(layer_type: LayerType, size: usize, activation: Option<Activation>) -> Layer {
-    let row = vec![vec![0.0 as f32; size]; 1];
-    let input: Matrix = Matrix::create_matrix(1, size, row);
+pub fn create_layer<T: Float>(layer_type: LayerType, size: usize, activation: Option<Activation<T>>) -> Layer<T> {
+    let row = vec![vec![T::ZERO; size]; 1];
+    let input: Matrix<T> = Matrix::create_matrix(1, size, row);
     Layer {layer_type, size, activation, input: Rc::new(RefCell::new(input))}
 }
 
-pub fn create_connection(from: &Layer, to: &Layer) -> Connection {
+pub fn create_connection<T: Float>(from: &Layer<T>, to: &Layer<T>) -> Connection<T> {
     let to_size = to.size;
     let from_size = from.size;
-    let weights_data = vec![vec![0.0 as f32; to_size]; from_size];
-    let bias_data = vec![vec![0.0 as f32; to_size]; 1];
+    let weights_data = vec![vec![T::ZERO; to_size]; from_size];
+    let bias_data = vec![vec![T::ZERO; to_size]; 1];
     let weights = Matrix::create_matrix(from_size, to_size, weights_data);
     let bias = Matrix::create_matrix(1, to_size, bias_data);
     Connection {from: from.clone(), to: to.clone(), weights, bias}
 }
 
-impl Layer {
+impl<T: Float> Layer<T> {
     pub fn activate(&mut self) {
         match self.activation {
             None => {},
@@ -48,16 +48,70 @@ impl Layer {
         }
     }
 
-    pub fn set_input(&mut self, input: Rc<RefCell<Matrix>>) {
+    pub fn set_input(&mut self, input: Rc<RefCell<Matrix<T>>>) {
         self.input = input;
     }
 }
 
-impl Connection {
+impl<T: Float> Connection<T> {
     pub fn init(&mut self) {
         self.bias.to_zero();
 
         let neurons_in = (self.weights.rows as f32).sqrt();
-        self.weights.transform(|x| box_muller(x) / neurons_in);
+        self.weights.transform(|x| T::from_f32(box_muller(x.to_f32()) / neurons_in));
+    }
+}
+pub struct Linear {
+    pub weights: Matrix,
+    pub bias: Matrix,
+    pub d_weights: Matrix,
+    pub d_bias: Matrix,
+    pub input: Matrix
+}
+
+pub fn create_linear(in_size: usize, out_size: usize) -> Linear {
+    let mut weights = Matrix::create_zero_matrix(in_size, out_size);
+    let neurons_in = (in_size as f32).sqrt();
+    weights.transform(|x| box_muller(x) / neurons_in);
+    Linear {
+        weights,
+        bias: Matrix::create_zero_matrix(1, out_size),
+        d_weights: Matrix::create_zero_matrix(in_size, out_size),
+        d_bias: Matrix::create_zero_matrix(1, out_size),
+        input: Matrix::create_zero_matrix(1, in_size)
+    }
+}
+
+impl Linear {
+    pub fn forward(&mut self, input: &Matrix) -> Matrix {
+        assert!(input.cols == self.weights.rows);
+        self.input = input.clone();
+        input.multiply(&self.weights).add_to_each_row(&self.bias)
     }
-}
\ No newline at end of file
+
+    // Accumulates the parameter gradients and returns the gradient w.r.t. the input.
+    pub fn backward(&mut self, d_output: &Matrix) -> Matrix {
+        assert!(d_output.rows == self.input.rows && d_output.cols == self.weights.cols);
+        self.input.transpose().multiply(d_output).add_to(&mut self.d_weights);
+        for row in d_output.data.iter() {
+            for (j, val) in row.iter().enumerate() {
+                self.d_bias.data[0][j] += val;
+            }
+        }
+        d_output.multiply(&self.weights.transpose())
+    }
+
+    pub fn update(&mut self, learning_rate: f32) {
+        let mut step = self.d_weights.copy();
+        step.scalar_multiply(-learning_rate);
+        step.add_to(&mut self.weights);
+        let mut step = self.d_bias.copy();
+        step.scalar_multiply(-learning_rate);
+        step.add_to(&mut self.bias);
+    }
+
+    pub fn zero_gradients(&mut self) {
+        self.d_weights.to_zero();
+        self.d_bias.to_zero();
+    }
+}
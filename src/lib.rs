@@ -2,8 +2,25 @@
 
 mod prelude;
 pub mod dataset;
+pub mod csv;
+pub mod idx;
+pub mod libsvm;
+pub mod npy;
+pub mod preprocessing;
+pub mod source;
+pub mod augment;
+pub mod validation;
+pub mod float;
 pub mod matrix;
+pub mod linalg;
 pub mod error;
 pub mod function;
 pub mod layer;
-pub mod network;
\ No newline at end of file
+pub mod network;
+pub mod precision;
+pub mod workspace;
+pub mod inference;
+pub mod attention;
+pub mod transformer;
+pub mod graph;
+pub mod autograd;
\ No newline at end of file
@@ -0,0 +1,76 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use crate::dataset::*;
+use crate::prelude::*;
+
+// Reads `<label> <index>:<value> ...` lines with 1-based feature indices.
+// Omitted features are zero. Without `num_features` the width is the
+// largest index seen. Comments after `#` are ignored.
+pub fn read_libsvm<R: Read>(reader: R, num_features: Option<usize>) -> Result<(DataSet, Vec<f32>)> {
+    let mut labels: Vec<f32> = Vec::new();
+    let mut entries: Vec<Vec<(usize, f32)>> = Vec::new();
+    let mut width = 0;
+    for (line_idx, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        let row = line_idx + 1;
+        let content = line.split('#').next().unwrap_or("").trim();
+        if content.is_empty() {
+            continue;
+        }
+
+        let mut tokens = content.split_whitespace();
+        let label = tokens.next().unwrap();
+        let label_error = || Error::Parse {row, column: 1, message: format!("{:?} is not a label", label)};
+        let value = label.parse::<f32>().map_err(|_| label_error())?;
+        if !value.is_finite() {
+            return Err(label_error());
+        }
+        labels.push(value);
+
+        let mut row_entries: Vec<(usize, f32)> = Vec::new();
+        for (token_idx, token) in tokens.enumerate() {
+            let column = token_idx + 2;
+            let parse_error = || Error::Parse {row, column, message: format!("{:?} is not an index:value pair", token)};
+            let (index, value) = token.split_once(':').ok_or_else(parse_error)?;
+            let index: usize = index.parse().map_err(|_| parse_error())?;
+            let value: f32 = value.parse().map_err(|_| parse_error())?;
+            if index == 0 {
+                return Err(Error::Parse {row, column, message: "feature indices start at 1".to_string()});
+            }
+            if let Some(limit) = num_features {
+                if index > limit {
+                    return Err(Error::Parse {row, column, message: format!("feature index {} exceeds {}", index, limit)});
+                }
+            }
+            width = width.max(index);
+            row_entries.push((index - 1, value));
+        }
+        entries.push(row_entries);
+    }
+
+    let cols = num_features.unwrap_or(width);
+    let data: Vec<Vec<f32>> = entries.into_iter().map(|row_entries| {
+        let mut row = vec![0.0; cols];
+        for (index, value) in row_entries {
+            row[index] = value;
+        }
+        row
+    }).collect();
+
+    Ok((create_dataset(data.len(), cols, data), labels))
+}
+
+impl DataSet {
+    // Returns the features and one-hot classes. Distinct labels are sorted
+    // and numbered from 0, so -1/+1 labels become classes 0 and 1.
+    pub fn from_libsvm<P: AsRef<Path>>(path: P, num_features: Option<usize>) -> Result<(DataSet, DataSet)> {
+        let (features, labels) = read_libsvm(File::open(path)?, num_features)?;
+        let mut distinct = labels.clone();
+        distinct.sort_by(|a, b| a.total_cmp(b));
+        distinct.dedup();
+
+        let indices: Vec<usize> = labels.iter().map(|label| distinct.iter().position(|d| d == label).unwrap()).collect();
+        Ok((features, one_hot(&indices, distinct.len())))
+    }
+}
@@ -0,0 +1,334 @@
+use std::cmp::Ordering;
+use crate::matrix::*;
+use crate::prelude::*;
+
+// Rotation sweeps after which the Jacobi methods stop even if the
+// off-diagonal mass has not fully vanished.
+const MAX_SWEEPS: usize = 100;
+
+// P * A = L * U, with row i of P * A being row `permutation[i]` of A, L unit
+// lower triangular and U upper triangular.
+#[derive(Clone, Debug)]
+pub struct Lu<T = f32> {
+    pub lower: Matrix<T>,
+    pub upper: Matrix<T>,
+    pub permutation: Vec<usize>,
+    swaps: usize,
+}
+
+// A = Q * R for an m x n matrix, with Q m x k having orthonormal columns, R
+// k x n upper triangular and k = min(m, n).
+#[derive(Clone, Debug)]
+pub struct Qr<T = f32> {
+    pub q: Matrix<T>,
+    pub r: Matrix<T>,
+}
+
+// A = V * diag(values) * V^T, eigenvalues in decreasing order and the
+// matching eigenvectors as the columns of `vectors`.
+#[derive(Clone, Debug)]
+pub struct SymmetricEigen<T = f32> {
+    pub values: Vec<T>,
+    pub vectors: Matrix<T>,
+}
+
+// A = U * diag(singular_values) * V^T for an m x n matrix, with U m x k, V
+// n x k, k = min(m, n) and the singular values in decreasing order. Columns
+// of U for zero singular values are left zero.
+#[derive(Clone, Debug)]
+pub struct Svd<T = f32> {
+    pub u: Matrix<T>,
+    pub singular_values: Vec<T>,
+    pub v: Matrix<T>,
+}
+
+fn sign_or_one<T: Float>(x: T) -> T {
+    if x < T::ZERO { -T::ONE } else { T::ONE }
+}
+
+// The tangent of the Jacobi rotation angle that zeroes the off-diagonal
+// entry for `zeta` = (a_qq - a_pp) / (2 a_pq), taking the smaller root.
+fn jacobi_tangent<T: Float>(zeta: T) -> T {
+    sign_or_one(zeta) / (zeta.abs() + (zeta * zeta + T::ONE).sqrt())
+}
+
+// Replaces columns p and q by (c * p - s * q, s * p + c * q).
+fn rotate_columns<T: Float>(data: &mut [Vec<T>], p: usize, q: usize, c: T, s: T) {
+    for row in data.iter_mut() {
+        let (x, y) = (row[p], row[q]);
+        row[p] = c * x - s * y;
+        row[q] = s * x + c * y;
+    }
+}
+
+// The columns of `data` reordered by `order`.
+fn select_columns<T: Float>(data: &[Vec<T>], order: &[usize]) -> Matrix<T> {
+    Matrix::from_fn(data.len(), order.len(), |i, j| data[i][order[j]])
+}
+
+// Indices of `values` from largest to smallest.
+fn decreasing_order<T: Float>(values: &[T]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[b].partial_cmp(&values[a]).unwrap_or(Ordering::Equal));
+    order
+}
+
+impl<T: Float> Matrix<T> {
+    fn check_square(&self) -> Result<()> {
+        if self.rows != self.cols {
+            return Err(Error::ShapeMismatch {expected: (self.rows, self.rows), found: (self.rows, self.cols)});
+        }
+        Ok(())
+    }
+
+    fn check_not_empty(&self) -> Result<()> {
+        if self.rows == 0 || self.cols == 0 {
+            return Err(Error::EmptyDataset);
+        }
+        Ok(())
+    }
+
+    // LU decomposition with partial pivoting. A singular matrix still has
+    // one; it only fails to `solve`.
+    pub fn lu(&self) -> Result<Lu<T>> {
+        self.check_square()?;
+        let n = self.rows;
+        let mut a = self.data.clone();
+        let mut permutation: Vec<usize> = (0..n).collect();
+        let mut swaps = 0;
+        for k in 0..n {
+            let pivot = (k..n).max_by(|&i, &j| a[i][k].abs().partial_cmp(&a[j][k].abs()).unwrap_or(Ordering::Equal)).unwrap();
+            if pivot != k {
+                a.swap(pivot, k);
+                permutation.swap(pivot, k);
+                swaps += 1;
+            }
+            if a[k][k] == T::ZERO {
+                continue;
+            }
+            let (top, bottom) = a.split_at_mut(k + 1);
+            let pivot_row = &top[k];
+            for row in bottom.iter_mut() {
+                let factor = row[k] / pivot_row[k];
+                row[k] = factor;
+                for (x, &p) in row[k + 1..].iter_mut().zip(pivot_row[k + 1..].iter()) {
+                    *x -= factor * p;
+                }
+            }
+        }
+
+        let lower = Matrix::from_fn(n, n, |i, j| if i == j { T::ONE } else if i > j { a[i][j] } else { T::ZERO });
+        let upper = Matrix::from_fn(n, n, |i, j| if j >= i { a[i][j] } else { T::ZERO });
+        Ok(Lu {lower, upper, permutation, swaps})
+    }
+
+    // Solves A * X = B for X, one column of B at a time.
+    pub fn solve(&self, b: &Matrix<T>) -> Result<Matrix<T>> {
+        self.lu()?.solve(b)
+    }
+
+    pub fn inverse(&self) -> Result<Matrix<T>> {
+        self.solve(&Matrix::identity(self.rows))
+    }
+
+    pub fn determinant(&self) -> Result<T> {
+        Ok(self.lu()?.determinant())
+    }
+
+    // Householder QR of any non-empty m x n matrix.
+    pub fn qr(&self) -> Result<Qr<T>> {
+        self.check_not_empty()?;
+        let (m, n) = (self.rows, self.cols);
+        let k = m.min(n);
+        let mut r = self.data.clone();
+        let mut q = Matrix::<T>::identity(m).data;
+        for col in 0..k.min(m - 1) {
+            let norm = (col..m).map(|i| r[i][col] * r[i][col]).sum::<T>().sqrt();
+            if norm == T::ZERO {
+                continue;
+            }
+            let alpha = -sign_or_one(r[col][col]) * norm;
+            let mut v: Vec<T> = (col..m).map(|i| r[i][col]).collect();
+            v[0] -= alpha;
+            let v_norm = v.iter().map(|&x| x * x).sum::<T>();
+            if v_norm == T::ZERO {
+                continue;
+            }
+            let two = T::from_f32(2.0);
+
+            // R = H * R and Q = Q * H with H = I - 2 v v^T / (v^T v).
+            let factors: Vec<T> = (0..n).map(|j| two * v.iter().enumerate().map(|(l, &x)| x * r[col + l][j]).sum::<T>() / v_norm).collect();
+            for (l, &x) in v.iter().enumerate() {
+                for (value, &factor) in r[col + l].iter_mut().zip(factors.iter()) {
+                    *value -= factor * x;
+                }
+            }
+            for row in q.iter_mut() {
+                let factor = two * (0..v.len()).map(|l| row[col + l] * v[l]).sum::<T>() / v_norm;
+                for (l, &x) in v.iter().enumerate() {
+                    row[col + l] -= factor * x;
+                }
+            }
+        }
+
+        Ok(Qr {
+            q: Matrix::from_fn(m, k, |i, j| q[i][j]),
+            r: Matrix::from_fn(k, n, |i, j| if j >= i { r[i][j] } else { T::ZERO }),
+        })
+    }
+
+    // The lower triangular L with A = L * L^T. Only the lower triangle of A
+    // is read.
+    pub fn cholesky(&self) -> Result<Matrix<T>> {
+        self.check_square()?;
+        let n = self.rows;
+        let mut l = Matrix::<T>::zeros(n, n);
+        for j in 0..n {
+            let diagonal = self.data[j][j] - (0..j).map(|k| l.data[j][k] * l.data[j][k]).sum::<T>();
+            if !diagonal.is_finite() || diagonal <= T::ZERO {
+                return Err(Error::NotPositiveDefinite);
+            }
+            l.data[j][j] = diagonal.sqrt();
+            for i in j + 1..n {
+                let sum = (0..j).map(|k| l.data[i][k] * l.data[j][k]).sum::<T>();
+                l.data[i][j] = (self.data[i][j] - sum) / l.data[j][j];
+            }
+        }
+
+        Ok(l)
+    }
+
+    // Eigendecomposition of a symmetric matrix by cyclic Jacobi rotations.
+    // Only the lower triangle is read.
+    pub fn symmetric_eigen(&self) -> Result<SymmetricEigen<T>> {
+        self.check_square()?;
+        let n = self.rows;
+        let mut a: Vec<Vec<T>> = (0..n).map(|i| (0..n).map(|j| self.data[i.max(j)][i.min(j)]).collect()).collect();
+        let mut v = Matrix::<T>::identity(n).data;
+        let total: T = a.iter().flatten().map(|&x| x * x).sum();
+
+        for _ in 0..MAX_SWEEPS {
+            let off: T = (0..n).flat_map(|i| (i + 1..n).map(move |j| (i, j))).map(|(i, j)| a[i][j] * a[i][j]).sum();
+            if off <= T::EPSILON * T::EPSILON * total {
+                break;
+            }
+            for p in 0..n {
+                for q in p + 1..n {
+                    if a[p][q] == T::ZERO {
+                        continue;
+                    }
+                    let t = jacobi_tangent((a[q][q] - a[p][p]) / (T::from_f32(2.0) * a[p][q]));
+                    let c = T::ONE / (t * t + T::ONE).sqrt();
+                    let s = t * c;
+                    rotate_columns(&mut a, p, q, c, s);
+                    let (row_p, row_q) = (a[p].clone(), a[q].clone());
+                    for k in 0..n {
+                        a[p][k] = c * row_p[k] - s * row_q[k];
+                        a[q][k] = s * row_p[k] + c * row_q[k];
+                    }
+                    rotate_columns(&mut v, p, q, c, s);
+                }
+            }
+        }
+
+        let diagonal: Vec<T> = (0..n).map(|i| a[i][i]).collect();
+        let order = decreasing_order(&diagonal);
+        Ok(SymmetricEigen {values: order.iter().map(|&i| diagonal[i]).collect(), vectors: select_columns(&v, &order)})
+    }
+
+    // Singular value decomposition of a non-empty matrix by one-sided
+    // Jacobi rotations.
+    pub fn svd(&self) -> Result<Svd<T>> {
+        self.check_not_empty()?;
+        if self.rows < self.cols {
+            let Svd {u, singular_values, v} = self.transpose().svd()?;
+            return Ok(Svd {u: v, singular_values, v: u});
+        }
+
+        let n = self.cols;
+        let mut u = self.data.clone();
+        let mut v = Matrix::<T>::identity(n).data;
+        for _ in 0..MAX_SWEEPS {
+            let mut rotated = false;
+            for p in 0..n {
+                for q in p + 1..n {
+                    let alpha: T = u.iter().map(|row| row[p] * row[p]).sum();
+                    let beta: T = u.iter().map(|row| row[q] * row[q]).sum();
+                    let gamma: T = u.iter().map(|row| row[p] * row[q]).sum();
+                    if gamma.abs() <= T::EPSILON * (alpha * beta).sqrt() {
+                        continue;
+                    }
+                    rotated = true;
+                    let t = jacobi_tangent((beta - alpha) / (T::from_f32(2.0) * gamma));
+                    let c = T::ONE / (t * t + T::ONE).sqrt();
+                    let s = t * c;
+                    rotate_columns(&mut u, p, q, c, s);
+                    rotate_columns(&mut v, p, q, c, s);
+                }
+            }
+            if !rotated {
+                break;
+            }
+        }
+
+        let norms: Vec<T> = (0..n).map(|j| u.iter().map(|row| row[j] * row[j]).sum::<T>().sqrt()).collect();
+        for row in u.iter_mut() {
+            for (x, &norm) in row.iter_mut().zip(norms.iter()) {
+                *x = if norm > T::ZERO { *x / norm } else { T::ZERO };
+            }
+        }
+        let order = decreasing_order(&norms);
+        Ok(Svd {
+            u: select_columns(&u, &order),
+            singular_values: order.iter().map(|&j| norms[j]).collect(),
+            v: select_columns(&v, &order),
+        })
+    }
+}
+
+impl<T: Float> Lu<T> {
+    pub fn determinant(&self) -> T {
+        let product = (0..self.upper.rows).map(|i| self.upper.data[i][i]).fold(T::ONE, |a, b| a * b);
+        if self.swaps.is_multiple_of(2) { product } else { -product }
+    }
+
+    // True when a pivot is negligible next to the largest entry of U.
+    pub fn is_singular(&self) -> bool {
+        let n = self.upper.rows;
+        let scale = self.upper.data.iter().flatten().fold(T::ZERO, |a, &b| a.max(b.abs()));
+        let tolerance = T::from_usize(n) * T::EPSILON * scale;
+        scale == T::ZERO || (0..n).any(|i| self.upper.data[i][i].abs() <= tolerance)
+    }
+
+    pub fn solve(&self, b: &Matrix<T>) -> Result<Matrix<T>> {
+        let n = self.upper.rows;
+        if b.rows != n {
+            return Err(Error::ShapeMismatch {expected: (n, b.cols), found: (b.rows, b.cols)});
+        }
+        if self.is_singular() {
+            return Err(Error::Singular);
+        }
+
+        let mut x = Matrix::<T>::zeros(n, b.cols);
+        for col in 0..b.cols {
+            // L * y = P * b, then U * x = y.
+            let mut y: Vec<T> = self.permutation.iter().map(|&i| b.data[i][col]).collect();
+            for i in 0..n {
+                for j in 0..i {
+                    let val = self.lower.data[i][j] * y[j];
+                    y[i] -= val;
+                }
+            }
+            for i in (0..n).rev() {
+                for j in i + 1..n {
+                    let val = self.upper.data[i][j] * y[j];
+                    y[i] -= val;
+                }
+                y[i] /= self.upper.data[i][i];
+                x.data[i][col] = y[i];
+            }
+        }
+
+        Ok(x)
+    }
+}
@@ -1,56 +1,110 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
+use rand::Rng;
 use crate::prelude::*;
 use crate::dataset::*;
 
-pub type Matrix = DataSet;
+pub type Matrix<T = f32> = DataSet<T>;
 
-impl Matrix {
+// The direction a reduction runs in. `Rows` folds the rows together, giving
+// one value per column as a 1 x cols row vector; `Cols` folds the columns,
+// giving one value per row as a rows x 1 column vector.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Axis {
+    Rows,
+    Cols,
+}
 
-    pub fn create_matrix(rows: usize, cols: usize, data: Vec<Vec<f32>>) -> Matrix {
+fn check_shape<T>(expected: (usize, usize), found: &Matrix<T>) -> Result<()> {
+    if (found.rows, found.cols) != expected {
+        return Err(Error::ShapeMismatch {expected, found: (found.rows, found.cols)});
+    }
+    Ok(())
+}
+
+impl<T: Float> Matrix<T> {
+
+    pub fn create_matrix(rows: usize, cols: usize, data: Vec<Vec<T>>) -> Matrix<T> {
         assert!(rows > 0 && cols > 0);
         Matrix {rows, cols, data}
     }
 
-    pub fn create_zero_matrix(rows: usize, cols: usize) -> Matrix {
+    pub fn create_zero_matrix(rows: usize, cols: usize) -> Matrix<T> {
         assert!(rows > 0 && cols > 0);
-        Matrix {rows, cols, data: vec![vec![0.0; cols]; rows]}
+        Matrix {rows, cols, data: vec![vec![T::ZERO; cols]; rows]}
+    }
+
+    pub fn zeros(rows: usize, cols: usize) -> Matrix<T> {
+        Matrix::create_zero_matrix(rows, cols)
+    }
+
+    pub fn ones(rows: usize, cols: usize) -> Matrix<T> {
+        Matrix::from_fn(rows, cols, |_, _| T::ONE)
+    }
+
+    pub fn identity(size: usize) -> Matrix<T> {
+        Matrix::from_fn(size, size, |i, j| if i == j { T::ONE } else { T::ZERO })
+    }
+
+    // Element (i, j) is `func(i, j)`.
+    pub fn from_fn<F>(rows: usize, cols: usize, mut func: F) -> Matrix<T>
+    where F: FnMut(usize, usize) -> T {
+        let data = (0..rows).map(|i| (0..cols).map(|j| func(i, j)).collect()).collect();
+        Matrix::create_matrix(rows, cols, data)
+    }
+
+    // Builds a matrix from `rows * cols` values in row-major order.
+    pub fn from_slice(rows: usize, cols: usize, values: &[T]) -> Matrix<T> {
+        assert!(values.len() == rows * cols);
+        Matrix::from_fn(rows, cols, |i, j| values[i * cols + j])
+    }
+
+    // Uniform values in [0, 1).
+    pub fn random(rows: usize, cols: usize) -> Matrix<T> {
+        Matrix::random_with(rows, cols, &mut rand::thread_rng())
     }
 
-    pub fn get(&self, row: usize, col: usize) -> f32 {
+    pub fn random_with<R: Rng + ?Sized>(rows: usize, cols: usize, rng: &mut R) -> Matrix<T> {
+        Matrix::from_fn(rows, cols, |_, _| T::from_f64(rng.gen::<f64>()))
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> T {
         self.data[row][col]
     }
 
-    pub fn set(&mut self, row: usize, col: usize, val: f32) {
+    pub fn set(&mut self, row: usize, col: usize, val: T) {
         self.data[row][col] = val;
     }
 
     pub fn to_zero(&mut self) {
         for z in self.data.iter_mut() {
-            z.fill(0.0);
+            z.fill(T::ZERO);
         }
     }
 
     pub fn transform<F>(&mut self, mut func: F)
-    where F: FnMut(f32) -> f32 {
+    where F: FnMut(T) -> T {
             for row in self.data.iter_mut() {
                 for x in row.iter_mut() {
-                    *x = func(x.clone());
+                    *x = func(*x);
                 }
         }
     }
 
-    pub fn copy(&self) -> Matrix {
+    pub fn copy(&self) -> Matrix<T> {
         self.clone()
     }
 
     // TODO: invertire l'oggetto implicito è to e quello passato è from
-    pub fn copy_into(&self, to: &mut Matrix) {
+    pub fn copy_into(&self, to: &mut Matrix<T>) {
         assert!(self.rows == to.rows && self.cols == to.cols);
-        to.data = self.data.clone()
-        
+        for (to_row, row) in to.data.iter_mut().zip(self.data.iter()) {
+            to_row.copy_from_slice(row);
+        }
     }
 
-    pub fn transpose(&self) -> Matrix {
-        let mut result: Matrix = Matrix::create_zero_matrix(self.cols, self.rows);
+    pub fn transpose(&self) -> Matrix<T> {
+        let mut result: Matrix<T> = Matrix::create_zero_matrix(self.cols, self.rows);
         for i in 0..self.rows {
             for j in 0..self.cols {
                 result.set(j, i, self.get(i, j));
@@ -60,22 +114,18 @@ impl Matrix {
         result
     }
 
-    pub fn transpose_into(&self, into: &mut Matrix) {
+    pub fn transpose_into(&self, into: &mut Matrix<T>) {
         assert!(self.rows == into.cols && self.cols == into.rows);
-        let transposed_matrix = self.transpose();
-        into.rows = transposed_matrix.rows;
-        into.cols = transposed_matrix.cols;
-        into.data = transposed_matrix.data;
-        /*for i in 0..self.rows {
+        for i in 0..self.rows {
             for j in 0..self.cols {
                 into.set(j, i, self.get(i, j));
             }
-        }*/
+        }
     }
 
-    pub fn add(&self, other: &Matrix) -> Matrix {
+    pub fn add(&self, other: &Matrix<T>) -> Matrix<T> {
         assert!(self.rows == other.rows && self.cols == other.cols);
-        let mut result: Matrix = Matrix::create_zero_matrix(self.rows, self.cols);
+        let mut result: Matrix<T> = Matrix::create_zero_matrix(self.rows, self.cols);
         for i in 0..self.rows {
             for j in 0..self.cols {
                 let val = self.get(i, j) + other.get(i, j);
@@ -86,7 +136,7 @@ impl Matrix {
         result
     }
 
-    pub fn add_to(&self, to: &mut Matrix) {
+    pub fn add_to(&self, to: &mut Matrix<T>) {
         assert!(self.rows == to.rows && self.cols == to.cols);
         for i in 0..self.rows {
             for j in 0..self.cols {
@@ -96,9 +146,9 @@ impl Matrix {
         }
     }
 
-    pub fn add_to_each_row(&self, other: &Matrix) -> Matrix {
+    pub fn add_to_each_row(&self, other: &Matrix<T>) -> Matrix<T> {
         assert!(self.cols == other.cols && other.rows == 1);
-        let mut result: Matrix = Matrix::create_zero_matrix(self.rows, self.cols);
+        let mut result: Matrix<T> = Matrix::create_zero_matrix(self.rows, self.cols);
         for i in 0..self.rows {
             for j in 0..self.cols {
                 let val = self.get(i, j) + other.get(0, j);
@@ -109,7 +159,17 @@ impl Matrix {
         result
     }
 
-    pub fn scalar_multiply(&mut self, k: f32) {
+    // In-place `add_to_each_row`: adds this 1 x cols row to every row of `to`.
+    pub fn add_to_each_row_of(&self, to: &mut Matrix<T>) {
+        assert!(self.rows == 1 && self.cols == to.cols);
+        for row in to.data.iter_mut() {
+            for (x, &b) in row.iter_mut().zip(self.data[0].iter()) {
+                *x += b;
+            }
+        }
+    }
+
+    pub fn scalar_multiply(&mut self, k: T) {
         for i in 0..self.rows {
             for j in 0..self.cols {
                 let val = self.get(i, j) * k;
@@ -118,15 +178,15 @@ impl Matrix {
         }
     }
 
-    pub fn multiply(&self, other: &Matrix) -> Matrix {
+    pub fn multiply(&self, other: &Matrix<T>) -> Matrix<T> {
         assert!(self.cols == other.rows);
-        let mut result: Matrix = Matrix::create_zero_matrix(self.rows, other.cols);
+        let mut result: Matrix<T> = Matrix::create_zero_matrix(self.rows, other.cols);
     
         result.to_zero();
         
         for i in 0..self.rows {
             for j in 0..other.cols {
-                let mut sum: f32 = 0.0;
+                let mut sum = T::ZERO;
                 for k in 0..other.rows {
                     sum += self.get(i, k) * other.get(k, j);
                 }
@@ -138,7 +198,7 @@ impl Matrix {
         result
     }
 
-    pub fn multiply_into(&self, other: &Matrix, into: &mut Matrix) {
+    pub fn multiply_into(&self, other: &Matrix<T>, into: &mut Matrix<T>) {
         assert!(self.cols == other.rows);
         assert!(self.rows == into.rows && other.cols == into.cols);
 
@@ -146,7 +206,7 @@ impl Matrix {
         
         for i in 0..self.rows {
             for j in 0..other.cols {
-                let mut sum:f32 = 0.0;
+                let mut sum = T::ZERO;
                 for k in 0..other.rows {
                     sum += self.get(i, k) * other.get(k, j);
                 }
@@ -156,9 +216,20 @@ impl Matrix {
         }
     }
 
-    pub fn hadamard(&self, other: &Matrix) -> Matrix {
+    // Same as `multiply`.
+    pub fn matmul(&self, other: &Matrix<T>) -> Matrix<T> {
+        self.multiply(other)
+    }
+
+    // The sum of the element-wise products of two matrices of the same shape.
+    pub fn dot(&self, other: &Matrix<T>) -> T {
+        assert!(self.rows == other.rows && self.cols == other.cols);
+        self.data.iter().flatten().zip(other.data.iter().flatten()).map(|(&a, &b)| a * b).sum()
+    }
+
+    pub fn hadamard(&self, other: &Matrix<T>) -> Matrix<T> {
         assert!(self.rows == other.rows && self.cols == other.cols);
-        let mut result: Matrix = Matrix::create_zero_matrix(self.rows, self.cols);
+        let mut result: Matrix<T> = Matrix::create_zero_matrix(self.rows, self.cols);
         for i in 0..self.rows {
             for j in 0..self.cols {
                 let val = self.get(i, j) * other.get(i, j);
@@ -168,7 +239,7 @@ impl Matrix {
         result
     }
 
-    pub fn hadamard_into(&self, other: &Matrix, into: &mut Matrix) {
+    pub fn hadamard_into(&self, other: &Matrix<T>, into: &mut Matrix<T>) {
         assert!(self.rows == other.rows && self.cols == other.cols);
         assert!(self.rows == into.rows && self.cols == into.cols);
         for i in 0..self.rows {
@@ -179,7 +250,28 @@ impl Matrix {
         }
     }
 
-    pub fn equals(&self, other: &Matrix) -> bool {
+    pub fn slice_cols(&self, start: usize, end: usize) -> Matrix<T> {
+        assert!(start < end && end <= self.cols);
+        let data = self.data.iter().map(|row| row[start..end].to_vec()).collect();
+        Matrix::create_matrix(self.rows, end - start, data)
+    }
+
+    pub fn set_cols(&mut self, start: usize, cols: &Matrix<T>) {
+        assert!(self.rows == cols.rows && start + cols.cols <= self.cols);
+        for (row, src) in self.data.iter_mut().zip(cols.data.iter()) {
+            row[start..start + cols.cols].copy_from_slice(src);
+        }
+    }
+
+    pub fn concat_cols(&self, other: &Matrix<T>) -> Matrix<T> {
+        assert!(self.rows == other.rows);
+        let mut result = Matrix::create_zero_matrix(self.rows, self.cols + other.cols);
+        result.set_cols(0, self);
+        result.set_cols(self.cols, other);
+        result
+    }
+
+    pub fn equals(&self, other: &Matrix<T>) -> bool {
         if (self.rows != other.rows) {
             return false;
         }
@@ -199,4 +291,345 @@ impl Matrix {
         true
     }
 
-}
\ No newline at end of file
+    // True when the shapes match and no two elements differ by more than `tolerance`.
+    pub fn approx_eq(&self, other: &Matrix<T>, tolerance: T) -> bool {
+        self.rows == other.rows && self.cols == other.cols
+            && self.data.iter().flatten().zip(other.data.iter().flatten()).all(|(&a, &b)| (a - b).abs() <= tolerance)
+    }
+
+    // The same matrix with every element converted to another float type.
+    pub fn cast<U: Float>(&self) -> Matrix<U> {
+        let data = self.data.iter().map(|row| row.iter().map(|x| U::from_f64(x.to_f64())).collect()).collect();
+        Matrix {rows: self.rows, cols: self.cols, data}
+    }
+
+    // Combines each element with the matching element of `other`, which is
+    // either the same shape, a 1 x cols row vector repeated down the rows, a
+    // rows x 1 column vector repeated across the columns, or a 1 x 1 scalar.
+    fn broadcast<F>(&self, other: &Matrix<T>, func: F) -> Result<Matrix<T>>
+    where F: Fn(T, T) -> T {
+        let row = match other.rows {
+            1 => |_: usize| 0,
+            _ if other.rows == self.rows => |i: usize| i,
+            _ => return Err(Error::ShapeMismatch {expected: (self.rows, self.cols), found: (other.rows, other.cols)}),
+        };
+        let col = match other.cols {
+            1 => |_: usize| 0,
+            _ if other.cols == self.cols => |j: usize| j,
+            _ => return Err(Error::ShapeMismatch {expected: (self.rows, self.cols), found: (other.rows, other.cols)}),
+        };
+        Ok(Matrix::from_fn(self.rows, self.cols, |i, j| func(self.data[i][j], other.data[row(i)][col(j)])))
+    }
+
+    pub fn broadcast_add(&self, other: &Matrix<T>) -> Matrix<T> {
+        self.try_broadcast_add(other).expect("shapes cannot be broadcast")
+    }
+
+    pub fn broadcast_sub(&self, other: &Matrix<T>) -> Matrix<T> {
+        self.try_broadcast_sub(other).expect("shapes cannot be broadcast")
+    }
+
+    pub fn broadcast_mul(&self, other: &Matrix<T>) -> Matrix<T> {
+        self.try_broadcast_mul(other).expect("shapes cannot be broadcast")
+    }
+
+    pub fn broadcast_div(&self, other: &Matrix<T>) -> Matrix<T> {
+        self.try_broadcast_div(other).expect("shapes cannot be broadcast")
+    }
+
+    // Applies `func` to every row (`Axis::Cols`) or column (`Axis::Rows`).
+    fn reduce<F>(&self, axis: Axis, func: F) -> Matrix<T>
+    where F: Fn(&[T]) -> T {
+        match axis {
+            Axis::Rows => {
+                let column = |j: usize| self.data.iter().map(|row| row[j]).collect::<Vec<T>>();
+                Matrix::from_fn(1, self.cols, |_, j| func(&column(j)))
+            },
+            Axis::Cols => Matrix::from_fn(self.rows, 1, |i, _| func(&self.data[i])),
+        }
+    }
+
+    pub fn sum(&self, axis: Axis) -> Matrix<T> {
+        self.reduce(axis, |values| values.iter().copied().sum())
+    }
+
+    pub fn mean(&self, axis: Axis) -> Matrix<T> {
+        self.reduce(axis, |values| values.iter().copied().sum::<T>() / T::from_usize(values.len()))
+    }
+
+    pub fn max(&self, axis: Axis) -> Matrix<T> {
+        self.reduce(axis, |values| values.iter().copied().fold(T::NEG_INFINITY, T::max))
+    }
+
+    pub fn min(&self, axis: Axis) -> Matrix<T> {
+        self.reduce(axis, |values| values.iter().copied().fold(T::INFINITY, T::min))
+    }
+
+    // Population variance, i.e. divided by the number of values.
+    pub fn variance(&self, axis: Axis) -> Matrix<T> {
+        self.reduce(axis, |values| {
+            let n = T::from_usize(values.len());
+            let mean = values.iter().copied().sum::<T>() / n;
+            values.iter().map(|&x| (x - mean) * (x - mean)).sum::<T>() / n
+        })
+    }
+
+    // Euclidean norm.
+    pub fn norm(&self, axis: Axis) -> Matrix<T> {
+        self.reduce(axis, |values| values.iter().map(|&x| x * x).sum::<T>().sqrt())
+    }
+
+    // Index of the largest value of each column (`Axis::Rows`) or row
+    // (`Axis::Cols`); the first one wins a tie.
+    pub fn argmax(&self, axis: Axis) -> Vec<usize> {
+        match axis {
+            Axis::Rows => (0..self.cols).map(|j| class_index(&self.data.iter().map(|row| row[j]).collect::<Vec<T>>())).collect(),
+            Axis::Cols => self.data.iter().map(|row| class_index(row)).collect(),
+        }
+    }
+
+    // Fallible versions of the constructors and operations above. They
+    // return `ShapeMismatch` or `EmptyDataset` where the others would panic.
+
+    pub fn try_create_matrix(rows: usize, cols: usize, data: Vec<Vec<T>>) -> Result<Matrix<T>> {
+        if rows == 0 || cols == 0 {
+            return Err(Error::EmptyDataset);
+        }
+        if data.len() != rows {
+            return Err(Error::ShapeMismatch {expected: (rows, cols), found: (data.len(), cols)});
+        }
+        if let Some(row) = data.iter().find(|row| row.len() != cols) {
+            return Err(Error::ShapeMismatch {expected: (rows, cols), found: (rows, row.len())});
+        }
+        Ok(Matrix::create_matrix(rows, cols, data))
+    }
+
+    pub fn try_create_zero_matrix(rows: usize, cols: usize) -> Result<Matrix<T>> {
+        if rows == 0 || cols == 0 {
+            return Err(Error::EmptyDataset);
+        }
+        Ok(Matrix::create_zero_matrix(rows, cols))
+    }
+
+    pub fn try_copy_into(&self, to: &mut Matrix<T>) -> Result<()> {
+        check_shape((self.rows, self.cols), to)?;
+        self.copy_into(to);
+        Ok(())
+    }
+
+    pub fn try_transpose_into(&self, into: &mut Matrix<T>) -> Result<()> {
+        check_shape((self.cols, self.rows), into)?;
+        self.transpose_into(into);
+        Ok(())
+    }
+
+    pub fn try_add(&self, other: &Matrix<T>) -> Result<Matrix<T>> {
+        check_shape((self.rows, self.cols), other)?;
+        Ok(self.add(other))
+    }
+
+    pub fn try_add_to(&self, to: &mut Matrix<T>) -> Result<()> {
+        check_shape((self.rows, self.cols), to)?;
+        self.add_to(to);
+        Ok(())
+    }
+
+    pub fn try_add_to_each_row(&self, other: &Matrix<T>) -> Result<Matrix<T>> {
+        check_shape((1, self.cols), other)?;
+        Ok(self.add_to_each_row(other))
+    }
+
+    pub fn try_multiply(&self, other: &Matrix<T>) -> Result<Matrix<T>> {
+        check_shape((self.cols, other.cols), other)?;
+        Ok(self.multiply(other))
+    }
+
+    pub fn try_multiply_into(&self, other: &Matrix<T>, into: &mut Matrix<T>) -> Result<()> {
+        check_shape((self.cols, other.cols), other)?;
+        check_shape((self.rows, other.cols), into)?;
+        self.multiply_into(other, into);
+        Ok(())
+    }
+
+    pub fn try_broadcast_add(&self, other: &Matrix<T>) -> Result<Matrix<T>> {
+        self.broadcast(other, |a, b| a + b)
+    }
+
+    pub fn try_broadcast_sub(&self, other: &Matrix<T>) -> Result<Matrix<T>> {
+        self.broadcast(other, |a, b| a - b)
+    }
+
+    pub fn try_broadcast_mul(&self, other: &Matrix<T>) -> Result<Matrix<T>> {
+        self.broadcast(other, |a, b| a * b)
+    }
+
+    pub fn try_broadcast_div(&self, other: &Matrix<T>) -> Result<Matrix<T>> {
+        self.broadcast(other, |a, b| a / b)
+    }
+
+    pub fn try_hadamard(&self, other: &Matrix<T>) -> Result<Matrix<T>> {
+        check_shape((self.rows, self.cols), other)?;
+        Ok(self.hadamard(other))
+    }
+
+    pub fn try_hadamard_into(&self, other: &Matrix<T>, into: &mut Matrix<T>) -> Result<()> {
+        check_shape((self.rows, self.cols), other)?;
+        check_shape((self.rows, self.cols), into)?;
+        self.hadamard_into(other, into);
+        Ok(())
+    }
+}
+
+// Element-wise arithmetic, scaling by a scalar and indexing by (row, col).
+// Mismatched shapes panic, as with the methods above.
+
+fn zip_with<T: Float, F>(a: &Matrix<T>, b: &Matrix<T>, func: F) -> Matrix<T>
+where F: Fn(T, T) -> T {
+    assert!(a.rows == b.rows && a.cols == b.cols);
+    Matrix::from_fn(a.rows, a.cols, |i, j| func(a.data[i][j], b.data[i][j]))
+}
+
+impl<T: Float> Add for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn add(self, other: &Matrix<T>) -> Matrix<T> {
+        zip_with(self, other, |a, b| a + b)
+    }
+}
+
+impl<T: Float> Add for Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn add(self, other: Matrix<T>) -> Matrix<T> {
+        &self + &other
+    }
+}
+
+impl<T: Float> Sub for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn sub(self, other: &Matrix<T>) -> Matrix<T> {
+        zip_with(self, other, |a, b| a - b)
+    }
+}
+
+impl<T: Float> Sub for Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn sub(self, other: Matrix<T>) -> Matrix<T> {
+        &self - &other
+    }
+}
+
+impl<T: Float> AddAssign<&Matrix<T>> for Matrix<T> {
+    fn add_assign(&mut self, other: &Matrix<T>) {
+        other.add_to(self);
+    }
+}
+
+impl<T: Float> SubAssign<&Matrix<T>> for Matrix<T> {
+    fn sub_assign(&mut self, other: &Matrix<T>) {
+        *self = &*self - other;
+    }
+}
+
+impl<T: Float> Mul<T> for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn mul(self, k: T) -> Matrix<T> {
+        let mut result = self.copy();
+        result.scalar_multiply(k);
+        result
+    }
+}
+
+impl<T: Float> Mul<T> for Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn mul(mut self, k: T) -> Matrix<T> {
+        self.scalar_multiply(k);
+        self
+    }
+}
+
+impl<T: Float> MulAssign<T> for Matrix<T> {
+    fn mul_assign(&mut self, k: T) {
+        self.scalar_multiply(k);
+    }
+}
+
+macro_rules! impl_scalar_mul {
+    ($t:ty) => {
+        impl Mul<Matrix<$t>> for $t {
+            type Output = Matrix<$t>;
+
+            fn mul(self, matrix: Matrix<$t>) -> Matrix<$t> {
+                matrix * self
+            }
+        }
+
+        impl Mul<&Matrix<$t>> for $t {
+            type Output = Matrix<$t>;
+
+            fn mul(self, matrix: &Matrix<$t>) -> Matrix<$t> {
+                matrix * self
+            }
+        }
+    };
+}
+
+impl_scalar_mul!(f32);
+impl_scalar_mul!(f64);
+
+impl<T: Float> Neg for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn neg(self) -> Matrix<T> {
+        self * -T::ONE
+    }
+}
+
+impl<T: Float> Neg for Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn neg(self) -> Matrix<T> {
+        self * -T::ONE
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        &self.data[row][col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        &mut self.data[row][col]
+    }
+}
+
+// One bracketed row per line with the columns aligned. The precision
+// defaults to 4 decimals, e.g. `format!("{:.2}", matrix)` for 2.
+impl<T: Float> fmt::Display for Matrix<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let precision = f.precision().unwrap_or(4);
+        let cells: Vec<Vec<String>> = self.data.iter()
+            .map(|row| row.iter().map(|x| format!("{:.*}", precision, x)).collect())
+            .collect();
+        let width = cells.iter().flatten().map(|cell| cell.len()).max().unwrap_or(0);
+        if cells.is_empty() {
+            return write!(f, "[]");
+        }
+        for (i, row) in cells.iter().enumerate() {
+            write!(f, "{}[", if i == 0 { "[" } else { " " })?;
+            for (j, cell) in row.iter().enumerate() {
+                write!(f, "{}{:>width$}", if j == 0 { "" } else { ", " }, cell, width = width)?;
+            }
+            write!(f, "]{}", if i + 1 == cells.len() { "]" } else { ",\n" })?;
+        }
+        Ok(())
+    }
+}
@@ -3,13 +3,34 @@ use crate::dataset::*;
 use crate::prelude::*;
 use crate::function::*;
 use crate::layer::*;
-use std::f32::MIN;
+use crate::npy::*;
+use crate::source::*;
+use crate::augment::*;
+use crate::precision::*;
+use crate::workspace::*;
+
+pub struct Network<T = f32> {
+    pub(crate) num_layers: usize,
+    pub(crate) layers: Vec<Layer<T>>,
+    pub(crate) num_connections: usize,
+    pub(crate) connections: Vec<Connection<T>>
+}
 
-pub struct Network {
-    num_layers: usize,
-    layers: Vec<Layer>,
-    num_connections: usize,
-    connections: Vec<Connection>
+// A copy with its own layer buffers, so passes on one do not touch the other.
+impl<T: Float> Clone for Network<T> {
+    fn clone(&self) -> Network<T> {
+        let layers: Vec<Layer<T>> = self.layers.iter()
+            .map(|layer| create_layer(layer.layer_type.clone(), layer.size, layer.activation))
+            .collect();
+        let connections = self.connections.iter().enumerate().map(|(i, connection)| {
+            let mut copy = create_connection(&layers[i], &layers[i+1]);
+            copy.weights = connection.weights.copy();
+            copy.bias = connection.bias.copy();
+            copy
+        }).collect();
+
+        Network {num_layers: self.num_layers, layers, num_connections: self.num_connections, connections}
+    }
 }
 
 pub enum LossFunction {
@@ -17,9 +38,60 @@ pub enum LossFunction {
     MeanSquaredError,
 }
 
-pub struct ParameterSet {
-    dataset: DataSet,
-    classes: DataSet,
+pub enum GradientClipping {
+    Value(f32),
+    GlobalNorm(f32),
+}
+
+// What the trainer does when a batch loss or gradient is NaN or infinite.
+// `Rollback` restores the weights of the last finite step and halves the learning rate.
+pub enum DivergencePolicy {
+    Ignore,
+    Halt,
+    Rollback,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Regularization {
+    None,
+    L1(f32),
+    L2(f32),
+    ElasticNet(f32, f32),
+}
+
+// How to train, apart from the rows trained on, so that training from a
+// `DataSource` or one batch at a time needs no data sets.
+pub struct TrainingConfig {
+    pub loss: LossFunction,
+    pub batch_size: usize,
+    pub learning_rate: f32,
+    pub search_time: f32,
+    pub regularization: f32,
+    pub momentum: f32,
+    pub max_iters: usize,
+    pub shuffle: bool,
+    pub verbose: bool,
+    pub clipping: Option<GradientClipping>,
+    pub divergence: DivergencePolicy,
+    // Per-connection penalties; connections past the end use L2(regularization).
+    pub layer_regularization: Vec<Regularization>,
+    pub regularize_bias: bool,
+    pub max_norm: Option<f32>,
+    // Skip the final batch of an epoch when it has fewer than `batch_size` rows.
+    pub drop_last: bool,
+    // Per-class weights, applied by the argmax of each target row.
+    pub class_weights: Option<Vec<f32>>,
+    // Overrides `shuffle` when set, e.g. to oversample rare classes.
+    pub sampler: Option<Sampler>,
+    // Applied to every training batch; switched off again once training ends.
+    pub augmentation: Option<Augmentation>,
+    // Precision in which weights, activations and gradients are held during the passes.
+    pub precision: Precision,
+    pub loss_scaling: Option<LossScaling>
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_training_config(
     loss: LossFunction,
     batch_size: usize,
     learning_rate: f32,
@@ -28,16 +100,204 @@ pub struct ParameterSet {
     momentum: f32,
     max_iters: usize,
     shuffle: bool,
-    verbose: bool
+    verbose: bool) -> TrainingConfig {
+
+    TrainingConfig {
+        loss,
+        batch_size,
+        learning_rate,
+        search_time,
+        regularization,
+        momentum,
+        max_iters,
+        shuffle,
+        verbose,
+        clipping: None,
+        divergence: DivergencePolicy::Halt,
+        layer_regularization: Vec::new(),
+        regularize_bias: false,
+        max_norm: None,
+        drop_last: false,
+        class_weights: None,
+        sampler: None,
+        augmentation: None,
+        precision: Precision::Full,
+        loss_scaling: None
+    }
+}
+
+// In-memory training data together with how to train on it.
+pub struct ParameterSet<T = f32> {
+    pub dataset: DataSet<T>,
+    pub classes: DataSet<T>,
+    // Per-row weights for `dataset`, multiplied into each row's loss and gradient.
+    pub sample_weights: Option<Vec<f32>>,
+    pub config: TrainingConfig,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_parameter_set<T: Float>(
+    dataset: DataSet<T>,
+    classes: DataSet<T>,
+    loss: LossFunction,
+    batch_size: usize,
+    learning_rate: f32,
+    search_time: f32,
+    regularization: f32,
+    momentum: f32,
+    max_iters: usize,
+    shuffle: bool,
+    verbose: bool) -> ParameterSet<T> {
+
+    ParameterSet {
+        dataset,
+        classes,
+        sample_weights: None,
+        config: create_training_config(loss, batch_size, learning_rate, search_time, regularization, momentum, max_iters, shuffle, verbose),
+    }
+}
+
+impl TrainingConfig {
+    // Checks the settings that do not depend on the network or the data.
+    pub fn validate(&self) -> Result<()> {
+        if self.batch_size == 0 {
+            return Err(Error::InvalidConfig("batch size must be at least 1".to_string()));
+        }
+        if self.max_iters == 0 {
+            return Err(Error::InvalidConfig("max_iters must be at least 1".to_string()));
+        }
+        if !(self.learning_rate.is_finite() && self.learning_rate > 0.0) {
+            return Err(Error::InvalidConfig(format!("learning rate {} is not positive", self.learning_rate)));
+        }
+        if !(0.0..1.0).contains(&self.momentum) {
+            return Err(Error::InvalidConfig(format!("momentum {} is not in [0, 1)", self.momentum)));
+        }
+        if !(self.search_time >= 0.0 && self.regularization >= 0.0) {
+            return Err(Error::InvalidConfig("search time and regularization must not be negative".to_string()));
+        }
+        check_weights(self.class_weights.iter().flatten())
+    }
+
+    pub fn regularization_for(&self, connection: usize) -> Regularization {
+        match self.layer_regularization.get(connection) {
+            Some(regularization) => *regularization,
+            None if self.regularization > 0.0 => Regularization::L2(self.regularization),
+            None => Regularization::None,
+        }
+    }
+}
+
+impl<T: Float> ParameterSet<T> {
+    pub fn validate(&self) -> Result<()> {
+        self.config.validate()?;
+        check_weights(self.sample_weights.iter().flatten())
+    }
+}
+
+fn check_weights<'a, I: Iterator<Item = &'a f32>>(mut weights: I) -> Result<()> {
+    match weights.find(|w| !(w.is_finite() && **w >= 0.0)) {
+        Some(weight) => Err(Error::InvalidConfig(format!("weight {} is not a finite non-negative number", weight))),
+        None => Ok(()),
+    }
+}
+
+impl Regularization {
+    pub fn penalty<T: Float>(&self, weights: &Matrix<T>) -> T {
+        let (l1, l2) = self.strengths();
+        let (l1, l2) = (T::from_f32(l1), T::from_f32(l2));
+        let half = T::from_f32(0.5);
+        weights.data.iter().flatten().map(|&w| l1 * w.abs() + half * l2 * w * w).sum()
+    }
+
+    pub fn gradient<T: Float>(&self, weights: &Matrix<T>) -> Matrix<T> {
+        let mut gradient = weights.copy();
+        self.gradient_into(weights, &mut gradient);
+        gradient
+    }
+
+    pub fn gradient_into<T: Float>(&self, weights: &Matrix<T>, into: &mut Matrix<T>) {
+        let (l1, l2) = self.strengths();
+        let (l1, l2) = (T::from_f32(l1), T::from_f32(l2));
+        weights.copy_into(into);
+        into.transform(|w| l1 * sign(w) + l2 * w);
+    }
+
+    fn strengths(&self) -> (f32, f32) {
+        match *self {
+            Regularization::None => (0.0, 0.0),
+            Regularization::L1(l1) => (l1, 0.0),
+            Regularization::L2(l2) => (0.0, l2),
+            Regularization::ElasticNet(l1, l2) => (l1, l2),
+        }
+    }
+}
+
+fn sign<T: Float>(x: T) -> T {
+    if x > T::ZERO { T::ONE } else if x < T::ZERO { -T::ONE } else { T::ZERO }
+}
+
+// Rescales each column, i.e. the incoming weights of one neuron, to at most `max_norm`.
+pub fn apply_max_norm<T: Float>(weights: &mut Matrix<T>, max_norm: f32) {
+    let max_norm = T::from_f32(max_norm);
+    for j in 0..weights.cols {
+        let norm = (0..weights.rows).map(|i| weights.get(i, j) * weights.get(i, j)).sum::<T>().sqrt();
+        if norm > max_norm {
+            for i in 0..weights.rows {
+                let val = weights.get(i, j) * max_norm / norm;
+                weights.set(i, j, val);
+            }
+        }
+    }
+}
+
+impl GradientClipping {
+    pub fn apply<T: Float>(&self, weights: &mut [Matrix<T>], biases: &mut [Matrix<T>]) {
+        match *self {
+            GradientClipping::Value(limit) => {
+                let limit = T::from_f32(limit);
+                for gradient in weights.iter_mut().chain(biases.iter_mut()) {
+                    gradient.transform(|x| x.clamp(-limit, limit));
+                }
+            },
+            GradientClipping::GlobalNorm(max_norm) => {
+                let max_norm = T::from_f32(max_norm);
+                let norm = global_norm(weights, biases);
+                if norm > max_norm {
+                    for gradient in weights.iter_mut().chain(biases.iter_mut()) {
+                        gradient.scalar_multiply(max_norm / norm);
+                    }
+                }
+            },
+        }
+    }
+}
+
+pub fn global_norm<T: Float>(weights: &[Matrix<T>], biases: &[Matrix<T>]) -> T {
+    weights.iter().chain(biases.iter())
+        .flat_map(|gradient| gradient.data.iter().flatten())
+        .map(|&x| x * x)
+        .sum::<T>()
+        .sqrt()
+}
+
+fn activation_label<T: Float>(activation: Option<Activation<T>>) -> &'static str {
+    match activation {
+        None => "none",
+        Some(func) => find_function_name(func).unwrap_or("custom"),
+    }
+}
+
+fn all_finite<T: Float>(matrices: &[Matrix<T>]) -> bool {
+    matrices.iter().all(|m| m.data.iter().flatten().all(|x| x.is_finite()))
 }
 
-pub fn create_network(
+pub fn create_network<T: Float>(
     num_features: usize, 
     num_hidden_layers: usize, 
     hidden_sizes: Vec<usize>,
-    hidden_activations: Vec<Option<Activation>>,
+    hidden_activations: Vec<Option<Activation<T>>>,
     num_outputs: usize,
-    output_activation: Option<Activation>) -> Network {
+    output_activation: Option<Activation<T>>) -> Network<T> {
 
     assert!(num_features > 0 && num_outputs > 0);
     let num_layers = num_hidden_layers + 2;
@@ -53,7 +313,7 @@ pub fn create_network(
     }
 
     let num_connections = num_layers - 1;
-    let mut connections: Vec<Connection> = Vec::new();
+    let mut connections: Vec<Connection<T>> = Vec::new();
     for i in 0..num_connections {
         connections.push(create_connection(&layers[i], &layers[i+1]));
         connections[i].init();
@@ -62,81 +322,292 @@ pub fn create_network(
     Network {num_layers, layers, num_connections, connections}
 }
 
-impl Network {
-    pub fn forward_pass(&mut self, input: Rc<RefCell<Matrix>>) {
-        {
-            let mut input_layer = &mut self.layers[0];
-            assert!(input.borrow().cols == input_layer.input.borrow().cols);
-            input_layer.input = input;
+// Like `create_network`, but reports an inconsistent layout as `InvalidConfig`.
+pub fn try_create_network<T: Float>(
+    num_features: usize,
+    num_hidden_layers: usize,
+    hidden_sizes: Vec<usize>,
+    hidden_activations: Vec<Option<Activation<T>>>,
+    num_outputs: usize,
+    output_activation: Option<Activation<T>>) -> Result<Network<T>> {
+
+    if num_features == 0 || num_outputs == 0 {
+        return Err(Error::InvalidConfig("a network needs at least one input and one output".to_string()));
+    }
+    if hidden_sizes.len() != num_hidden_layers || hidden_activations.len() != num_hidden_layers {
+        return Err(Error::InvalidConfig(format!(
+            "{} hidden layers but {} sizes and {} activations",
+            num_hidden_layers, hidden_sizes.len(), hidden_activations.len())));
+    }
+    if hidden_sizes.contains(&0) {
+        return Err(Error::InvalidConfig("hidden layers must not be empty".to_string()));
+    }
+
+    Ok(create_network(num_features, num_hidden_layers, hidden_sizes, hidden_activations, num_outputs, output_activation))
+}
+
+impl<T: Float> Network<T> {
+    pub(crate) fn check_input(&self, input: &Matrix<T>) -> Result<()> {
+        if input.rows == 0 {
+            return Err(Error::EmptyDataset);
+        }
+        if input.cols != self.layers[0].size {
+            return Err(Error::ShapeMismatch {expected: (input.rows, self.layers[0].size), found: (input.rows, input.cols)});
         }
+        Ok(())
+    }
 
-        let mut tmp;
-        let mut tmp2;
-        for i in 0..self.num_connections {
-            tmp = self.layers[i].input.borrow().multiply(&self.connections[i].weights);
-            tmp2 = tmp.add_to_each_row(&self.connections[i].bias);
+    fn check_targets(&self, input: &Matrix<T>, targets: &Matrix<T>) -> Result<()> {
+        self.check_input(input)?;
+        let expected = (input.rows, self.layers[self.num_layers-1].size);
+        if (targets.rows, targets.cols) != expected {
+            return Err(Error::ShapeMismatch {expected, found: (targets.rows, targets.cols)});
+        }
+        Ok(())
+    }
+
+    pub fn try_forward_pass(&mut self, input: Rc<RefCell<Matrix<T>>>) -> Result<()> {
+        self.check_input(&input.borrow())?;
+        self.forward_pass(input);
+        Ok(())
+    }
 
-            let mut layer = &mut self.connections[i].to;
-            layer.input = Rc::new(RefCell::new(tmp2));
-            layer.activate();
+    pub fn forward_pass(&mut self, input: Rc<RefCell<Matrix<T>>>) {
+        // The pass writes into the layer buffers, so an input that is one of them is copied first.
+        if self.layers.iter().any(|layer| Rc::ptr_eq(&input, &layer.input)) {
+            let copy = input.borrow().copy();
+            self.forward(&copy, Precision::Full);
+        } else {
+            self.forward(&input.borrow(), Precision::Full);
         }
     }
 
-    pub fn cross_entropy_loss(&self, prediction: &Matrix, actual: Rc<RefCell<DataSet>>, regularization: f32) -> f32 {
-        assert!(prediction.rows == actual.borrow().rows);
-        assert!(prediction.cols == actual.borrow().cols);
-        let mut total_err: f32 = 0.0;
-        for i in 0..prediction.rows {
-            let mut cur_err: f32 = 0.0;
-            for j in 0..prediction.cols {
-                cur_err += actual.borrow().data[i][j] * f32::max(MIN, prediction.get(i, j)).ln();
+    // Writes every layer's output in place; only allocates when the number
+    // of rows differs from the previous pass. Each output is rounded to
+    // `precision`, as if the activations were stored in it.
+    fn forward(&mut self, input: &Matrix<T>, precision: Precision) {
+        assert!(input.cols == self.layers[0].size);
+        for layer in self.layers.iter() {
+            let mut output = layer.input.borrow_mut();
+            if output.rows != input.rows {
+                *output = Matrix::create_zero_matrix(input.rows, layer.size);
             }
+        }
+        input.copy_into(&mut self.layers[0].input.borrow_mut());
+        precision.round_matrix(&mut self.layers[0].input.borrow_mut());
 
-            total_err += cur_err;
+        for i in 0..self.num_connections {
+            {
+                // Layers share their input with the connections built from them.
+                let from = self.layers[i].input.borrow();
+                let mut to = self.layers[i+1].input.borrow_mut();
+                from.multiply_into(&self.connections[i].weights, &mut to);
+                self.connections[i].bias.add_to_each_row_of(&mut to);
+            }
+            self.layers[i+1].activate();
+            precision.round_matrix(&mut self.layers[i+1].input.borrow_mut());
+        }
+    }
+
+    pub fn cross_entropy_loss(&self, prediction: &Matrix<T>, actual: Rc<RefCell<DataSet<T>>>, regularization: f32) -> T {
+        self.cross_entropy(prediction, &actual.borrow()) + self.l2_penalty(regularization)
+    }
+
+    pub fn mean_squared_error(&self, prediction: &Matrix<T>, actual: Rc<RefCell<DataSet<T>>>, regularization: f32) -> T {
+        self.squared_error(prediction, &actual.borrow()) + self.l2_penalty(regularization)
+    }
+
+    // The loss the trainer minimises under `config`: each row is scaled by
+    // the weight of its class, and the penalty of the configured per-layer
+    // regularization is added, as in `regularization_penalty`.
+    pub fn cross_entropy_loss_weighted(&self, prediction: &Matrix<T>, actual: Rc<RefCell<DataSet<T>>>, config: &TrainingConfig) -> T {
+        self.weighted_loss(&LossFunction::CrossEntropy, prediction, &actual.borrow(), config) + self.regularization_penalty(config)
+    }
+
+    pub fn mean_squared_error_weighted(&self, prediction: &Matrix<T>, actual: Rc<RefCell<DataSet<T>>>, config: &TrainingConfig) -> T {
+        self.weighted_loss(&LossFunction::MeanSquaredError, prediction, &actual.borrow(), config) + self.regularization_penalty(config)
+    }
+
+    // The penalty whose gradient the trainer adds for each connection,
+    // including the biases when `config.regularize_bias` is set.
+    pub fn regularization_penalty(&self, config: &TrainingConfig) -> T {
+        let mut penalty = T::ZERO;
+        for i in 0..self.num_connections {
+            let regularization = config.regularization_for(i);
+            penalty += regularization.penalty(&self.connections[i].weights);
+            if config.regularize_bias {
+                penalty += regularization.penalty(&self.connections[i].bias);
+            }
         }
+        penalty
+    }
 
-        let mut reg_err: f32 = 0.0;
+    fn l2_penalty(&self, regularization: f32) -> T {
+        let mut reg_err = T::ZERO;
         for i in 0..self.num_connections {
             let weights = &self.connections[i].weights;
             for j in 0..weights.rows {
                 for k in 0..weights.cols {
-                    reg_err += weights.get(j,k) * weights.get(j, k);
+                    reg_err += weights.get(j, k) * weights.get(j, k);
                 }
             }
         }
+        T::from_f32(regularization * 0.5) * reg_err
+    }
+
+    fn weighted_loss(&self, loss: &LossFunction, prediction: &Matrix<T>, actual: &DataSet<T>, config: &TrainingConfig) -> T {
+        let class_weights = match &config.class_weights {
+            Some(class_weights) => class_weights,
+            None => return self.loss_value(loss, prediction, actual),
+        };
+        assert!(prediction.rows == actual.rows);
+        assert!(class_weights.len() == actual.cols);
+        let mut total_err = T::ZERO;
+        for i in 0..actual.rows {
+            let predicted = Matrix::create_matrix(1, prediction.cols, vec![prediction.data[i].clone()]);
+            let target = Matrix::create_matrix(1, actual.cols, vec![actual.data[i].clone()]);
+            let weight = T::from_f32(class_weights[class_index(&actual.data[i])]);
+            total_err += weight * self.loss_value(loss, &predicted, &target);
+        }
+        total_err / T::from_usize(actual.rows)
+    }
 
-        ((-1.0 / (actual.borrow().rows as f32)) * total_err) + (regularization * 0.5 * reg_err)
+    fn loss_value(&self, loss: &LossFunction, prediction: &Matrix<T>, actual: &DataSet<T>) -> T {
+        match loss {
+            LossFunction::CrossEntropy => self.cross_entropy(prediction, actual),
+            LossFunction::MeanSquaredError => self.squared_error(prediction, actual),
+        }
     }
 
-    pub fn mean_squared_error(&self, prediction: &Matrix, actual: Rc<RefCell<DataSet>>, regularization: f32) -> f32 {
-        assert!(prediction.rows == actual.borrow().rows);
-        assert!(prediction.cols == actual.borrow().cols);
-        let mut total_err: f32 = 0.0;
+    fn cross_entropy(&self, prediction: &Matrix<T>, actual: &DataSet<T>) -> T {
+        assert!(prediction.rows == actual.rows);
+        assert!(prediction.cols == actual.cols);
+        let mut total_err = T::ZERO;
         for i in 0..prediction.rows {
-            let mut cur_err: f32 = 0.0;
+            let mut cur_err = T::ZERO;
             for j in 0..prediction.cols {
-                let tmp = actual.borrow().data[i][j] - prediction.get(i, j);
-                cur_err += tmp;
+                cur_err += actual.data[i][j] * T::MIN_POSITIVE.max(prediction.get(i, j)).ln();
             }
 
             total_err += cur_err;
         }
 
-        let mut reg_err: f32 = 0.0;
-        for i in 0..self.num_connections {
-            let weights = &self.connections[i].weights;
-            for j in 0..weights.rows {
-                for k in 0..weights.cols {
-                    reg_err += weights.get(j,k) * weights.get(j, k);
-                }
+        (-T::ONE / T::from_usize(actual.rows)) * total_err
+    }
+
+    fn squared_error(&self, prediction: &Matrix<T>, actual: &DataSet<T>) -> T {
+        assert!(prediction.rows == actual.rows);
+        assert!(prediction.cols == actual.cols);
+        let mut total_err = T::ZERO;
+        for i in 0..prediction.rows {
+            let mut cur_err = T::ZERO;
+            for j in 0..prediction.cols {
+                let tmp = actual.data[i][j] - prediction.get(i, j);
+                cur_err += tmp * tmp;
             }
+
+            total_err += cur_err;
         }
 
-        ((0.5 / (actual.borrow().rows as f32)) * total_err) + (regularization * 0.5 * reg_err)
+        (T::from_f32(0.5) / T::from_usize(actual.rows)) * total_err
     }
     
-    pub fn get_output(&self) -> Rc<RefCell<Matrix>> {
-        self.layers[self.num_layers-1].input.clone()
+    // A copy of this network with weights and activations in another float
+    // type, e.g. to fine-tune or gradient-check an f32 model in f64. Custom
+    // activations exist for one type only, so a network using one cannot be
+    // cast and gives `InvalidConfig`.
+    pub fn cast<U: Float>(&self) -> Result<Network<U>> {
+        let mut layers: Vec<Layer<U>> = Vec::new();
+        for (i, layer) in self.layers.iter().enumerate() {
+            let activation = match layer.activation {
+                None => None,
+                Some(activation) => match find_function_name(activation).and_then(find_function_by_name::<U>) {
+                    Some(cast) => Some(cast),
+                    None => return Err(Error::InvalidConfig(format!("layer {} has a custom activation that cannot be cast", i))),
+                },
+            };
+            layers.push(create_layer(layer.layer_type.clone(), layer.size, activation));
+        }
+        let connections = self.connections.iter().enumerate().map(|(i, connection)| {
+            let mut cast = create_connection(&layers[i], &layers[i+1]);
+            cast.weights = connection.weights.cast();
+            cast.bias = connection.bias.cast();
+            cast
+        }).collect();
+
+        Ok(Network {num_layers: self.num_layers, layers, num_connections: self.num_connections, connections})
+    }
+
+    pub fn num_layers(&self) -> usize {
+        self.num_layers
+    }
+
+    pub fn layers(&self) -> &[Layer<T>] {
+        &self.layers
+    }
+
+    pub fn connections(&self) -> &[Connection<T>] {
+        &self.connections
+    }
+
+    pub fn layer_sizes(&self) -> Vec<usize> {
+        self.layers.iter().map(|layer| layer.size).collect()
+    }
+
+    // "none" for layers without an activation, "custom" for ones that are not built in.
+    pub fn activation_names(&self) -> Vec<&'static str> {
+        self.layers.iter().map(|layer| activation_label(layer.activation)).collect()
+    }
+
+    // Weights and bias of connection `i`, which feeds layer `i + 1`.
+    pub fn weights(&self, i: usize) -> &Matrix<T> {
+        &self.connections[i].weights
+    }
+
+    pub fn bias(&self, i: usize) -> &Matrix<T> {
+        &self.connections[i].bias
+    }
+
+    // Every trainable matrix: the weights then the bias of each connection in order.
+    pub fn parameters(&self) -> impl Iterator<Item = &Matrix<T>> {
+        self.connections.iter().flat_map(|connection| [&connection.weights, &connection.bias])
+    }
+
+    pub fn num_parameters(&self) -> usize {
+        self.parameters().map(|parameter| parameter.rows * parameter.cols).sum()
+    }
+
+    // A table of every layer with its size, activation and the number of
+    // parameters in the connection feeding it.
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{:<6} {:<7} {:>6}  {:<10} {:>10}\n", "Layer", "Type", "Size", "Activation", "Parameters");
+        for (i, layer) in self.layers.iter().enumerate() {
+            let layer_type = match layer.layer_type {
+                LayerType::INPUT => "input",
+                LayerType::HIDDEN => "hidden",
+                LayerType::OUTPUT => "output",
+            };
+            let parameters = match i {
+                0 => 0,
+                _ => {
+                    let connection = &self.connections[i-1];
+                    connection.weights.rows * connection.weights.cols + connection.bias.cols
+                }
+            };
+            summary += &format!("{:<6} {:<7} {:>6}  {:<10} {:>10}\n", i, layer_type, layer.size, activation_label(layer.activation), parameters);
+        }
+        summary += &format!("Total parameters: {}\n", self.num_parameters());
+        summary
+    }
+
+    pub fn print_summary(&self) {
+        print!("{}", self.summary());
+    }
+
+    // A snapshot of the output of the last forward pass. Later passes reuse
+    // the layer buffers in place and do not change it.
+    pub fn get_output(&self) -> Rc<RefCell<Matrix<T>>> {
+        Rc::new(RefCell::new(self.layers[self.num_layers-1].input.borrow().copy()))
     }
 
     pub fn predict(&mut self) -> Vec<i32> {
@@ -156,210 +627,347 @@ impl Network {
         predictions
     }
 
-    pub fn accuracy(&mut self, dataset: Rc<RefCell<Matrix>>, classes: Rc<RefCell<Matrix>>) -> f32 {
+    pub fn try_accuracy(&mut self, dataset: Rc<RefCell<Matrix<T>>>, classes: Rc<RefCell<Matrix<T>>>) -> Result<f32> {
+        self.check_targets(&dataset.borrow(), &classes.borrow())?;
+        Ok(self.accuracy(dataset, classes))
+    }
+
+    pub fn accuracy(&mut self, dataset: Rc<RefCell<Matrix<T>>>, classes: Rc<RefCell<Matrix<T>>>) -> f32 {
         assert!(dataset.borrow().rows == classes.borrow().rows);
         assert!(classes.borrow().cols == self.layers[self.num_layers-1].size);
         self.forward_pass(dataset.clone());
         let predictions = self.predict();
         let mut num_correct: f32 = 0.0;
-        for i in 0..dataset.borrow().rows {
-            if (classes.borrow().data[i][predictions[i] as usize] == 1.0) {
-                num_correct = num_correct + 1.0;
+        for (i, &prediction) in predictions.iter().enumerate() {
+            if (classes.borrow().data[i][prediction as usize] == T::ONE) {
+                num_correct += 1.0;
             }
         }
 
         num_correct / (classes.borrow().rows as f32)
     }
 
-    pub fn batch_gradient_descent(&mut self, params: &mut ParameterSet) {
-        assert!(self.layers[0].size == params.dataset.cols);
-        assert!(params.dataset.rows == params.classes.rows);
-        assert!(self.layers[self.num_layers-1].size == params.classes.cols);
-        assert!(params.batch_size <= params.dataset.rows);
-        assert!(params.max_iters >= 1);
+    pub fn batch_gradient_descent(&mut self, params: &mut ParameterSet<T>) -> Result<()> {
+        self.check_targets(&params.dataset, &params.classes)?;
+        params.validate()?;
+        if params.config.batch_size > params.dataset.rows {
+            return Err(Error::InvalidConfig(format!("batch size {} exceeds the {} training rows", params.config.batch_size, params.dataset.rows)));
+        }
+        if let Some(weights) = &params.sample_weights {
+            if weights.len() != params.dataset.rows {
+                return Err(Error::ShapeMismatch {expected: (params.dataset.rows, 1), found: (weights.len(), 1)});
+            }
+        }
 
-        let mut errori: Vec<Matrix> = Vec::new();
-        let mut dWi: Vec<Matrix> = Vec::new();
-        let mut dbi: Vec<Matrix> = Vec::new();
-        let mut regi: Vec<Matrix> = Vec::new();
-        let mut before_output_t = Matrix::create_zero_matrix(self.layers[self.num_layers-2].size, 1);
+        // The source takes the rows for the duration of training and hands them back afterwards.
+        let dataset = std::mem::replace(&mut params.dataset, DataSet {rows: 0, cols: 0, data: Vec::new()});
+        let classes = std::mem::replace(&mut params.classes, DataSet {rows: 0, cols: 0, data: Vec::new()});
+        let mut source = create_memory_source(dataset, classes, params.config.shuffle);
+        source.weights = params.sample_weights.clone();
+        if let Some(sampler) = &params.config.sampler {
+            source.sampler = sampler.clone();
+        }
+        let result = self.train_from_source(&mut source, &mut params.config);
+        params.dataset = source.features;
+        params.classes = source.targets;
 
-        for i in 0..self.num_connections {
-            errori.push(Matrix::create_zero_matrix(1, self.layers[i].size));
-            dWi.push(Matrix::create_zero_matrix(self.connections[i].weights.rows, self.connections[i].weights.cols));
-            dbi.push(Matrix::create_zero_matrix(1, self.connections[i].bias.cols));
-            regi.push(Matrix::create_zero_matrix(self.connections[i].weights.rows, self.connections[i].weights.cols));
-        }
-
-        errori.push(Matrix::create_zero_matrix(1, self.layers[self.num_connections].size));
-
-        let num_hidden = self.num_layers - 2;
-        let mut WTi: Vec<Matrix> = Vec::new();
-        let mut error_last_ti: Vec<Matrix> = Vec::new();
-        let mut fprimei: Vec<Matrix> = Vec::new();
-        let mut input_ti: Vec<Matrix> = Vec::new();
-        if (num_hidden > 0) {
-            for k in 0..num_hidden {
-                WTi.push(Matrix::create_zero_matrix(self.connections[k+1].weights.cols, self.connections[k+1].weights.rows));
-                error_last_ti.push(Matrix::create_zero_matrix(1, WTi[k].cols));
-                fprimei.push(Matrix::create_zero_matrix(1, self.connections[k].to.size));
-                input_ti.push(Matrix::create_zero_matrix(self.connections[k].from.size, 1));
+        result
+    }
+
+    // Trains on mini-batches of `config.batch_size` rows read from `source`,
+    // which is reset at the start of every epoch. `config.shuffle` and
+    // `config.sampler` are left to the source.
+    pub fn train_from_source<S: DataSource<T> + ?Sized>(&mut self, source: &mut S, config: &mut TrainingConfig) -> Result<()> {
+        config.validate()?;
+        if source.num_features() != self.layers[0].size {
+            return Err(Error::ShapeMismatch {expected: (1, self.layers[0].size), found: (1, source.num_features())});
+        }
+        if source.num_targets() != self.layers[self.num_layers-1].size {
+            return Err(Error::ShapeMismatch {expected: (1, self.layers[self.num_layers-1].size), found: (1, source.num_targets())});
+        }
+        if let Some(augmentation) = &config.augmentation {
+            augmentation.check(source.num_features())?;
+        }
+        if let Some(class_weights) = &config.class_weights {
+            if class_weights.len() != source.num_targets() {
+                return Err(Error::ShapeMismatch {expected: (1, source.num_targets()), found: (1, class_weights.len())});
             }
         }
 
-        let mut dWi_avg: Vec<Matrix> = Vec::new();
-        let mut dbi_avg: Vec<Matrix> = Vec::new();
-        let mut dWi_last: Vec<Matrix> = Vec::new();
-        let mut dbi_last: Vec<Matrix> = Vec::new();
-        for i in 0..self.num_connections {
-            dWi_avg.push(Matrix::create_zero_matrix(self.connections[i].weights.rows, self.connections[i].weights.cols));
-            dbi_avg.push(Matrix::create_zero_matrix(1, self.connections[i].bias.cols));
-            dWi_last.push(Matrix::create_zero_matrix(self.connections[i].weights.rows, self.connections[i].weights.cols));
-            dbi_last.push(Matrix::create_zero_matrix(1, self.connections[i].bias.cols));
-        }
-
-        let num_batches = params.dataset.rows / params.batch_size + if (params.dataset.rows % params.batch_size != 0) {1} else {0};
-        let (mut training, mut batch, mut epoch, mut layer): (usize, usize, usize, usize) ;
-        let mut batches: Vec<Batch> = Vec::new();
-        let mut classBatches: Vec<DataSet> = Vec::new();
-        epoch = 1;
-        while epoch <= params.max_iters {
-            if (params.shuffle) {
-                shuffle_together(&mut params.dataset, &mut params.classes);
+        if let Some(augmentation) = config.augmentation.as_mut() {
+            augmentation.set_training(true);
+        }
+        let result = self.train_epochs(source, config);
+        if let Some(augmentation) = config.augmentation.as_mut() {
+            augmentation.set_training(false);
+        }
+
+        result
+    }
+
+    fn train_epochs<S: DataSource<T> + ?Sized>(&mut self, source: &mut S, config: &mut TrainingConfig) -> Result<()> {
+        let mut workspace = create_workspace(self);
+        while workspace.epoch <= config.max_iters {
+            source.reset()?;
+            workspace.batch = 0;
+            while let Some((mut batch_training, mut batch_classes, batch_weights)) = source.next_weighted_batch(config.batch_size)? {
+                if config.drop_last && batch_training.rows < config.batch_size {
+                    break;
+                }
+                if let Some(augmentation) = config.augmentation.as_mut() {
+                    augmentation.apply(&mut batch_training, &mut batch_classes);
+                }
+                let loss = self.train_step(&mut workspace, &batch_training, &batch_classes, Some(&batch_weights), config)?;
+                if config.verbose {
+                    println!("Epoch {}, batch {}: loss {}", workspace.epoch, workspace.batch, loss);
+                }
+                workspace.batch += 1;
             }
 
-            let data_batches = create_batches(&params.dataset, num_batches);
-            let class_batches = create_batches(&params.classes, num_batches);
-            for batch in 0..num_batches {
-                if (epoch > params.max_iters) { break; }  
-                let cur_batch_size = if batch == num_batches - 1 {
-                    if params.dataset.rows % params.batch_size != 0 {
-                        params.dataset.rows % params.batch_size
-                    } else {
-                        params.batch_size
-                    }
-                } else {
-                    params.batch_size
-                };
+            workspace.epoch += 1;
+        }
 
-                let batch_training = &data_batches[batch];
-                let batch_classes = &class_batches[batch];
-                let split_training = split_rows(batch_training);
-                let split_classes = split_rows(batch_classes);
-
-                for training in 0..cur_batch_size {
-                    let row_idx = split_training[training].row_idx;
-                    let row_example = vec![params.dataset.data[row_idx].clone(); 1];
-                    let example: Matrix = Matrix::create_matrix(1, params.dataset.cols, row_example);
-                    let row_idx_target = split_classes[training].row_idx;
-                    let row_target = vec![params.classes.data[row_idx_target].clone(); 1];
-                    let target = Matrix::create_matrix(1, params.dataset.cols, row_target);
-                    self.forward_pass(Rc::new(RefCell::new(example)));
-
-                    for layer in (self.num_layers-1)..0 {
-                        let to = &mut self.layers[layer];
-                        let con = &mut self.connections;
-                        if (layer == self.num_layers - 1) {
-                            to.input.borrow().copy_into(&mut errori[layer]);
-                            match params.loss {
-                                LossFunction::CrossEntropy => {
-                                    for i in 0..errori[layer].rows {
-                                        for j in 0..errori[layer].cols {
-                                            errori[layer].data[i][j] -= target.data[i][j];
-                                        }
-                                    }
-                                },
-                                _ => {
-                                        for i in 0..errori[layer].rows {
-                                            for j in 0..errori[layer].cols {
-                                                errori[layer].data[i][j] -= target.data[i][j];
-                                            }
-                                        }
-                                }
-                            }
-
-                            con[layer-1].from.input.borrow().transpose_into(&mut before_output_t);
-                            before_output_t.multiply_into(&errori[layer], &mut dWi[layer-1]);
-                            errori[layer].copy_into(&mut dbi[layer-1]);
-                        }
-
-                        else {
-                            let hidden_layer = layer-1;
-                            con[layer].weights.transpose_into(&mut WTi[hidden_layer]);
-                            errori[layer + 1].multiply_into(&WTi[hidden_layer], &mut error_last_ti[hidden_layer]);
-                            con[layer-1].to.input.borrow().copy_into(&mut fprimei[hidden_layer]);
-                            let derivative = activation_derivative(con[layer-1].to.activation);
-                            for i in 0..fprimei[hidden_layer].rows {
-                                for j in 0..fprimei[hidden_layer].cols {
-                                    fprimei[hidden_layer].data[i][j] = derivative(fprimei[hidden_layer].data[i][j]);
-                                }
-                            }
-                            error_last_ti[hidden_layer].hadamard_into(&fprimei[hidden_layer], &mut errori[layer]);
-                            con[layer-1].from.input.borrow().transpose_into(&mut input_ti[hidden_layer]);
-                            input_ti[hidden_layer].multiply_into(&errori[layer], &mut dWi[layer - 1]);
-                            errori[layer].copy_into(&mut dbi[layer-1]);
-                        }
-                    }
+        Ok(())
+    }
 
-                    for i in 0..self.num_connections {
-                        dWi[i].add_to(&mut dWi_avg[i]);
-                        dbi[i].add_to(&mut dbi_avg[i]);
-                    }
+    // One update from a mini-batch, using only the buffers in `workspace`, so
+    // that repeated steps on same-sized networks do not allocate. `weights`
+    // scales each row's loss and gradient. Returns the mean weighted row loss.
+    // `config.shuffle`, `config.sampler` and `config.augmentation` are not used.
+    pub fn train_step(
+        &mut self,
+        workspace: &mut Workspace<T>,
+        features: &Matrix<T>,
+        targets: &Matrix<T>,
+        weights: Option<&[f32]>,
+        config: &mut TrainingConfig) -> Result<T> {
+
+        self.check_targets(features, targets)?;
+        if !workspace.fits(self) {
+            return Err(Error::InvalidConfig("the workspace was created for a different network".to_string()));
+        }
+        if let Some(weights) = weights {
+            if weights.len() != features.rows {
+                return Err(Error::ShapeMismatch {expected: (features.rows, 1), found: (weights.len(), 1)});
+            }
+        }
+        if let Some(class_weights) = &config.class_weights {
+            if class_weights.len() != targets.cols {
+                return Err(Error::ShapeMismatch {expected: (1, targets.cols), found: (1, class_weights.len())});
+            }
+        }
 
-                    before_output_t.to_zero();
+        let (epoch, batch) = (workspace.epoch, workspace.batch);
+        let reduced = config.precision != Precision::Full;
+        if reduced {
+            // The passes below run on rounded copies; the f32 master weights
+            // are swapped back in before the update.
+            for i in 0..self.num_connections {
+                self.connections[i].weights.copy_into(&mut workspace.working_weights[i]);
+                self.connections[i].bias.copy_into(&mut workspace.working_biases[i]);
+                config.precision.round_matrix(&mut workspace.working_weights[i]);
+                config.precision.round_matrix(&mut workspace.working_biases[i]);
+            }
+            self.swap_working_weights(workspace);
+        }
+        let mut batch_loss = T::ZERO;
+        for row in 0..features.rows {
+            workspace.example.data[0].copy_from_slice(&features.data[row]);
+            workspace.target.data[0].copy_from_slice(&targets.data[row]);
+            let weight = weights.map_or(1.0, |weights| weights[row]) * match &config.class_weights {
+                Some(class_weights) => class_weights[class_index(&targets.data[row])],
+                None => 1.0,
+            };
+            self.forward(&workspace.example, config.precision);
+            let output = self.layers[self.num_layers-1].input.clone();
+            batch_loss += T::from_f32(weight) * self.loss_value(&config.loss, &output.borrow(), &workspace.target);
+            self.backward(workspace);
+
+            let scale = weight * config.loss_scaling.as_ref().map_or(1.0, |scaling| scaling.scale);
+            for i in 0..self.num_connections {
+                if scale != 1.0 {
+                    workspace.weight_gradients[i].scalar_multiply(T::from_f32(scale));
+                    workspace.bias_gradients[i].scalar_multiply(T::from_f32(scale));
+                }
+                config.precision.round_matrix(&mut workspace.weight_gradients[i]);
+                config.precision.round_matrix(&mut workspace.bias_gradients[i]);
+                workspace.weight_gradients[i].add_to(&mut workspace.weight_sums[i]);
+                workspace.bias_gradients[i].add_to(&mut workspace.bias_sums[i]);
+            }
+        }
+        if reduced {
+            self.swap_working_weights(workspace);
+        }
+        let mean_loss = batch_loss / T::from_usize(features.rows);
+
+        if let Some(scaling) = config.loss_scaling.as_mut() {
+            let overflow = !all_finite(&workspace.weight_sums) || !all_finite(&workspace.bias_sums);
+            let scale = scaling.scale;
+            scaling.update(overflow);
+            if overflow && batch_loss.is_finite() {
+                // The scaled gradients overflowed, not the model: skip this step.
+                zero_all(&mut workspace.weight_sums);
+                zero_all(&mut workspace.bias_sums);
+                return Ok(mean_loss);
+            }
+            for i in 0..self.num_connections {
+                workspace.weight_sums[i].scalar_multiply(T::from_f32(1.0 / scale));
+                workspace.bias_sums[i].scalar_multiply(T::from_f32(1.0 / scale));
+            }
+        }
+
+        if !batch_loss.is_finite() || !all_finite(&workspace.weight_sums) || !all_finite(&workspace.bias_sums) {
+            match config.divergence {
+                DivergencePolicy::Ignore => {},
+                DivergencePolicy::Halt => return Err(Error::Diverged {epoch, batch, reason: format!("batch loss is {}", batch_loss)}),
+                DivergencePolicy::Rollback => {
+                    if !workspace.has_last {
+                        return Err(Error::Diverged {epoch, batch, reason: format!("batch loss is {}", batch_loss)});
+                    }
                     for i in 0..self.num_connections {
-                        errori[i].to_zero();
-                        dWi[i].to_zero();
-                        dbi[i].to_zero();
+                        workspace.last_weights[i].copy_into(&mut self.connections[i].weights);
+                        workspace.last_biases[i].copy_into(&mut self.connections[i].bias);
                     }
+                    zero_all(&mut workspace.weight_sums);
+                    zero_all(&mut workspace.bias_sums);
+                    zero_all(&mut workspace.weight_steps);
+                    zero_all(&mut workspace.bias_steps);
+                    workspace.learning_rate_scale *= 0.5;
+                    return Ok(mean_loss);
+                },
+            }
+        } else if let DivergencePolicy::Rollback = config.divergence {
+            for i in 0..self.num_connections {
+                self.connections[i].weights.copy_into(&mut workspace.last_weights[i]);
+                self.connections[i].bias.copy_into(&mut workspace.last_biases[i]);
+            }
+            workspace.has_last = true;
+        }
 
-                    errori[self.num_connections].to_zero();
-                    if (num_hidden > 0) {
-                        for i in 0..num_hidden {
-                            WTi[i].to_zero();
-                            error_last_ti[i].to_zero();
-                            fprimei[i].to_zero();
-                            input_ti[i].to_zero();
-                        }
-                    }
-                }
+        for i in 0..self.num_connections {
+            workspace.weight_sums[i].scalar_multiply(T::ONE / T::from_usize(features.rows));
+            workspace.bias_sums[i].scalar_multiply(T::ONE / T::from_usize(features.rows));
+        }
 
-                let current_lr = if params.search_time == 0.0 { params.learning_rate } else { params.learning_rate / (1.0 + (epoch as f32 / params.search_time))};
-                for i in 0..self.num_connections {
-                    let lambda = current_lr / params.dataset.rows as f32;
-                    dWi_avg[i].scalar_multiply(lambda);
-                    dbi_avg[i].scalar_multiply(lambda);
-                }
+        for i in 0..self.num_connections {
+            let regularization = config.regularization_for(i);
+            regularization.gradient_into(&self.connections[i].weights, &mut workspace.weight_penalty[i]);
+            workspace.weight_penalty[i].add_to(&mut workspace.weight_sums[i]);
+            if config.regularize_bias {
+                regularization.gradient_into(&self.connections[i].bias, &mut workspace.bias_penalty[i]);
+                workspace.bias_penalty[i].add_to(&mut workspace.bias_sums[i]);
+            }
+        }
 
-                for i in 0..self.num_connections {
-                    dWi_last[i].scalar_multiply(params.momentum);
-                    dbi_last[i].scalar_multiply(params.momentum);
-                    dWi_last[i].add_to(&mut dWi_avg[i]);
-                    dbi_last[i].add_to(&mut dbi_avg[i]);
-                }
+        if let Some(clipping) = &config.clipping {
+            clipping.apply(&mut workspace.weight_sums, &mut workspace.bias_sums);
+        }
 
-                for i in 0..self.num_connections {
-                    dWi_avg[i].scalar_multiply(-1.0);
-                    dbi_avg[i].scalar_multiply(-1.0);
-                    dWi_avg[i].add_to(&mut self.connections[i].weights);
-                    dbi_avg[i].add_to(&mut self.connections[i].bias);
-                }
+        let base_lr = config.learning_rate * workspace.learning_rate_scale;
+        let current_lr = T::from_f32(if config.search_time == 0.0 { base_lr } else { base_lr / (1.0 + (epoch as f32 / config.search_time))});
+        for i in 0..self.num_connections {
+            workspace.weight_sums[i].scalar_multiply(current_lr);
+            workspace.bias_sums[i].scalar_multiply(current_lr);
+        }
 
-                for i in 0..self.num_connections {
-                    dWi_avg[i].copy_into(&mut dWi_last[i]);
-                    dbi_avg[i].copy_into(&mut dbi_last[i]);
-                    dWi_last[i].scalar_multiply(-1.0);
-                    dbi_last[i].scalar_multiply(-1.0);
+        for i in 0..self.num_connections {
+            workspace.weight_steps[i].scalar_multiply(T::from_f32(config.momentum));
+            workspace.bias_steps[i].scalar_multiply(T::from_f32(config.momentum));
+            workspace.weight_steps[i].add_to(&mut workspace.weight_sums[i]);
+            workspace.bias_steps[i].add_to(&mut workspace.bias_sums[i]);
+        }
+
+        for i in 0..self.num_connections {
+            workspace.weight_sums[i].scalar_multiply(-T::ONE);
+            workspace.bias_sums[i].scalar_multiply(-T::ONE);
+            workspace.weight_sums[i].add_to(&mut self.connections[i].weights);
+            workspace.bias_sums[i].add_to(&mut self.connections[i].bias);
+            if let Some(max_norm) = config.max_norm {
+                apply_max_norm(&mut self.connections[i].weights, max_norm);
+            }
+        }
+
+        for i in 0..self.num_connections {
+            workspace.weight_sums[i].copy_into(&mut workspace.weight_steps[i]);
+            workspace.bias_sums[i].copy_into(&mut workspace.bias_steps[i]);
+            workspace.weight_steps[i].scalar_multiply(-T::ONE);
+            workspace.bias_steps[i].scalar_multiply(-T::ONE);
+        }
+
+        zero_all(&mut workspace.weight_sums);
+        zero_all(&mut workspace.bias_sums);
+
+        Ok(mean_loss)
+    }
+
+    fn swap_working_weights(&mut self, workspace: &mut Workspace<T>) {
+        for i in 0..self.num_connections {
+            std::mem::swap(&mut self.connections[i].weights, &mut workspace.working_weights[i]);
+            std::mem::swap(&mut self.connections[i].bias, &mut workspace.working_biases[i]);
+        }
+    }
+
+    // Gradients of the single row in `workspace.target` with respect to every
+    // connection, from the layer outputs left by the last forward pass.
+    fn backward(&self, workspace: &mut Workspace<T>) {
+        let last = self.num_layers - 1;
+        for layer in (1..self.num_layers).rev() {
+            if layer == last {
+                self.layers[layer].input.borrow().copy_into(&mut workspace.errors[layer]);
+                for (error, &target) in workspace.errors[layer].data[0].iter_mut().zip(workspace.target.data[0].iter()) {
+                    *error -= target;
                 }
+            } else {
+                let hidden_layer = layer - 1;
+                self.connections[layer].weights.transpose_into(&mut workspace.weights_t[hidden_layer]);
+                workspace.errors[layer + 1].multiply_into(&workspace.weights_t[hidden_layer], &mut workspace.back_errors[hidden_layer]);
+                self.layers[layer].input.borrow().copy_into(&mut workspace.derivatives[hidden_layer]);
+                let derivative = activation_derivative(self.layers[layer].activation);
+                workspace.derivatives[hidden_layer].transform(derivative);
+                workspace.back_errors[hidden_layer].hadamard_into(&workspace.derivatives[hidden_layer], &mut workspace.errors[layer]);
+            }
+
+            self.layers[layer - 1].input.borrow().transpose_into(&mut workspace.inputs_t[layer - 1]);
+            workspace.inputs_t[layer - 1].multiply_into(&workspace.errors[layer], &mut workspace.weight_gradients[layer - 1]);
+            workspace.errors[layer].copy_into(&mut workspace.bias_gradients[layer - 1]);
+        }
+    }
+}
 
-                for i in 0..self.num_connections {
-                    dWi_avg[i].to_zero();
-                    dbi_avg[i].to_zero();
-                    regi[i].to_zero();
+fn zero_all<T: Float>(matrices: &mut [Matrix<T>]) {
+    for matrix in matrices.iter_mut() {
+        matrix.to_zero();
+    }
+}
+
+impl<T: Float> Network<T> {
+    // Stores each connection as `weights_<i>` and `bias_<i>` in a NumPy
+    // archive, as f64 arrays for an f64 network and f32 arrays otherwise.
+    pub fn save_npz<P: AsRef<std::path::Path>>(&self, path: P) -> Result<()> {
+        let names: Vec<(String, String)> = (0..self.num_connections).map(|i| (format!("weights_{}", i), format!("bias_{}", i))).collect();
+        let mut arrays: Vec<(&str, &Matrix<T>)> = Vec::new();
+        for (i, (weights, bias)) in names.iter().enumerate() {
+            arrays.push((weights, &self.connections[i].weights));
+            arrays.push((bias, &self.connections[i].bias));
+        }
+        DataSet::to_npz(path, &arrays)
+    }
+
+    pub fn load_npz<P: AsRef<std::path::Path>>(&mut self, path: P) -> Result<()> {
+        let arrays = read_npz(std::io::BufReader::new(std::fs::File::open(path)?))?;
+        for (i, connection) in self.connections.iter_mut().enumerate() {
+            for (name, target) in [(format!("weights_{}", i), &mut connection.weights), (format!("bias_{}", i), &mut connection.bias)] {
+                let array = match arrays.iter().find(|(array_name, _)| *array_name == name) {
+                    Some((_, array)) => array.to_dataset_as::<T>(),
+                    None => return Err(Error::Generic(format!("archive has no array named {}", name))),
+                };
+                if array.rows != target.rows || array.cols != target.cols {
+                    return Err(Error::ShapeMismatch {expected: (target.rows, target.cols), found: (array.rows, array.cols)});
                 }
+                array.copy_into(target);
             }
         }
 
+        Ok(())
     }
-    
 }
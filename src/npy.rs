@@ -0,0 +1,257 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::path::Path;
+use crate::dataset::*;
+use crate::prelude::*;
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+// A NumPy array in C order. Values are widened to f64 so that integer
+// labels survive the round trip.
+pub struct NpyArray {
+    pub shape: Vec<usize>,
+    pub data: Vec<f64>,
+}
+
+fn header_error(message: String) -> Error {
+    Error::Parse {row: 0, column: 0, message}
+}
+
+pub fn read_npy<R: Read>(mut reader: R) -> Result<NpyArray> {
+    let (descr, shape, _) = read_header(&mut reader)?;
+
+    let element_size: usize = match descr.as_str() {
+        "<f4" | "<i4" => 4,
+        "<f8" | "<i8" => 8,
+        "|u1" | "|i1" => 1,
+        _ => return Err(header_error(format!("unsupported dtype {}", descr))),
+    };
+    // The header is untrusted: size the buffer from what the file actually holds.
+    let size = shape.iter().try_fold(element_size, |size, &dim| size.checked_mul(dim))
+        .ok_or_else(|| header_error(format!("shape {:?} is too large", shape)))?;
+    let mut bytes = Vec::new();
+    reader.take(size as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != size {
+        return Err(header_error(format!("shape {:?} needs {} bytes of data, found {}", shape, size, bytes.len())));
+    }
+    let data = bytes.chunks_exact(element_size).map(|b| match descr.as_str() {
+        "<f4" => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+        "<i4" => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+        "<f8" => f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]),
+        "<i8" => i64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f64,
+        "|u1" => b[0] as f64,
+        _ => b[0] as i8 as f64,
+    }).collect();
+
+    Ok(NpyArray {shape, data})
+}
+
+// Reads the magic, version and header dictionary, returning the dtype,
+// the shape and the offset at which the data starts.
+pub(crate) fn read_header<R: Read>(reader: &mut R) -> Result<(String, Vec<usize>, usize)> {
+    let mut prefix = [0u8; 8];
+    reader.read_exact(&mut prefix)?;
+    if &prefix[..6] != MAGIC {
+        return Err(header_error("not a .npy file".to_string()));
+    }
+    let (header_len, length_size) = match prefix[6] {
+        1 => {
+            let mut len = [0u8; 2];
+            reader.read_exact(&mut len)?;
+            (u16::from_le_bytes(len) as usize, 2)
+        },
+        2 | 3 => {
+            let mut len = [0u8; 4];
+            reader.read_exact(&mut len)?;
+            (u32::from_le_bytes(len) as usize, 4)
+        },
+        version => return Err(header_error(format!("unsupported .npy version {}", version))),
+    };
+    let mut header = vec![0u8; header_len];
+    reader.read_exact(&mut header)?;
+    let header = String::from_utf8_lossy(&header);
+
+    let descr = header_value(&header, "descr")?.trim_matches(|c| c == '\'' || c == '"').to_string();
+    if header_value(&header, "fortran_order")? != "False" {
+        return Err(header_error("Fortran-ordered arrays are not supported".to_string()));
+    }
+    let shape_text = header_value(&header, "shape")?;
+    let shape = shape_text.trim_matches(|c| c == '(' || c == ')')
+        .split(',')
+        .map(|dim| dim.trim())
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| dim.parse::<usize>().map_err(|_| header_error(format!("bad shape {}", shape_text))))
+        .collect::<Result<Vec<usize>>>()?;
+
+    Ok((descr, shape, prefix.len() + length_size + header_len))
+}
+
+// Extracts the raw text of `key` from a header such as
+// {'descr': '<f4', 'fortran_order': False, 'shape': (3, 4), }
+fn header_value(header: &str, key: &str) -> Result<String> {
+    let quoted = format!("'{}'", key);
+    let start = header.find(&quoted).ok_or_else(|| header_error(format!("header has no {}", key)))? + quoted.len();
+    let rest = header[start..].trim_start().trim_start_matches(':').trim_start();
+    let end = if rest.starts_with('(') {
+        rest.find(')').map(|i| i + 1)
+    } else {
+        rest.find(',')
+    }.ok_or_else(|| header_error(format!("malformed {}", key)))?;
+
+    Ok(rest[..end].trim().to_string())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NpyDtype {
+    F32,
+    F64,
+    I64,
+}
+
+impl NpyDtype {
+    // The float dtype that holds every value of `T` exactly.
+    pub fn of<T: Float>() -> NpyDtype {
+        if T::EPSILON.to_f64() < f32::EPSILON as f64 { NpyDtype::F64 } else { NpyDtype::F32 }
+    }
+}
+
+// Writes a version 1.0 little-endian array. I64 rounds each value.
+pub fn write_npy<W: Write, T: Float>(writer: &mut W, shape: &[usize], data: &[T], dtype: NpyDtype) -> Result<()> {
+    assert!(shape.iter().product::<usize>() == data.len());
+    let dims: Vec<String> = shape.iter().map(|d| d.to_string()).collect();
+    let shape_text = if dims.len() == 1 { format!("({},)", dims[0]) } else { format!("({})", dims.join(", ")) };
+    let descr = match dtype {
+        NpyDtype::F32 => "<f4",
+        NpyDtype::F64 => "<f8",
+        NpyDtype::I64 => "<i8",
+    };
+    let mut header = format!("{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}", descr, shape_text);
+    // The magic, version and length take 10 bytes; pad so the data starts on a 64-byte boundary.
+    let padding = 63 - (10 + header.len()) % 64;
+    header.push_str(&" ".repeat(padding));
+    header.push('\n');
+
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[1, 0])?;
+    writer.write_all(&(header.len() as u16).to_le_bytes())?;
+    writer.write_all(header.as_bytes())?;
+    for &value in data {
+        match dtype {
+            NpyDtype::F32 => writer.write_all(&value.to_f32().to_le_bytes())?,
+            NpyDtype::F64 => writer.write_all(&value.to_f64().to_le_bytes())?,
+            NpyDtype::I64 => writer.write_all(&(value.to_f64().round() as i64).to_le_bytes())?,
+        }
+    }
+
+    Ok(())
+}
+
+pub fn read_npz<R: Read + Seek>(reader: R) -> Result<Vec<(String, NpyArray)>> {
+    let mut archive = zip::ZipArchive::new(reader).map_err(zip_error)?;
+    let mut arrays = Vec::new();
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i).map_err(zip_error)?;
+        let name = entry.name().trim_end_matches(".npy").to_string();
+        arrays.push((name, read_npy(entry)?));
+    }
+
+    Ok(arrays)
+}
+
+// Each array is stored in the float dtype of its element type.
+pub fn write_npz<W: Write + Seek, T: Float>(writer: W, arrays: &[(&str, &DataSet<T>)]) -> Result<()> {
+    let mut archive = zip::ZipWriter::new(writer);
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    for (name, dataset) in arrays {
+        archive.start_file(format!("{}.npy", name), options).map_err(zip_error)?;
+        dataset.write_npy(&mut archive, NpyDtype::of::<T>())?;
+    }
+    archive.finish().map_err(zip_error)?;
+
+    Ok(())
+}
+
+fn zip_error(error: zip::result::ZipError) -> Error {
+    match error {
+        zip::result::ZipError::Io(error) => Error::Io(error),
+        error => Error::Parse {row: 0, column: 0, message: error.to_string()},
+    }
+}
+
+impl NpyArray {
+    // 1-D arrays become a single column; higher dimensions are flattened per row.
+    pub fn to_dataset(&self) -> DataSet {
+        self.to_dataset_as()
+    }
+
+    // As `to_dataset`, converting each value to `T` straight from the f64 it was read as.
+    pub fn to_dataset_as<T: Float>(&self) -> DataSet<T> {
+        let (rows, cols) = match self.shape.len() {
+            0 => (1, 1),
+            1 => (self.shape[0], 1),
+            _ => (self.shape[0], self.shape[1..].iter().product()),
+        };
+        let data = if cols == 0 {
+            vec![Vec::new(); rows]
+        } else {
+            self.data.chunks(cols).map(|row| row.iter().map(|&x| T::from_f64(x)).collect()).collect()
+        };
+
+        DataSet {rows, cols, data}
+    }
+
+    // Integer class labels as one-hot rows; `num_classes` defaults to the largest label plus one.
+    // Rows in errors are 1-based, as in the CSV reader.
+    pub fn to_one_hot(&self, num_classes: Option<usize>) -> Result<DataSet> {
+        let mut labels: Vec<usize> = Vec::new();
+        for (row, &value) in self.data.iter().enumerate() {
+            if value < 0.0 || value.fract() != 0.0 {
+                return Err(Error::Parse {row: row + 1, column: 1, message: format!("{} is not a class label", value)});
+            }
+            labels.push(value as usize);
+        }
+        let num_classes = num_classes.unwrap_or(labels.iter().max().map_or(0, |max| max + 1));
+        if let Some(row) = labels.iter().position(|&label| label >= num_classes) {
+            return Err(Error::Parse {row: row + 1, column: 1, message: format!("label {} is not below {}", labels[row], num_classes)});
+        }
+
+        Ok(one_hot(&labels, num_classes))
+    }
+}
+
+impl DataSet {
+    pub fn from_npy<P: AsRef<Path>>(path: P) -> Result<DataSet> {
+        Ok(read_npy(BufReader::new(File::open(path)?))?.to_dataset())
+    }
+
+    pub fn from_npy_labels<P: AsRef<Path>>(path: P, num_classes: Option<usize>) -> Result<DataSet> {
+        read_npy(BufReader::new(File::open(path)?))?.to_one_hot(num_classes)
+    }
+
+    pub fn from_npz<P: AsRef<Path>>(path: P, name: &str) -> Result<DataSet> {
+        let arrays = read_npz(BufReader::new(File::open(path)?))?;
+        match arrays.into_iter().find(|(array_name, _)| array_name == name) {
+            Some((_, array)) => Ok(array.to_dataset()),
+            None => Err(Error::Generic(format!("archive has no array named {}", name))),
+        }
+    }
+
+}
+
+impl<T: Float> DataSet<T> {
+    pub fn to_npy<P: AsRef<Path>>(&self, path: P, dtype: NpyDtype) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_npy(&mut writer, dtype)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn write_npy<W: Write>(&self, writer: &mut W, dtype: NpyDtype) -> Result<()> {
+        let data: Vec<T> = self.data.iter().flatten().cloned().collect();
+        write_npy(writer, &[self.rows, self.cols], &data, dtype)
+    }
+
+    pub fn to_npz<P: AsRef<Path>>(path: P, arrays: &[(&str, &DataSet<T>)]) -> Result<()> {
+        write_npz(BufWriter::new(File::create(path)?), arrays)
+    }
+}
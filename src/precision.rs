@@ -0,0 +1,247 @@
+use crate::dataset::*;
+use crate::function::*;
+use crate::matrix::*;
+use crate::network::*;
+use crate::prelude::*;
+
+// A 16-bit storage type. Values are widened to f32 for arithmetic and
+// rounded back when stored.
+pub trait HalfFloat: Copy + Send + Sync + 'static {
+    fn from_f32(value: f32) -> Self;
+    fn to_f32(self) -> f32;
+}
+
+impl HalfFloat for bf16 {
+    fn from_f32(value: f32) -> Self {
+        bf16::from_f32(value)
+    }
+
+    fn to_f32(self) -> f32 {
+        bf16::to_f32(self)
+    }
+}
+
+impl HalfFloat for f16 {
+    fn from_f32(value: f32) -> Self {
+        f16::from_f32(value)
+    }
+
+    fn to_f32(self) -> f32 {
+        f16::to_f32(self)
+    }
+}
+
+// How weights, activations and gradients are stored while training. Each
+// batch runs its forward and backward passes on weights rounded to this
+// precision, rounding every layer output and gradient as well; the update
+// is applied to an f32 master copy of the weights.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Precision {
+    Full,
+    BF16,
+    F16,
+}
+
+impl Precision {
+    // The nearest value representable in this precision.
+    pub fn round(&self, value: f32) -> f32 {
+        match self {
+            Precision::Full => value,
+            Precision::BF16 => bf16::from_f32(value).to_f32(),
+            Precision::F16 => f16::from_f32(value).to_f32(),
+        }
+    }
+
+    pub fn round_matrix<T: Float>(&self, matrix: &mut Matrix<T>) {
+        if *self != Precision::Full {
+            matrix.transform(|x| T::from_f32(self.round(x.to_f32())));
+        }
+    }
+}
+
+// Multiplies gradients by `scale` before they are rounded, so that small
+// values do not flush to zero, and divides them out again before the update.
+// A dynamic scale halves whenever the scaled gradients overflow (that step
+// is skipped) and doubles after `growth_interval` steps without overflow.
+#[derive(Clone, Debug)]
+pub struct LossScaling {
+    pub scale: f32,
+    pub dynamic: bool,
+    pub growth_interval: usize,
+    good_steps: usize,
+}
+
+pub fn create_loss_scaling(scale: f32, dynamic: bool) -> Result<LossScaling> {
+    if !(scale.is_finite() && scale > 0.0) {
+        return Err(Error::InvalidConfig(format!("loss scale {} must be finite and positive", scale)));
+    }
+    Ok(LossScaling {scale, dynamic, growth_interval: 200, good_steps: 0})
+}
+
+impl LossScaling {
+    pub fn update(&mut self, overflow: bool) {
+        if !self.dynamic {
+            return;
+        }
+        if overflow {
+            self.scale = (self.scale * 0.5).max(1.0);
+            self.good_steps = 0;
+        } else {
+            self.good_steps += 1;
+            if self.good_steps >= self.growth_interval {
+                self.scale *= 2.0;
+                self.good_steps = 0;
+            }
+        }
+    }
+}
+
+// A row-major matrix stored in a 16-bit format.
+#[derive(Clone, Debug)]
+pub struct HalfMatrix<T> {
+    pub rows: usize,
+    pub cols: usize,
+    pub data: Vec<T>,
+}
+
+impl<T: HalfFloat> HalfMatrix<T> {
+    pub fn from_matrix(matrix: &Matrix) -> HalfMatrix<T> {
+        let data = matrix.data.iter().flatten().map(|&x| T::from_f32(x)).collect();
+        HalfMatrix {rows: matrix.rows, cols: matrix.cols, data}
+    }
+
+    pub fn to_matrix(&self) -> Matrix {
+        let data = self.data.chunks(self.cols).map(|row| row.iter().map(|x| x.to_f32()).collect()).collect();
+        Matrix::create_matrix(self.rows, self.cols, data)
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> f32 {
+        self.data[row * self.cols + col].to_f32()
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        self.data.len() * std::mem::size_of::<T>()
+    }
+
+    // Products are summed in f32; only the result is rounded back to T.
+    pub fn multiply(&self, other: &HalfMatrix<T>) -> HalfMatrix<T> {
+        HalfMatrix::from_matrix(&self.multiply_f32(other))
+    }
+
+    // The product with every sum left in f32.
+    fn multiply_f32(&self, other: &HalfMatrix<T>) -> Matrix {
+        assert!(self.cols == other.rows);
+        let mut data = Vec::with_capacity(self.rows);
+        for i in 0..self.rows {
+            let mut row = Vec::with_capacity(other.cols);
+            for j in 0..other.cols {
+                let mut sum: f32 = 0.0;
+                for k in 0..self.cols {
+                    sum += self.get(i, k) * other.get(k, j);
+                }
+                row.push(sum);
+            }
+            data.push(row);
+        }
+        Matrix::create_matrix(self.rows, other.cols, data)
+    }
+}
+
+struct HalfConnection<T> {
+    weights: HalfMatrix<T>,
+    bias: HalfMatrix<T>,
+    activation: Option<Activation>,
+}
+
+// An inference-only copy of a `Network` whose weights and layer outputs are
+// kept in 16 bits, with every matrix product accumulated in f32.
+pub struct HalfNetwork<T> {
+    connections: Vec<HalfConnection<T>>,
+}
+
+impl Network {
+    pub fn to_half_precision<T: HalfFloat>(&self) -> HalfNetwork<T> {
+        let connections = self.connections.iter().map(|connection| HalfConnection {
+            weights: HalfMatrix::from_matrix(&connection.weights),
+            bias: HalfMatrix::from_matrix(&connection.bias),
+            activation: connection.to.activation,
+        }).collect();
+        HalfNetwork {connections}
+    }
+}
+
+impl<T: HalfFloat> HalfNetwork<T> {
+    pub fn num_inputs(&self) -> usize {
+        self.connections[0].weights.rows
+    }
+
+    pub fn num_outputs(&self) -> usize {
+        self.connections[self.connections.len() - 1].weights.cols
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        self.connections.iter().map(|c| c.weights.memory_bytes() + c.bias.memory_bytes()).sum()
+    }
+
+    pub fn infer(&self, input: &Matrix) -> Result<Matrix> {
+        if input.rows == 0 {
+            return Err(Error::EmptyDataset);
+        }
+        if input.cols != self.num_inputs() {
+            return Err(Error::ShapeMismatch {expected: (input.rows, self.num_inputs()), found: (input.rows, input.cols)});
+        }
+
+        let mut activations: HalfMatrix<T> = HalfMatrix::from_matrix(input);
+        for connection in self.connections.iter() {
+            // Bias and activation are applied in f32; each layer is rounded once.
+            let mut output = activations.multiply_f32(&connection.weights);
+            for row in output.data.iter_mut() {
+                for (j, x) in row.iter_mut().enumerate() {
+                    *x += connection.bias.get(0, j);
+                }
+            }
+            activations = HalfMatrix::from_matrix(&apply_activation(connection.activation, output));
+        }
+
+        Ok(activations.to_matrix())
+    }
+
+    pub fn predict_classes(&self, input: &Matrix) -> Result<Vec<usize>> {
+        Ok(class_indices(&self.infer(input)?))
+    }
+
+    pub fn accuracy(&self, features: &DataSet, classes: &DataSet) -> Result<f32> {
+        if classes.rows != features.rows || classes.cols != self.num_outputs() {
+            return Err(Error::ShapeMismatch {expected: (features.rows, self.num_outputs()), found: (classes.rows, classes.cols)});
+        }
+        let predictions = self.predict_classes(features)?;
+        let correct = predictions.iter().zip(class_indices(classes)).filter(|(p, c)| **p == *c).count();
+        Ok(correct as f32 / features.rows as f32)
+    }
+}
+
+// How far a half-precision copy drifts from the f32 network it came from.
+#[derive(Clone, Debug)]
+pub struct PrecisionReport {
+    pub full_accuracy: f32,
+    pub half_accuracy: f32,
+    // Half minus full; negative when the copy does worse.
+    pub accuracy_difference: f32,
+    pub max_output_error: f32,
+}
+
+// `network` is left untouched; its forward pass runs on a copy.
+pub fn compare_precision<T: HalfFloat>(network: &Network, half: &HalfNetwork<T>, features: &DataSet, classes: &DataSet) -> Result<PrecisionReport> {
+    let half_accuracy = half.accuracy(features, classes)?;
+    let half_output = half.infer(features)?;
+    let mut network = network.clone();
+    network.try_forward_pass(Rc::new(RefCell::new(features.clone())))?;
+    let full_output = network.get_output().borrow().copy();
+    let full_accuracy = class_indices(&full_output).iter().zip(class_indices(classes))
+        .filter(|(p, c)| **p == *c).count() as f32 / features.rows as f32;
+    let max_output_error = full_output.data.iter().flatten().zip(half_output.data.iter().flatten())
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f32::max);
+
+    Ok(PrecisionReport {full_accuracy, half_accuracy, accuracy_difference: half_accuracy - full_accuracy, max_output_error})
+}
@@ -1,8 +1,11 @@
+#![allow(clippy::legacy_numeric_constants)]
+
 pub use crate::error::Error;
+pub use crate::float::Float;
 pub use std::usize;
 pub use std::ptr::null;
 pub use std::rc::Rc;
 pub use std::cell::RefCell;
-pub use half::bf16;
+pub use half::{bf16, f16};
 
 pub type Result<T> = core::result::Result<T, Error>;
\ No newline at end of file
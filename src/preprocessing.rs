@@ -0,0 +1,615 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use crate::dataset::*;
+use crate::linalg::*;
+use crate::matrix::*;
+use crate::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScalerKind {
+    // Zero mean and unit variance.
+    Standard,
+    // Maps each column's [min, max] onto the given range.
+    MinMax(f32, f32),
+    // Zero median and unit interquartile range.
+    Robust,
+}
+
+// Every scaler reduces to `(x - offset) / scale` per column once fitted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scaler {
+    pub kind: ScalerKind,
+    pub offset: Vec<f32>,
+    pub scale: Vec<f32>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LabelEncoder {
+    pub classes: Vec<f32>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OneHotEncoder {
+    pub categories: Vec<Vec<f32>>,
+    // Encode values not seen during fit as all zeros instead of failing.
+    pub ignore_unknown: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PcaComponents {
+    Count(usize),
+    // The fewest components whose explained variance reaches this fraction of the total.
+    VarianceRatio(f32),
+}
+
+// Projects rows onto the principal axes of the fitted data, strongest first.
+// With `whiten` every component is also scaled to unit variance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pca {
+    pub components: PcaComponents,
+    pub whiten: bool,
+    pub mean: Vec<f32>,
+    // One unit-length axis per kept component.
+    pub axes: Vec<Vec<f32>>,
+    pub explained_variance: Vec<f32>,
+    pub total_variance: f32,
+}
+
+// ZCA whitening: decorrelates the columns and scales them to unit variance
+// while staying as close as possible to the original features. `epsilon` is
+// added to every variance so that flat directions are not blown up.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Whitening {
+    pub epsilon: f32,
+    pub mean: Vec<f32>,
+    pub matrix: Vec<Vec<f32>>,
+}
+
+pub fn create_scaler(kind: ScalerKind) -> Scaler {
+    Scaler {kind, offset: Vec::new(), scale: Vec::new()}
+}
+
+pub fn create_pca(components: PcaComponents, whiten: bool) -> Pca {
+    Pca {components, whiten, mean: Vec::new(), axes: Vec::new(), explained_variance: Vec::new(), total_variance: 0.0}
+}
+
+pub fn create_whitening(epsilon: f32) -> Whitening {
+    Whitening {epsilon, mean: Vec::new(), matrix: Vec::new()}
+}
+
+pub fn create_label_encoder() -> LabelEncoder {
+    LabelEncoder {classes: Vec::new()}
+}
+
+pub fn create_one_hot_encoder(ignore_unknown: bool) -> OneHotEncoder {
+    OneHotEncoder {categories: Vec::new(), ignore_unknown}
+}
+
+fn column(dataset: &DataSet, j: usize) -> Vec<f32> {
+    dataset.data.iter().map(|row| row[j]).collect()
+}
+
+fn sorted_distinct(values: &[f32]) -> Vec<f32> {
+    let mut distinct = values.to_vec();
+    distinct.sort_by(|a, b| a.total_cmp(b));
+    distinct.dedup();
+    distinct
+}
+
+fn quantile(sorted: &[f32], q: f32) -> f32 {
+    let position = q * (sorted.len() - 1) as f32;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f32)
+}
+
+fn check_fitted(fitted_cols: usize, dataset: &DataSet) -> Result<()> {
+    if fitted_cols == 0 {
+        return Err(Error::Generic("transform called before fit".to_string()));
+    }
+    if dataset.cols != fitted_cols {
+        return Err(Error::ShapeMismatch {expected: (dataset.rows, fitted_cols), found: (dataset.rows, dataset.cols)});
+    }
+    Ok(())
+}
+
+// For transforms whose matrix products cannot produce an empty result.
+fn check_rows(dataset: &DataSet) -> Result<()> {
+    if dataset.rows == 0 {
+        return Err(Error::EmptyDataset);
+    }
+    Ok(())
+}
+
+fn check_kind(kind: &ScalerKind) -> Result<()> {
+    if let ScalerKind::MinMax(low, high) = kind {
+        if !(low.is_finite() && high.is_finite() && low < high) {
+            return Err(Error::InvalidConfig(format!("min-max range [{}, {}] must be finite with low below high", low, high)));
+        }
+    }
+    Ok(())
+}
+
+impl Scaler {
+    pub fn fit(&mut self, dataset: &DataSet) -> Result<()> {
+        check_kind(&self.kind)?;
+        if dataset.rows == 0 || dataset.cols == 0 {
+            return Err(Error::EmptyDataset);
+        }
+
+        self.offset.clear();
+        self.scale.clear();
+        for j in 0..dataset.cols {
+            let values = column(dataset, j);
+            let n = values.len() as f32;
+            let (offset, scale) = match self.kind {
+                ScalerKind::Standard => {
+                    let mean = values.iter().sum::<f32>() / n;
+                    let variance = values.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / n;
+                    (mean, variance.sqrt())
+                },
+                ScalerKind::MinMax(low, high) => {
+                    let min = values.iter().cloned().fold(f32::INFINITY, f32::min);
+                    let max = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+                    let scale = (max - min) / (high - low);
+                    (min - low * scale, scale)
+                },
+                ScalerKind::Robust => {
+                    let mut sorted = values.clone();
+                    sorted.sort_by(|a, b| a.total_cmp(b));
+                    (quantile(&sorted, 0.5), quantile(&sorted, 0.75) - quantile(&sorted, 0.25))
+                },
+            };
+            // Constant columns are only shifted.
+            self.offset.push(offset);
+            self.scale.push(if scale == 0.0 || !scale.is_finite() { 1.0 } else { scale });
+        }
+
+        Ok(())
+    }
+
+    pub fn transform(&self, dataset: &DataSet) -> Result<DataSet> {
+        check_fitted(self.offset.len(), dataset)?;
+        let data = dataset.data.iter()
+            .map(|row| row.iter().enumerate().map(|(j, x)| (x - self.offset[j]) / self.scale[j]).collect())
+            .collect();
+        Ok(create_dataset(dataset.rows, dataset.cols, data))
+    }
+
+    pub fn inverse_transform(&self, dataset: &DataSet) -> Result<DataSet> {
+        check_fitted(self.offset.len(), dataset)?;
+        let data = dataset.data.iter()
+            .map(|row| row.iter().enumerate().map(|(j, x)| x * self.scale[j] + self.offset[j]).collect())
+            .collect();
+        Ok(create_dataset(dataset.rows, dataset.cols, data))
+    }
+
+    pub fn fit_transform(&mut self, dataset: &DataSet) -> Result<DataSet> {
+        self.fit(dataset)?;
+        self.transform(dataset)
+    }
+
+    pub fn save<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let kind = match self.kind {
+            ScalerKind::Standard => "standard".to_string(),
+            ScalerKind::MinMax(low, high) => format!("minmax {} {}", low, high),
+            ScalerKind::Robust => "robust".to_string(),
+        };
+        writeln!(writer, "scaler {}", kind)?;
+        write_values(writer, &self.offset)?;
+        write_values(writer, &self.scale)?;
+        Ok(())
+    }
+
+    pub fn load<R: Read>(reader: R) -> Result<Scaler> {
+        let mut lines = read_lines(reader)?;
+        let header = next_line(&mut lines, "scaler")?;
+        let fields: Vec<&str> = header.split_whitespace().collect();
+        let kind = match fields.as_slice() {
+            ["scaler", "standard"] => ScalerKind::Standard,
+            ["scaler", "robust"] => ScalerKind::Robust,
+            ["scaler", "minmax", low, high] => ScalerKind::MinMax(parse_value(low, 1)?, parse_value(high, 1)?),
+            _ => return Err(Error::Parse {row: 1, column: 1, message: format!("unknown scaler {:?}", header)}),
+        };
+        check_kind(&kind)?;
+        let offset = parse_values(&next_line(&mut lines, "offsets")?, 2)?;
+        let scale = parse_values(&next_line(&mut lines, "scales")?, 3)?;
+        if offset.len() != scale.len() {
+            return Err(Error::Parse {row: 3, column: 1, message: format!("{} offsets but {} scales", offset.len(), scale.len())});
+        }
+
+        Ok(Scaler {kind, offset, scale})
+    }
+}
+
+// The column means and the eigendecomposition of the covariance matrix,
+// computed in f64. Each eigenvector's largest entry is made positive so that
+// refitting the same data gives the same axes.
+fn covariance_eigen(dataset: &DataSet) -> Result<(Vec<f32>, SymmetricEigen<f64>)> {
+    if dataset.rows == 0 || dataset.cols == 0 {
+        return Err(Error::EmptyDataset);
+    }
+    let x = dataset.cast::<f64>();
+    let mean = x.mean(Axis::Rows);
+    let centred = x.broadcast_sub(&mean);
+    let covariance = centred.transpose().matmul(&centred) * (1.0 / dataset.rows as f64);
+    let mut eigen = covariance.symmetric_eigen()?;
+    for j in 0..eigen.vectors.cols {
+        let largest = (0..eigen.vectors.rows).map(|i| eigen.vectors.get(i, j))
+            .fold(0.0, |a: f64, b| if b.abs() > a.abs() { b } else { a });
+        if largest < 0.0 {
+            for i in 0..eigen.vectors.rows {
+                eigen.vectors.data[i][j] = -eigen.vectors.data[i][j];
+            }
+        }
+    }
+    for value in eigen.values.iter_mut() {
+        *value = value.max(0.0);
+    }
+
+    Ok((mean.data[0].iter().map(|&x| x as f32).collect(), eigen))
+}
+
+fn to_matrix(rows: &[Vec<f32>]) -> Matrix {
+    Matrix::create_matrix(rows.len(), rows[0].len(), rows.to_vec())
+}
+
+impl Pca {
+    pub fn fit(&mut self, dataset: &DataSet) -> Result<()> {
+        let (mean, eigen) = covariance_eigen(dataset)?;
+        let total: f64 = eigen.values.iter().sum();
+        let count = match self.components {
+            PcaComponents::Count(count) if count == 0 || count > dataset.cols => {
+                return Err(Error::InvalidConfig(format!("cannot keep {} components of {} columns", count, dataset.cols)));
+            },
+            PcaComponents::Count(count) => count,
+            PcaComponents::VarianceRatio(ratio) if !(ratio > 0.0 && ratio <= 1.0) => {
+                return Err(Error::InvalidConfig(format!("variance ratio {} is not in (0, 1]", ratio)));
+            },
+            PcaComponents::VarianceRatio(ratio) => {
+                // Slightly under the target so that rounding cannot add a component.
+                let target = ratio as f64 * total * (1.0 - 1e-9);
+                let mut explained = 0.0;
+                let mut count = 0;
+                while count < eigen.values.len() && (count == 0 || explained < target) {
+                    explained += eigen.values[count];
+                    count += 1;
+                }
+                count
+            },
+        };
+
+        self.mean = mean;
+        self.axes = (0..count).map(|j| (0..dataset.cols).map(|i| eigen.vectors.get(i, j) as f32).collect()).collect();
+        self.explained_variance = eigen.values[..count].iter().map(|&x| x as f32).collect();
+        self.total_variance = total as f32;
+        Ok(())
+    }
+
+    pub fn num_components(&self) -> usize {
+        self.axes.len()
+    }
+
+    // The share of the total variance along each kept axis.
+    pub fn explained_variance_ratio(&self) -> Vec<f32> {
+        self.explained_variance.iter().map(|x| if self.total_variance > 0.0 { x / self.total_variance } else { 0.0 }).collect()
+    }
+
+    fn component_scale(&self, component: usize) -> f32 {
+        let std_dev = self.explained_variance[component].sqrt();
+        if self.whiten && std_dev > 0.0 { std_dev } else { 1.0 }
+    }
+
+    pub fn transform(&self, dataset: &DataSet) -> Result<DataSet> {
+        check_fitted(self.mean.len(), dataset)?;
+        check_rows(dataset)?;
+        let mean = to_matrix(std::slice::from_ref(&self.mean));
+        let mut projected = dataset.broadcast_sub(&mean).matmul(&to_matrix(&self.axes).transpose());
+        for row in projected.data.iter_mut() {
+            for (j, x) in row.iter_mut().enumerate() {
+                *x /= self.component_scale(j);
+            }
+        }
+        Ok(projected)
+    }
+
+    // Maps components back to the original columns. Whatever the dropped
+    // components held is lost.
+    pub fn inverse_transform(&self, dataset: &DataSet) -> Result<DataSet> {
+        check_fitted(self.axes.len(), dataset)?;
+        check_rows(dataset)?;
+        let mut components = dataset.clone();
+        for row in components.data.iter_mut() {
+            for (j, x) in row.iter_mut().enumerate() {
+                *x *= self.component_scale(j);
+            }
+        }
+        Ok(components.matmul(&to_matrix(&self.axes)).broadcast_add(&to_matrix(std::slice::from_ref(&self.mean))))
+    }
+
+    pub fn fit_transform(&mut self, dataset: &DataSet) -> Result<DataSet> {
+        self.fit(dataset)?;
+        self.transform(dataset)
+    }
+
+    pub fn save<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let components = match self.components {
+            PcaComponents::Count(count) => format!("count {}", count),
+            PcaComponents::VarianceRatio(ratio) => format!("ratio {}", ratio),
+        };
+        writeln!(writer, "pca {} {} {} {}", components, self.whiten, self.axes.len(), self.total_variance)?;
+        write_values(writer, &self.mean)?;
+        write_values(writer, &self.explained_variance)?;
+        for axis in self.axes.iter() {
+            write_values(writer, axis)?;
+        }
+        Ok(())
+    }
+
+    pub fn load<R: Read>(reader: R) -> Result<Pca> {
+        let mut lines = read_lines(reader)?;
+        let header = next_line(&mut lines, "pca")?;
+        let fields: Vec<&str> = header.split_whitespace().collect();
+        let (components, whiten, count, total_variance) = match fields.as_slice() {
+            ["pca", kind, value, whiten, count, total] => {
+                let components = match *kind {
+                    "count" => PcaComponents::Count(parse_count(value, 3)?),
+                    "ratio" => PcaComponents::VarianceRatio(parse_value(value, 1)?),
+                    _ => return Err(Error::Parse {row: 1, column: 2, message: format!("unknown component rule {:?}", kind)}),
+                };
+                let whiten = whiten.parse::<bool>().map_err(|_| Error::Parse {row: 1, column: 4, message: format!("bad flag {:?}", whiten)})?;
+                (components, whiten, parse_count(count, 5)?, parse_value(total, 1)?)
+            },
+            _ => return Err(Error::Parse {row: 1, column: 1, message: "not a PCA transform".to_string()}),
+        };
+        if count == 0 {
+            return Err(Error::Parse {row: 1, column: 5, message: "a PCA transform needs at least one axis".to_string()});
+        }
+        let mean = parse_values(&next_line(&mut lines, "means")?, 2)?;
+        let explained_variance = parse_values(&next_line(&mut lines, "variances")?, 3)?;
+        let mut axes = Vec::new();
+        for row in 0..count {
+            let axis = parse_values(&next_line(&mut lines, "axes")?, row + 4)?;
+            if axis.len() != mean.len() {
+                return Err(Error::Parse {row: row + 4, column: 1, message: format!("axis has {} values for {} columns", axis.len(), mean.len())});
+            }
+            axes.push(axis);
+        }
+        if explained_variance.len() != count {
+            return Err(Error::Parse {row: 3, column: 1, message: format!("{} variances for {} axes", explained_variance.len(), count)});
+        }
+
+        Ok(Pca {components, whiten, mean, axes, explained_variance, total_variance})
+    }
+}
+
+impl Whitening {
+    pub fn fit(&mut self, dataset: &DataSet) -> Result<()> {
+        if self.epsilon.is_nan() || self.epsilon < 0.0 {
+            return Err(Error::InvalidConfig(format!("whitening epsilon {} is not a non-negative number", self.epsilon)));
+        }
+        let (mean, eigen) = covariance_eigen(dataset)?;
+        let n = dataset.cols;
+        let scales: Vec<f64> = eigen.values.iter().map(|&value| 1.0 / (value + self.epsilon as f64).sqrt()).collect();
+        if scales.iter().any(|scale| !scale.is_finite()) {
+            return Err(Error::Singular);
+        }
+        // W = V * diag(1 / sqrt(variance + epsilon)) * V^T
+        let vectors = &eigen.vectors;
+        self.mean = mean;
+        self.matrix = (0..n).map(|i| (0..n).map(|j| {
+            (0..n).map(|k| vectors.get(i, k) * scales[k] * vectors.get(j, k)).sum::<f64>() as f32
+        }).collect()).collect();
+        Ok(())
+    }
+
+    pub fn transform(&self, dataset: &DataSet) -> Result<DataSet> {
+        check_fitted(self.mean.len(), dataset)?;
+        check_rows(dataset)?;
+        Ok(dataset.broadcast_sub(&to_matrix(std::slice::from_ref(&self.mean))).matmul(&to_matrix(&self.matrix)))
+    }
+
+    pub fn inverse_transform(&self, dataset: &DataSet) -> Result<DataSet> {
+        check_fitted(self.mean.len(), dataset)?;
+        check_rows(dataset)?;
+        let inverse = to_matrix(&self.matrix).cast::<f64>().inverse()?.cast();
+        Ok(dataset.matmul(&inverse).broadcast_add(&to_matrix(std::slice::from_ref(&self.mean))))
+    }
+
+    pub fn fit_transform(&mut self, dataset: &DataSet) -> Result<DataSet> {
+        self.fit(dataset)?;
+        self.transform(dataset)
+    }
+
+    pub fn save<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writeln!(writer, "whitening {}", self.epsilon)?;
+        write_values(writer, &self.mean)?;
+        for row in self.matrix.iter() {
+            write_values(writer, row)?;
+        }
+        Ok(())
+    }
+
+    pub fn load<R: Read>(reader: R) -> Result<Whitening> {
+        let mut lines = read_lines(reader)?;
+        let header = next_line(&mut lines, "whitening")?;
+        let epsilon = match header.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            ["whitening", epsilon] => parse_value(epsilon, 1)?,
+            _ => return Err(Error::Parse {row: 1, column: 1, message: "not a whitening transform".to_string()}),
+        };
+        let mean = parse_values(&next_line(&mut lines, "means")?, 2)?;
+        let mut matrix = Vec::new();
+        for row in 0..mean.len() {
+            let values = parse_values(&next_line(&mut lines, "matrix")?, row + 3)?;
+            if values.len() != mean.len() {
+                return Err(Error::Parse {row: row + 3, column: 1, message: format!("{} values for {} columns", values.len(), mean.len())});
+            }
+            matrix.push(values);
+        }
+
+        Ok(Whitening {epsilon, mean, matrix})
+    }
+}
+
+impl LabelEncoder {
+    pub fn fit(&mut self, labels: &[f32]) -> Result<()> {
+        if labels.is_empty() {
+            return Err(Error::EmptyDataset);
+        }
+        self.classes = sorted_distinct(labels);
+        Ok(())
+    }
+
+    pub fn num_classes(&self) -> usize {
+        self.classes.len()
+    }
+
+    pub fn transform(&self, labels: &[f32]) -> Result<Vec<usize>> {
+        labels.iter().map(|label| match self.classes.iter().position(|c| c == label) {
+            Some(index) => Ok(index),
+            None => Err(Error::Generic(format!("unknown label {}", label))),
+        }).collect()
+    }
+
+    pub fn inverse_transform(&self, indices: &[usize]) -> Result<Vec<f32>> {
+        indices.iter().map(|&index| match self.classes.get(index) {
+            Some(&label) => Ok(label),
+            None => Err(Error::Generic(format!("class index {} out of range", index))),
+        }).collect()
+    }
+
+    // One-hot class rows in the layout `Network::accuracy` expects.
+    pub fn to_classes(&self, labels: &[f32]) -> Result<DataSet> {
+        Ok(one_hot(&self.transform(labels)?, self.num_classes()))
+    }
+
+    // Decodes class rows, such as network outputs, through their largest entry.
+    pub fn from_classes(&self, classes: &DataSet) -> Result<Vec<f32>> {
+        self.inverse_transform(&class_indices(classes))
+    }
+
+    pub fn save<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writeln!(writer, "labels")?;
+        write_values(writer, &self.classes)
+    }
+
+    pub fn load<R: Read>(reader: R) -> Result<LabelEncoder> {
+        let mut lines = read_lines(reader)?;
+        if next_line(&mut lines, "labels")?.trim() != "labels" {
+            return Err(Error::Parse {row: 1, column: 1, message: "not a label encoder".to_string()});
+        }
+        Ok(LabelEncoder {classes: parse_values(&next_line(&mut lines, "classes")?, 2)?})
+    }
+}
+
+impl OneHotEncoder {
+    pub fn fit(&mut self, dataset: &DataSet) -> Result<()> {
+        if dataset.rows == 0 || dataset.cols == 0 {
+            return Err(Error::EmptyDataset);
+        }
+        self.categories = (0..dataset.cols).map(|j| sorted_distinct(&column(dataset, j))).collect();
+        Ok(())
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.categories.iter().map(|c| c.len()).sum()
+    }
+
+    pub fn transform(&self, dataset: &DataSet) -> Result<DataSet> {
+        check_fitted(self.categories.len(), dataset)?;
+        let mut data = Vec::new();
+        for (i, row) in dataset.data.iter().enumerate() {
+            let mut encoded = vec![0.0; self.output_size()];
+            let mut start = 0;
+            for (j, categories) in self.categories.iter().enumerate() {
+                match categories.iter().position(|c| *c == row[j]) {
+                    Some(index) => encoded[start + index] = 1.0,
+                    None if self.ignore_unknown => {},
+                    None => return Err(Error::Parse {row: i + 1, column: j + 1, message: format!("unknown category {}", row[j])}),
+                }
+                start += categories.len();
+            }
+            data.push(encoded);
+        }
+
+        Ok(create_dataset(dataset.rows, self.output_size(), data))
+    }
+
+    pub fn inverse_transform(&self, dataset: &DataSet) -> Result<DataSet> {
+        if dataset.cols != self.output_size() || self.categories.is_empty() {
+            return Err(Error::ShapeMismatch {expected: (dataset.rows, self.output_size()), found: (dataset.rows, dataset.cols)});
+        }
+        let data = dataset.data.iter().map(|row| {
+            let mut start = 0;
+            self.categories.iter().map(|categories| {
+                let value = categories[class_index(&row[start..start + categories.len()])];
+                start += categories.len();
+                value
+            }).collect()
+        }).collect();
+
+        Ok(create_dataset(dataset.rows, self.categories.len(), data))
+    }
+
+    pub fn fit_transform(&mut self, dataset: &DataSet) -> Result<DataSet> {
+        self.fit(dataset)?;
+        self.transform(dataset)
+    }
+
+    pub fn save<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writeln!(writer, "onehot {} {}", self.categories.len(), self.ignore_unknown)?;
+        for categories in self.categories.iter() {
+            write_values(writer, categories)?;
+        }
+        Ok(())
+    }
+
+    pub fn load<R: Read>(reader: R) -> Result<OneHotEncoder> {
+        let mut lines = read_lines(reader)?;
+        let header = next_line(&mut lines, "onehot")?;
+        let fields: Vec<&str> = header.split_whitespace().collect();
+        let (count, ignore_unknown) = match fields.as_slice() {
+            ["onehot", count, ignore] => (
+                count.parse::<usize>().map_err(|_| Error::Parse {row: 1, column: 2, message: format!("bad column count {:?}", count)})?,
+                ignore.parse::<bool>().map_err(|_| Error::Parse {row: 1, column: 3, message: format!("bad flag {:?}", ignore)})?,
+            ),
+            _ => return Err(Error::Parse {row: 1, column: 1, message: "not a one-hot encoder".to_string()}),
+        };
+        let mut categories = Vec::new();
+        for row in 0..count {
+            let values = parse_values(&next_line(&mut lines, "categories")?, row + 2)?;
+            if values.is_empty() {
+                return Err(Error::Parse {row: row + 2, column: 1, message: "a column needs at least one category".to_string()});
+            }
+            categories.push(values);
+        }
+
+        Ok(OneHotEncoder {categories, ignore_unknown})
+    }
+}
+
+fn write_values<W: Write>(writer: &mut W, values: &[f32]) -> Result<()> {
+    let fields: Vec<String> = values.iter().map(|x| x.to_string()).collect();
+    writeln!(writer, "{}", fields.join(" "))?;
+    Ok(())
+}
+
+fn read_lines<R: Read>(reader: R) -> Result<std::vec::IntoIter<String>> {
+    let lines = BufReader::new(reader).lines().collect::<std::io::Result<Vec<String>>>()?;
+    Ok(lines.into_iter())
+}
+
+fn next_line(lines: &mut std::vec::IntoIter<String>, what: &str) -> Result<String> {
+    lines.next().ok_or_else(|| Error::Parse {row: 0, column: 0, message: format!("missing {}", what)})
+}
+
+fn parse_value(text: &str, row: usize) -> Result<f32> {
+    text.parse::<f32>().map_err(|_| Error::Parse {row, column: 0, message: format!("{:?} is not a number", text)})
+}
+
+fn parse_count(text: &str, column: usize) -> Result<usize> {
+    text.parse::<usize>().map_err(|_| Error::Parse {row: 1, column, message: format!("bad count {:?}", text)})
+}
+
+fn parse_values(line: &str, row: usize) -> Result<Vec<f32>> {
+    line.split_whitespace().map(|field| parse_value(field, row)).collect()
+}
@@ -0,0 +1,583 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread::JoinHandle;
+use memmap2::Mmap;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::csv::*;
+use crate::dataset::*;
+use crate::npy::read_header;
+use crate::prelude::*;
+
+// One row of features and its matching targets.
+pub type Sample<T = f32> = (Vec<T>, Vec<T>);
+
+// A sample and the weight of its row in the loss.
+pub type WeightedSample<T = f32> = (Sample<T>, f32);
+
+// Feature rows, target rows and the weight of each row.
+pub type WeightedBatch<T = f32> = (DataSet<T>, DataSet<T>, Vec<f32>);
+
+// Produces training rows on demand so that a data set never has to be held
+// in memory as a whole. A source is read front to back once per epoch.
+pub trait DataSource<T: Float = f32> {
+    fn num_features(&self) -> usize;
+    fn num_targets(&self) -> usize;
+
+    // Rewinds the source to the start of a new epoch.
+    fn reset(&mut self) -> Result<()>;
+
+    // The next row of the current epoch, or None once it is exhausted.
+    fn next_sample(&mut self) -> Result<Option<Sample<T>>>;
+
+    // The next row with its training weight; sources without weights give 1.
+    fn next_weighted_sample(&mut self) -> Result<Option<WeightedSample<T>>> {
+        Ok(self.next_sample()?.map(|sample| (sample, 1.0)))
+    }
+
+    // Up to `batch_size` rows as a feature set and a target set, or None
+    // once the epoch is exhausted. The last batch may be smaller.
+    fn next_batch(&mut self, batch_size: usize) -> Result<Option<(DataSet<T>, DataSet<T>)>> {
+        Ok(self.next_weighted_batch(batch_size)?.map(|(features, targets, _)| (features, targets)))
+    }
+
+    fn next_weighted_batch(&mut self, batch_size: usize) -> Result<Option<WeightedBatch<T>>> {
+        let mut features: Vec<Vec<T>> = Vec::new();
+        let mut targets: Vec<Vec<T>> = Vec::new();
+        let mut weights: Vec<f32> = Vec::new();
+        while features.len() < batch_size {
+            match self.next_weighted_sample()? {
+                Some(((feature, target), weight)) => {
+                    features.push(feature);
+                    targets.push(target);
+                    weights.push(weight);
+                },
+                None => break,
+            }
+        }
+        if features.is_empty() {
+            return Ok(None);
+        }
+
+        let rows = features.len();
+        let features = DataSet {rows, cols: self.num_features(), data: features};
+        let targets = DataSet {rows, cols: self.num_targets(), data: targets};
+        Ok(Some((features, targets, weights)))
+    }
+}
+
+// Rows already held in memory, visited each epoch in the order chosen by
+// `sampler`, with optional per-row weights.
+pub struct MemorySource<T = f32> {
+    pub features: DataSet<T>,
+    pub targets: DataSet<T>,
+    pub sampler: Sampler,
+    pub weights: Option<Vec<f32>>,
+    order: Vec<usize>,
+    position: usize,
+}
+
+pub fn create_memory_source<T: Float>(features: DataSet<T>, targets: DataSet<T>, shuffle: bool) -> MemorySource<T> {
+    create_sampled_source(features, targets, if shuffle { Sampler::Shuffled } else { Sampler::Sequential })
+}
+
+pub fn create_sampled_source<T: Float>(features: DataSet<T>, targets: DataSet<T>, sampler: Sampler) -> MemorySource<T> {
+    assert!(features.rows == targets.rows);
+    let order = (0..features.rows).collect();
+    MemorySource {features, targets, sampler, weights: None, order, position: 0}
+}
+
+impl<T: Float> DataSource<T> for MemorySource<T> {
+    fn num_features(&self) -> usize {
+        self.features.cols
+    }
+
+    fn num_targets(&self) -> usize {
+        self.targets.cols
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        if let Some(weights) = &self.weights {
+            if weights.len() != self.features.rows {
+                return Err(Error::ShapeMismatch {expected: (self.features.rows, 1), found: (weights.len(), 1)});
+            }
+        }
+        self.order = self.sampler.order(&self.targets, &mut rand::thread_rng())?;
+        self.position = 0;
+        Ok(())
+    }
+
+    fn next_sample(&mut self) -> Result<Option<Sample<T>>> {
+        Ok(self.next_weighted_sample()?.map(|(sample, _)| sample))
+    }
+
+    fn next_weighted_sample(&mut self) -> Result<Option<WeightedSample<T>>> {
+        let row = match self.order.get(self.position) {
+            Some(&row) => row,
+            None => return Ok(None),
+        };
+        self.position += 1;
+        let weight = self.weights.as_ref().map_or(1.0, |weights| weights[row]);
+        Ok(Some(((self.features.data[row].clone(), self.targets.data[row].clone()), weight)))
+    }
+}
+
+// Rows computed by a closure, which is given the row's index within the
+// epoch and returns None to end the epoch.
+pub struct GeneratorSource<F, T = f32> {
+    num_features: usize,
+    num_targets: usize,
+    generator: F,
+    position: usize,
+    element: PhantomData<T>,
+}
+
+pub fn create_generator_source<T: Float, F>(num_features: usize, num_targets: usize, generator: F) -> GeneratorSource<F, T>
+where F: FnMut(usize) -> Option<Sample<T>> {
+    GeneratorSource {num_features, num_targets, generator, position: 0, element: PhantomData}
+}
+
+impl<T: Float, F> DataSource<T> for GeneratorSource<F, T>
+where F: FnMut(usize) -> Option<Sample<T>> {
+    fn num_features(&self) -> usize {
+        self.num_features
+    }
+
+    fn num_targets(&self) -> usize {
+        self.num_targets
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.position = 0;
+        Ok(())
+    }
+
+    fn next_sample(&mut self) -> Result<Option<Sample<T>>> {
+        let sample = match (self.generator)(self.position) {
+            Some(sample) => sample,
+            None => return Ok(None),
+        };
+        if sample.0.len() != self.num_features || sample.1.len() != self.num_targets {
+            let found = if sample.0.len() != self.num_features { sample.0.len() } else { sample.1.len() };
+            let expected = if sample.0.len() != self.num_features { self.num_features } else { self.num_targets };
+            return Err(Error::ShapeMismatch {expected: (1, expected), found: (1, found)});
+        }
+        self.position += 1;
+        Ok(Some(sample))
+    }
+}
+
+// Streams a CSV file line by line. Missing values may raise an error, skip
+// the row or be filled with a constant; column means need the whole file
+// and are not supported.
+pub struct CsvSource<T = f32> {
+    path: PathBuf,
+    options: CsvOptions,
+    feature_indices: Vec<usize>,
+    target_indices: Vec<usize>,
+    lines: Option<Lines<BufReader<File>>>,
+    line_number: usize,
+    element: PhantomData<T>,
+}
+
+impl<T: Float> CsvSource<T> {
+    pub fn open<P: AsRef<Path>>(path: P, options: &CsvOptions, targets: &[Column]) -> Result<CsvSource<T>> {
+        if let MissingValues::ColumnMean = options.missing {
+            return Err(Error::InvalidConfig("column means cannot be computed while streaming".to_string()));
+        }
+
+        let mut lines = BufReader::new(File::open(path.as_ref())?).lines();
+        let mut header: Vec<String> = Vec::new();
+        if options.has_header {
+            if let Some(line) = lines.next() {
+                header = split_fields(&line?, options.delimiter);
+            }
+        }
+        let mut width = header.len();
+        if !options.has_header {
+            for line in lines {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    width = split_fields(&line, options.delimiter).len();
+                    break;
+                }
+            }
+        }
+        let resolve = |column: &Column| -> Result<usize> {
+            match column {
+                Column::Index(index) => Ok(*index),
+                Column::Name(name) => header.iter().position(|h| h == name)
+                    .ok_or_else(|| Error::Parse {row: 1, column: 0, message: format!("no column named {:?}", name)}),
+            }
+        };
+
+        let target_indices = targets.iter().map(resolve).collect::<Result<Vec<usize>>>()?;
+        let feature_indices: Vec<usize> = if options.columns.is_empty() {
+            (0..width).filter(|index| !target_indices.contains(index)).collect()
+        } else {
+            options.columns.iter().map(resolve).collect::<Result<Vec<usize>>>()?
+                .into_iter().filter(|index| !target_indices.contains(index)).collect()
+        };
+
+        let mut source = CsvSource {
+            path: path.as_ref().to_path_buf(),
+            options: options.clone(),
+            feature_indices,
+            target_indices,
+            lines: None,
+            line_number: 0,
+            element: PhantomData,
+        };
+        source.reset()?;
+        Ok(source)
+    }
+
+    fn parse_fields(&self, line: &str, indices: &[usize]) -> Result<Option<Vec<T>>> {
+        let fields = split_fields(line, self.options.delimiter);
+        let mut values = Vec::new();
+        for &index in indices {
+            let field = match fields.get(index) {
+                Some(field) => field,
+                None => return Err(Error::Parse {row: self.line_number, column: index + 1, message: format!("row has only {} fields", fields.len())}),
+            };
+            if field.is_empty() || self.options.missing_markers.iter().any(|m| m == field) {
+                match self.options.missing {
+                    MissingValues::Fill(value) => values.push(T::from_f32(value)),
+                    MissingValues::SkipRow => return Ok(None),
+                    _ => return Err(Error::Parse {row: self.line_number, column: index + 1, message: "missing value".to_string()}),
+                }
+            } else {
+                match field.parse::<f64>() {
+                    Ok(value) => values.push(T::from_f64(value)),
+                    Err(_) => return Err(Error::Parse {row: self.line_number, column: index + 1, message: format!("{:?} is not a number", field)}),
+                }
+            }
+        }
+
+        Ok(Some(values))
+    }
+}
+
+impl<T: Float> DataSource<T> for CsvSource<T> {
+    fn num_features(&self) -> usize {
+        self.feature_indices.len()
+    }
+
+    fn num_targets(&self) -> usize {
+        self.target_indices.len()
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        let mut lines = BufReader::new(File::open(&self.path)?).lines();
+        self.line_number = 0;
+        if self.options.has_header {
+            lines.next().transpose()?;
+            self.line_number += 1;
+        }
+        self.lines = Some(lines);
+        Ok(())
+    }
+
+    fn next_sample(&mut self) -> Result<Option<Sample<T>>> {
+        loop {
+            let line = match self.lines.as_mut().and_then(|lines| lines.next()) {
+                Some(line) => line?,
+                None => return Ok(None),
+            };
+            self.line_number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let features = self.parse_fields(&line, &self.feature_indices)?;
+            let targets = self.parse_fields(&line, &self.target_indices)?;
+            if let (Some(features), Some(targets)) = (features, targets) {
+                return Ok(Some((features, targets)));
+            }
+        }
+    }
+}
+
+// A pair of 2-D little-endian f32 .npy files mapped into memory, so that
+// only the rows being read are paged in. Rows are converted to `T` as they
+// are read.
+pub struct MmapSource<T = f32> {
+    features: Mmap,
+    targets: Mmap,
+    feature_offset: usize,
+    target_offset: usize,
+    rows: usize,
+    num_features: usize,
+    num_targets: usize,
+    position: usize,
+    element: PhantomData<T>,
+}
+
+fn map_npy<P: AsRef<Path>>(path: P) -> Result<(Mmap, usize, usize, usize)> {
+    let file = File::open(path)?;
+    // Safety: the mapping is read-only; the file must not be truncated while the source is alive.
+    let map = unsafe { Mmap::map(&file)? };
+    let (descr, shape, offset) = read_header(&mut &map[..])?;
+    if descr != "<f4" {
+        return Err(Error::Parse {row: 0, column: 0, message: format!("only <f4 arrays can be mapped, found {}", descr)});
+    }
+    let (rows, cols) = match shape.len() {
+        1 => (shape[0], 1),
+        2 => (shape[0], shape[1]),
+        _ => return Err(Error::Parse {row: 0, column: 0, message: format!("expected a 1-D or 2-D array, found shape {:?}", shape)}),
+    };
+    let end = rows.checked_mul(cols).and_then(|size| size.checked_mul(4)).and_then(|size| size.checked_add(offset));
+    if end.is_none_or(|end| map.len() < end) {
+        return Err(Error::Parse {row: 0, column: 0, message: "file is shorter than its header claims".to_string()});
+    }
+
+    Ok((map, offset, rows, cols))
+}
+
+fn read_row<T: Float>(map: &Mmap, offset: usize, row: usize, cols: usize) -> Vec<T> {
+    let start = offset + row * cols * 4;
+    map[start..start + cols * 4].chunks_exact(4).map(|b| T::from_f32(f32::from_le_bytes([b[0], b[1], b[2], b[3]]))).collect()
+}
+
+impl<T: Float> MmapSource<T> {
+    pub fn open<P: AsRef<Path>, Q: AsRef<Path>>(features: P, targets: Q) -> Result<MmapSource<T>> {
+        let (features, feature_offset, rows, num_features) = map_npy(features)?;
+        let (targets, target_offset, target_rows, num_targets) = map_npy(targets)?;
+        if rows != target_rows {
+            return Err(Error::ShapeMismatch {expected: (rows, num_features), found: (target_rows, num_targets)});
+        }
+
+        Ok(MmapSource {features, targets, feature_offset, target_offset, rows, num_features, num_targets, position: 0, element: PhantomData})
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+}
+
+impl<T: Float> DataSource<T> for MmapSource<T> {
+    fn num_features(&self) -> usize {
+        self.num_features
+    }
+
+    fn num_targets(&self) -> usize {
+        self.num_targets
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.position = 0;
+        Ok(())
+    }
+
+    fn next_sample(&mut self) -> Result<Option<Sample<T>>> {
+        if self.position >= self.rows {
+            return Ok(None);
+        }
+        let row = self.position;
+        self.position += 1;
+        Ok(Some((
+            read_row(&self.features, self.feature_offset, row, self.num_features),
+            read_row(&self.targets, self.target_offset, row, self.num_targets),
+        )))
+    }
+}
+
+// Approximately shuffles a stream by keeping `capacity` rows in a buffer
+// and emitting a random one of them each time a row is requested.
+pub struct ShuffleBuffer<S, T = f32> {
+    source: S,
+    capacity: usize,
+    buffer: Vec<WeightedSample<T>>,
+    rng: StdRng,
+}
+
+pub fn create_shuffle_buffer<T: Float, S: DataSource<T>>(source: S, capacity: usize, seed: u64) -> ShuffleBuffer<S, T> {
+    assert!(capacity > 0);
+    ShuffleBuffer {source, capacity, buffer: Vec::with_capacity(capacity), rng: StdRng::seed_from_u64(seed)}
+}
+
+impl<T: Float, S: DataSource<T>> DataSource<T> for ShuffleBuffer<S, T> {
+    fn num_features(&self) -> usize {
+        self.source.num_features()
+    }
+
+    fn num_targets(&self) -> usize {
+        self.source.num_targets()
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.buffer.clear();
+        self.source.reset()
+    }
+
+    fn next_sample(&mut self) -> Result<Option<Sample<T>>> {
+        Ok(self.next_weighted_sample()?.map(|(sample, _)| sample))
+    }
+
+    fn next_weighted_sample(&mut self) -> Result<Option<WeightedSample<T>>> {
+        while self.buffer.len() < self.capacity {
+            match self.source.next_weighted_sample()? {
+                Some(sample) => self.buffer.push(sample),
+                None => break,
+            }
+        }
+        if self.buffer.is_empty() {
+            return Ok(None);
+        }
+        let index = self.rng.gen_range(0..self.buffer.len());
+        Ok(Some(self.buffer.swap_remove(index)))
+    }
+}
+
+// Reads rows from `source` on a background thread, keeping up to `depth`
+// chunks of `chunk_size` rows ready ahead of the training loop.
+pub struct Prefetch<S, T = f32> {
+    source: Option<S>,
+    worker: Option<JoinHandle<S>>,
+    receiver: Option<Receiver<Result<Vec<WeightedSample<T>>>>>,
+    pending: VecDeque<WeightedSample<T>>,
+    chunk_size: usize,
+    depth: usize,
+    num_features: usize,
+    num_targets: usize,
+    finished: bool,
+}
+
+pub fn create_prefetch<T: Float, S: DataSource<T> + Send + 'static>(source: S, chunk_size: usize, depth: usize) -> Prefetch<S, T> {
+    assert!(chunk_size > 0);
+    Prefetch {
+        num_features: source.num_features(),
+        num_targets: source.num_targets(),
+        source: Some(source),
+        worker: None,
+        receiver: None,
+        pending: VecDeque::new(),
+        chunk_size,
+        depth,
+        finished: false,
+    }
+}
+
+impl<T: Float, S: DataSource<T> + Send + 'static> Prefetch<S, T> {
+    fn start(&mut self) {
+        let mut source = match self.source.take() {
+            Some(source) => source,
+            None => return,
+        };
+        let (sender, receiver) = sync_channel(self.depth);
+        let chunk_size = self.chunk_size;
+        self.worker = Some(std::thread::spawn(move || {
+            loop {
+                let mut chunk = Vec::with_capacity(chunk_size);
+                let mut failed = false;
+                while chunk.len() < chunk_size {
+                    match source.next_weighted_sample() {
+                        Ok(Some(sample)) => chunk.push(sample),
+                        Ok(None) => break,
+                        Err(error) => {
+                            let _ = sender.send(Err(error));
+                            failed = true;
+                            break;
+                        },
+                    }
+                }
+                if failed {
+                    break;
+                }
+                // An empty chunk marks the end of the epoch; a closed channel means the reader went away.
+                let done = chunk.is_empty();
+                if sender.send(Ok(chunk)).is_err() || done {
+                    break;
+                }
+            }
+            source
+        }));
+        self.receiver = Some(receiver);
+    }
+
+    // Closes the channel so that the worker stops, and takes the source back.
+    fn stop(&mut self) -> Result<()> {
+        self.receiver = None;
+        if let Some(worker) = self.worker.take() {
+            match worker.join() {
+                Ok(source) => self.source = Some(source),
+                Err(_) => return Err(Error::Generic("prefetch worker panicked".to_string())),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: Float, S: DataSource<T> + Send + 'static> DataSource<T> for Prefetch<S, T> {
+    fn num_features(&self) -> usize {
+        self.num_features
+    }
+
+    fn num_targets(&self) -> usize {
+        self.num_targets
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.stop()?;
+        self.pending.clear();
+        self.finished = false;
+        match self.source.as_mut() {
+            Some(source) => source.reset(),
+            None => Err(Error::Generic("prefetch source was lost".to_string())),
+        }
+    }
+
+    fn next_sample(&mut self) -> Result<Option<Sample<T>>> {
+        Ok(self.next_weighted_sample()?.map(|(sample, _)| sample))
+    }
+
+    fn next_weighted_sample(&mut self) -> Result<Option<WeightedSample<T>>> {
+        if let Some(sample) = self.pending.pop_front() {
+            return Ok(Some(sample));
+        }
+        if self.finished {
+            return Ok(None);
+        }
+        if self.receiver.is_none() {
+            self.start();
+        }
+
+        let received = match &self.receiver {
+            Some(receiver) => receiver.recv(),
+            None => return Err(Error::Generic("prefetch source was lost".to_string())),
+        };
+        match received {
+            Ok(Ok(chunk)) if !chunk.is_empty() => {
+                self.pending.extend(chunk);
+                Ok(self.pending.pop_front())
+            },
+            Ok(Ok(_)) => {
+                self.finished = true;
+                self.stop()?;
+                Ok(None)
+            },
+            Ok(Err(error)) => {
+                self.finished = true;
+                self.stop()?;
+                Err(error)
+            },
+            Err(_) => {
+                self.finished = true;
+                self.stop()?;
+                Err(Error::Generic("prefetch worker stopped unexpectedly".to_string()))
+            },
+        }
+    }
+}
+
+impl<S, T> Drop for Prefetch<S, T> {
+    fn drop(&mut self) {
+        self.receiver = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
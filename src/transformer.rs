@@ -0,0 +1,184 @@
+use crate::matrix::*;
+use crate::prelude::*;
+use crate::function::*;
+use crate::layer::*;
+use crate::attention::*;
+
+pub struct LayerNorm {
+    pub size: usize,
+    pub epsilon: f32,
+    pub gamma: Matrix,
+    pub beta: Matrix,
+    pub d_gamma: Matrix,
+    pub d_beta: Matrix,
+    normalized: Matrix,
+    inv_std: Vec<f32>
+}
+
+pub fn create_layer_norm(size: usize) -> LayerNorm {
+    LayerNorm {
+        size,
+        epsilon: 1e-5,
+        gamma: Matrix::create_matrix(1, size, vec![vec![1.0; size]; 1]),
+        beta: Matrix::create_zero_matrix(1, size),
+        d_gamma: Matrix::create_zero_matrix(1, size),
+        d_beta: Matrix::create_zero_matrix(1, size),
+        normalized: Matrix::create_zero_matrix(1, size),
+        inv_std: Vec::new()
+    }
+}
+
+impl LayerNorm {
+    pub fn forward(&mut self, input: &Matrix) -> Matrix {
+        assert!(input.cols == self.size);
+        let n = self.size as f32;
+        self.normalized = Matrix::create_zero_matrix(input.rows, input.cols);
+        self.inv_std = vec![0.0; input.rows];
+        let mut output = Matrix::create_zero_matrix(input.rows, input.cols);
+        for i in 0..input.rows {
+            let mean = input.data[i].iter().sum::<f32>() / n;
+            let variance = input.data[i].iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / n;
+            let inv_std = 1.0 / (variance + self.epsilon).sqrt();
+            self.inv_std[i] = inv_std;
+            for j in 0..input.cols {
+                let x_hat = (input.get(i, j) - mean) * inv_std;
+                self.normalized.set(i, j, x_hat);
+                output.set(i, j, self.gamma.get(0, j) * x_hat + self.beta.get(0, j));
+            }
+        }
+
+        output
+    }
+
+    pub fn backward(&mut self, d_output: &Matrix) -> Matrix {
+        assert!(d_output.rows == self.normalized.rows && d_output.cols == self.size);
+        let n = self.size as f32;
+        let mut d_input = Matrix::create_zero_matrix(d_output.rows, d_output.cols);
+        for i in 0..d_output.rows {
+            let mut sum_d_hat = 0.0;
+            let mut sum_d_hat_x_hat = 0.0;
+            for j in 0..self.size {
+                let x_hat = self.normalized.get(i, j);
+                let d_hat = d_output.get(i, j) * self.gamma.get(0, j);
+                sum_d_hat += d_hat;
+                sum_d_hat_x_hat += d_hat * x_hat;
+                self.d_gamma.data[0][j] += d_output.get(i, j) * x_hat;
+                self.d_beta.data[0][j] += d_output.get(i, j);
+            }
+            for j in 0..self.size {
+                let x_hat = self.normalized.get(i, j);
+                let d_hat = d_output.get(i, j) * self.gamma.get(0, j);
+                let val = self.inv_std[i] / n * (n * d_hat - sum_d_hat - x_hat * sum_d_hat_x_hat);
+                d_input.set(i, j, val);
+            }
+        }
+
+        d_input
+    }
+
+    pub fn update(&mut self, learning_rate: f32) {
+        let mut step = self.d_gamma.copy();
+        step.scalar_multiply(-learning_rate);
+        step.add_to(&mut self.gamma);
+        let mut step = self.d_beta.copy();
+        step.scalar_multiply(-learning_rate);
+        step.add_to(&mut self.beta);
+    }
+
+    pub fn zero_gradients(&mut self) {
+        self.d_gamma.to_zero();
+        self.d_beta.to_zero();
+    }
+}
+
+pub struct FeedForward {
+    pub hidden: Linear,
+    pub output: Linear,
+    activated: Matrix
+}
+
+pub fn create_feed_forward(model_size: usize, hidden_size: usize) -> FeedForward {
+    FeedForward {
+        hidden: create_linear(model_size, hidden_size),
+        output: create_linear(hidden_size, model_size),
+        activated: Matrix::create_zero_matrix(1, hidden_size)
+    }
+}
+
+impl FeedForward {
+    pub fn forward(&mut self, input: &Matrix) -> Matrix {
+        let mut activated = self.hidden.forward(input);
+        activated.transform(relu_func);
+        let output = self.output.forward(&activated);
+        self.activated = activated;
+        output
+    }
+
+    pub fn backward(&mut self, d_output: &Matrix) -> Matrix {
+        let mut d_activated = self.output.backward(d_output);
+        for i in 0..d_activated.rows {
+            for j in 0..d_activated.cols {
+                let val = d_activated.get(i, j) * relu_deriv(self.activated.get(i, j));
+                d_activated.set(i, j, val);
+            }
+        }
+        self.hidden.backward(&d_activated)
+    }
+
+    pub fn update(&mut self, learning_rate: f32) {
+        self.hidden.update(learning_rate);
+        self.output.update(learning_rate);
+    }
+
+    pub fn zero_gradients(&mut self) {
+        self.hidden.zero_gradients();
+        self.output.zero_gradients();
+    }
+}
+
+// Post-norm encoder block: norm(x + attention(x)) followed by norm(h + feed_forward(h)).
+pub struct EncoderBlock {
+    pub attention: MultiHeadAttention,
+    pub attention_norm: LayerNorm,
+    pub feed_forward: FeedForward,
+    pub feed_forward_norm: LayerNorm
+}
+
+pub fn create_encoder_block(model_size: usize, num_heads: usize, hidden_size: usize) -> EncoderBlock {
+    EncoderBlock {
+        attention: create_multi_head_attention(model_size, num_heads),
+        attention_norm: create_layer_norm(model_size),
+        feed_forward: create_feed_forward(model_size, hidden_size),
+        feed_forward_norm: create_layer_norm(model_size)
+    }
+}
+
+impl EncoderBlock {
+    pub fn forward(&mut self, input: &Matrix, mask: Option<&Matrix>) -> Matrix {
+        let attended = self.attention.forward(input, mask);
+        let hidden = self.attention_norm.forward(&input.add(&attended));
+        let fed = self.feed_forward.forward(&hidden);
+        self.feed_forward_norm.forward(&hidden.add(&fed))
+    }
+
+    pub fn backward(&mut self, d_output: &Matrix) -> Matrix {
+        let d_residual = self.feed_forward_norm.backward(d_output);
+        let d_hidden = d_residual.add(&self.feed_forward.backward(&d_residual));
+        let d_residual = self.attention_norm.backward(&d_hidden);
+        d_residual.add(&self.attention.backward(&d_residual))
+    }
+
+    pub fn update(&mut self, learning_rate: f32) {
+        self.attention.update(learning_rate);
+        self.attention_norm.update(learning_rate);
+        self.feed_forward.update(learning_rate);
+        self.feed_forward_norm.update(learning_rate);
+    }
+
+    pub fn zero_gradients(&mut self) {
+        self.attention.zero_gradients();
+        self.attention_norm.zero_gradients();
+        self.feed_forward.zero_gradients();
+        self.feed_forward_norm.zero_gradients();
+    }
+}
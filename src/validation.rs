@@ -0,0 +1,226 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use crate::dataset::*;
+use crate::network::*;
+use crate::prelude::*;
+
+// Matching feature and class rows taken from a larger pair of data sets.
+#[derive(Debug, Clone)]
+pub struct Subset {
+    pub features: DataSet,
+    pub classes: DataSet,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fold {
+    pub train: Vec<usize>,
+    pub test: Vec<usize>,
+}
+
+// Yields one `Fold` per group, each group serving once as the test rows.
+pub struct KFold {
+    groups: Vec<Vec<usize>>,
+    current: usize,
+}
+
+pub struct CrossValidation {
+    pub fold_accuracy: Vec<f32>,
+    pub fold_loss: Vec<f32>,
+}
+
+fn subset(features: &DataSet, classes: &DataSet, indices: &[usize]) -> Subset {
+    Subset {features: select_rows(features, indices), classes: select_rows(classes, indices)}
+}
+
+fn check_pair(features: &DataSet, classes: &DataSet) -> Result<()> {
+    if features.rows != classes.rows {
+        return Err(Error::ShapeMismatch {expected: (features.rows, classes.cols), found: (classes.rows, classes.cols)});
+    }
+    if features.rows == 0 {
+        return Err(Error::EmptyDataset);
+    }
+    Ok(())
+}
+
+fn check_fraction(fraction: f32) -> Result<()> {
+    if !(0.0..1.0).contains(&fraction) {
+        return Err(Error::InvalidConfig(format!("split fraction {} is not in [0, 1)", fraction)));
+    }
+    Ok(())
+}
+
+// Row indices grouped by class, each group shuffled.
+fn class_groups(classes: &DataSet, rng: &mut StdRng) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = vec![Vec::new(); classes.cols];
+    for (row, class) in class_indices(classes).into_iter().enumerate() {
+        groups[class].push(row);
+    }
+    for group in groups.iter_mut() {
+        group.shuffle(rng);
+    }
+    groups
+}
+
+pub fn train_test_split(features: &DataSet, classes: &DataSet, test_fraction: f32, seed: u64) -> Result<(Subset, Subset)> {
+    check_pair(features, classes)?;
+    check_fraction(test_fraction)?;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let indices = permutation(features.rows, &mut rng);
+    let num_test = (test_fraction * features.rows as f32).round() as usize;
+
+    Ok((subset(features, classes, &indices[num_test..]), subset(features, classes, &indices[..num_test])))
+}
+
+// Keeps the class proportions of `classes` in both parts.
+pub fn stratified_train_test_split(features: &DataSet, classes: &DataSet, test_fraction: f32, seed: u64) -> Result<(Subset, Subset)> {
+    check_pair(features, classes)?;
+    check_fraction(test_fraction)?;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut train: Vec<usize> = Vec::new();
+    let mut test: Vec<usize> = Vec::new();
+    for group in class_groups(classes, &mut rng) {
+        let num_test = (test_fraction * group.len() as f32).round() as usize;
+        test.extend_from_slice(&group[..num_test]);
+        train.extend_from_slice(&group[num_test..]);
+    }
+    train.shuffle(&mut rng);
+    test.shuffle(&mut rng);
+
+    Ok((subset(features, classes, &train), subset(features, classes, &test)))
+}
+
+pub fn train_validation_test_split(
+    features: &DataSet,
+    classes: &DataSet,
+    validation_fraction: f32,
+    test_fraction: f32,
+    seed: u64) -> Result<(Subset, Subset, Subset)> {
+
+    check_fraction(validation_fraction + test_fraction)?;
+    let (rest, test) = train_test_split(features, classes, test_fraction, seed)?;
+    let relative = validation_fraction / (1.0 - test_fraction);
+    let (train, validation) = train_test_split(&rest.features, &rest.classes, relative, seed.wrapping_add(1))?;
+
+    Ok((train, validation, test))
+}
+
+// Without a seed the rows are split in order.
+pub fn k_fold(rows: usize, k: usize, seed: Option<u64>) -> Result<KFold> {
+    if k < 2 || k > rows {
+        return Err(Error::InvalidConfig(format!("cannot split {} rows into {} folds", rows, k)));
+    }
+    let indices = match seed {
+        Some(seed) => permutation(rows, &mut StdRng::seed_from_u64(seed)),
+        None => (0..rows).collect(),
+    };
+
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut start = 0;
+    for fold in 0..k {
+        let size = rows / k + if fold < rows % k { 1 } else { 0 };
+        groups.push(indices[start..start + size].to_vec());
+        start += size;
+    }
+
+    Ok(KFold {groups, current: 0})
+}
+
+// Deals the rows of every class round-robin over the folds so that each
+// fold keeps the overall class proportions.
+pub fn stratified_k_fold(classes: &DataSet, k: usize, seed: u64) -> Result<KFold> {
+    if k < 2 || k > classes.rows {
+        return Err(Error::InvalidConfig(format!("cannot split {} rows into {} folds", classes.rows, k)));
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut groups: Vec<Vec<usize>> = vec![Vec::new(); k];
+    let mut next = 0;
+    for group in class_groups(classes, &mut rng) {
+        for row in group {
+            groups[next].push(row);
+            next = (next + 1) % k;
+        }
+    }
+
+    Ok(KFold {groups, current: 0})
+}
+
+impl Iterator for KFold {
+    type Item = Fold;
+
+    fn next(&mut self) -> Option<Fold> {
+        if self.current >= self.groups.len() {
+            return None;
+        }
+        let test = self.groups[self.current].clone();
+        let train = self.groups.iter().enumerate()
+            .filter(|&(fold, _)| fold != self.current)
+            .flat_map(|(_, group)| group.iter().cloned())
+            .collect();
+        self.current += 1;
+
+        Some(Fold {train, test})
+    }
+}
+
+// Trains a fresh network from `create` on every fold's training rows, with
+// parameters from `configure`, and scores it on the fold's test rows.
+pub fn cross_validate<C, P>(
+    features: &DataSet,
+    classes: &DataSet,
+    folds: KFold,
+    mut create: C,
+    mut configure: P) -> Result<CrossValidation>
+where
+    C: FnMut() -> Network,
+    P: FnMut(DataSet, DataSet) -> ParameterSet {
+
+    check_pair(features, classes)?;
+    let mut fold_accuracy = Vec::new();
+    let mut fold_loss = Vec::new();
+    for fold in folds {
+        let train = subset(features, classes, &fold.train);
+        let test = subset(features, classes, &fold.test);
+        let mut network = create();
+        let mut params = configure(train.features, train.classes);
+        network.batch_gradient_descent(&mut params)?;
+
+        let test_features = Rc::new(RefCell::new(test.features));
+        let test_classes = Rc::new(RefCell::new(test.classes));
+        fold_accuracy.push(network.try_accuracy(test_features, test_classes.clone())?);
+        let output = network.get_output();
+        fold_loss.push(match params.config.loss {
+            LossFunction::CrossEntropy => network.cross_entropy_loss(&output.borrow(), test_classes, 0.0),
+            LossFunction::MeanSquaredError => network.mean_squared_error(&output.borrow(), test_classes, 0.0),
+        });
+    }
+
+    Ok(CrossValidation {fold_accuracy, fold_loss})
+}
+
+fn mean(values: &[f32]) -> f32 {
+    values.iter().sum::<f32>() / values.len() as f32
+}
+
+fn std_dev(values: &[f32]) -> f32 {
+    let mean = mean(values);
+    (values.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / values.len() as f32).sqrt()
+}
+
+impl CrossValidation {
+    pub fn mean_accuracy(&self) -> f32 {
+        mean(&self.fold_accuracy)
+    }
+
+    pub fn std_accuracy(&self) -> f32 {
+        std_dev(&self.fold_accuracy)
+    }
+
+    pub fn mean_loss(&self) -> f32 {
+        mean(&self.fold_loss)
+    }
+
+    pub fn std_loss(&self) -> f32 {
+        std_dev(&self.fold_loss)
+    }
+}
@@ -0,0 +1,103 @@
+use crate::matrix::*;
+use crate::network::*;
+use crate::prelude::*;
+
+// Every buffer a training step needs, sized once for a network and reused
+// for each row and batch so that steady-state steps do not allocate. Rows
+// are back-propagated one at a time, so per-row buffers hold a single row.
+// Momentum and rollback state also live here and carry over between steps.
+pub struct Workspace<T = f32> {
+    // 1-based epoch, used for learning rate decay and in `Diverged` errors.
+    pub epoch: usize,
+    // Batch within the epoch, reported in `Diverged` errors.
+    pub batch: usize,
+    pub(crate) example: Matrix<T>,
+    pub(crate) target: Matrix<T>,
+    // Error at each layer's output; the entry for the input layer is unused.
+    pub(crate) errors: Vec<Matrix<T>>,
+    // Per connection: transposed input, this row's gradients and their batch sums.
+    pub(crate) inputs_t: Vec<Matrix<T>>,
+    pub(crate) weight_gradients: Vec<Matrix<T>>,
+    pub(crate) bias_gradients: Vec<Matrix<T>>,
+    pub(crate) weight_sums: Vec<Matrix<T>>,
+    pub(crate) bias_sums: Vec<Matrix<T>>,
+    pub(crate) weight_penalty: Vec<Matrix<T>>,
+    pub(crate) bias_penalty: Vec<Matrix<T>>,
+    // The previous update, for momentum.
+    pub(crate) weight_steps: Vec<Matrix<T>>,
+    pub(crate) bias_steps: Vec<Matrix<T>>,
+    // Weights rounded to the training precision, used by the passes while
+    // the f32 masters wait for the update.
+    pub(crate) working_weights: Vec<Matrix<T>>,
+    pub(crate) working_biases: Vec<Matrix<T>>,
+    // Per hidden layer: outgoing weights transposed, the error they carry
+    // back and the activation derivative.
+    pub(crate) weights_t: Vec<Matrix<T>>,
+    pub(crate) back_errors: Vec<Matrix<T>>,
+    pub(crate) derivatives: Vec<Matrix<T>>,
+    // Weights before the last finite step, for `DivergencePolicy::Rollback`.
+    pub(crate) last_weights: Vec<Matrix<T>>,
+    pub(crate) last_biases: Vec<Matrix<T>>,
+    pub(crate) has_last: bool,
+    pub(crate) learning_rate_scale: f32,
+}
+
+pub fn create_workspace<T: Float>(network: &Network<T>) -> Workspace<T> {
+    let layers = &network.layers;
+    let connections = &network.connections;
+    let like = |m: &Matrix<T>| Matrix::create_zero_matrix(m.rows, m.cols);
+    let weights = || connections.iter().map(|c| like(&c.weights)).collect::<Vec<_>>();
+    let biases = || connections.iter().map(|c| like(&c.bias)).collect::<Vec<_>>();
+    let hidden = 1..network.num_layers - 1;
+
+    Workspace {
+        epoch: 1,
+        batch: 0,
+        example: Matrix::create_zero_matrix(1, layers[0].size),
+        target: Matrix::create_zero_matrix(1, layers[network.num_layers-1].size),
+        errors: layers.iter().map(|layer| Matrix::create_zero_matrix(1, layer.size)).collect(),
+        inputs_t: connections.iter().map(|c| Matrix::create_zero_matrix(c.weights.rows, 1)).collect(),
+        weight_gradients: weights(),
+        bias_gradients: biases(),
+        weight_sums: weights(),
+        bias_sums: biases(),
+        weight_penalty: weights(),
+        bias_penalty: biases(),
+        weight_steps: weights(),
+        bias_steps: biases(),
+        working_weights: weights(),
+        working_biases: biases(),
+        weights_t: hidden.clone().map(|layer| Matrix::create_zero_matrix(connections[layer].weights.cols, connections[layer].weights.rows)).collect(),
+        back_errors: hidden.clone().map(|layer| Matrix::create_zero_matrix(1, layers[layer].size)).collect(),
+        derivatives: hidden.map(|layer| Matrix::create_zero_matrix(1, layers[layer].size)).collect(),
+        last_weights: weights(),
+        last_biases: biases(),
+        has_last: false,
+        learning_rate_scale: 1.0,
+    }
+}
+
+impl<T: Float> Workspace<T> {
+    // Whether this workspace was sized for `network`.
+    pub fn fits(&self, network: &Network<T>) -> bool {
+        self.weight_sums.len() == network.num_connections
+            && self.weight_sums.iter().zip(network.connections.iter())
+                .all(|(sum, c)| (sum.rows, sum.cols) == (c.weights.rows, c.weights.cols))
+    }
+
+    // The factor by which `DivergencePolicy::Rollback` has cut the learning rate.
+    pub fn learning_rate_scale(&self) -> f32 {
+        self.learning_rate_scale
+    }
+
+    // Forgets momentum and rollback state, e.g. before training from scratch.
+    pub fn reset(&mut self) {
+        for step in self.weight_steps.iter_mut().chain(self.bias_steps.iter_mut()) {
+            step.to_zero();
+        }
+        self.has_last = false;
+        self.learning_rate_scale = 1.0;
+        self.epoch = 1;
+        self.batch = 0;
+    }
+}
@@ -0,0 +1,131 @@
+#[cfg(test)]
+mod attention_tests {
+    use cranium_rs::matrix::*;
+    use cranium_rs::attention::*;
+
+    fn weighted_sum(output: &Matrix, weights: &Matrix) -> f32 {
+        output.hadamard(weights).data.iter().flatten().sum()
+    }
+
+    fn sample_matrix(rows: usize, cols: usize, seed: f32) -> Matrix {
+        let mut matrix = Matrix::create_zero_matrix(rows, cols);
+        for i in 0..rows {
+            for j in 0..cols {
+                matrix.set(i, j, ((i * cols + j) as f32 * 0.37 + seed).sin());
+            }
+        }
+        matrix
+    }
+
+    #[test]
+    fn test_causal_mask() {
+        let mask = causal_mask(3);
+        let expected = vec![vec![1.0, 0.0, 0.0], vec![1.0, 1.0, 0.0], vec![1.0, 1.0, 1.0]];
+        assert_eq!(mask.data, expected);
+    }
+
+    #[test]
+    fn test_positional_encoding() {
+        let encoding = positional_encoding(4, 6);
+        assert_eq!(encoding.rows, 4);
+        assert_eq!(encoding.cols, 6);
+        for j in 0..6 {
+            let expected = if j % 2 == 0 { 0.0 } else { 1.0 };
+            assert_eq!(encoding.get(0, j), expected);
+        }
+        assert!((encoding.get(1, 0) - 1.0_f32.sin()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_attention_with_large_scores_stays_finite() {
+        let query = Matrix::create_matrix(2, 2, vec![vec![100.0, 0.0], vec![0.0, 100.0]]);
+        let key = Matrix::create_matrix(2, 2, vec![vec![100.0, 0.0], vec![0.0, 90.0]]);
+        let value = sample_matrix(2, 2, 0.1);
+        let attention = scaled_dot_product_attention(&query, &key, &value, None);
+
+        assert!(attention.weights.data.iter().flatten().all(|w| w.is_finite()));
+        assert!((attention.weights.get(0, 0) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_attention_weights_sum_to_one() {
+        let query = sample_matrix(3, 4, 0.1);
+        let key = sample_matrix(5, 4, 0.2);
+        let value = sample_matrix(5, 2, 0.3);
+        let attention = scaled_dot_product_attention(&query, &key, &value, None);
+
+        assert_eq!(attention.output.rows, 3);
+        assert_eq!(attention.output.cols, 2);
+        for row in attention.weights.data.iter() {
+            assert!((row.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_attention_mask() {
+        let input = sample_matrix(3, 4, 0.5);
+        let attention = scaled_dot_product_attention(&input, &input, &input, Some(&causal_mask(3)));
+
+        assert_eq!(attention.weights.get(0, 0), 1.0);
+        assert_eq!(attention.weights.get(0, 1), 0.0);
+        assert_eq!(attention.weights.get(1, 2), 0.0);
+        assert_eq!(attention.output.data[0], input.data[0]);
+    }
+
+    #[test]
+    fn test_attention_backward() {
+        let query = sample_matrix(3, 4, 0.1);
+        let key = sample_matrix(3, 4, 0.7);
+        let value = sample_matrix(3, 4, 1.3);
+        let d_output = sample_matrix(3, 4, 2.1);
+        let attention = scaled_dot_product_attention(&query, &key, &value, None);
+        let grads = scaled_dot_product_attention_backward(&query, &key, &value, &attention.weights, &d_output);
+
+        let epsilon = 1e-2;
+        for i in 0..3 {
+            for j in 0..4 {
+                let mut plus = query.copy();
+                plus.set(i, j, query.get(i, j) + epsilon);
+                let mut minus = query.copy();
+                minus.set(i, j, query.get(i, j) - epsilon);
+                let loss_plus = weighted_sum(&scaled_dot_product_attention(&plus, &key, &value, None).output, &d_output);
+                let loss_minus = weighted_sum(&scaled_dot_product_attention(&minus, &key, &value, None).output, &d_output);
+                let numeric = (loss_plus - loss_minus) / (2.0 * epsilon);
+                assert!((numeric - grads.d_query.get(i, j)).abs() < 1e-2);
+
+                let mut plus = key.copy();
+                plus.set(i, j, key.get(i, j) + epsilon);
+                let mut minus = key.copy();
+                minus.set(i, j, key.get(i, j) - epsilon);
+                let loss_plus = weighted_sum(&scaled_dot_product_attention(&query, &plus, &value, None).output, &d_output);
+                let loss_minus = weighted_sum(&scaled_dot_product_attention(&query, &minus, &value, None).output, &d_output);
+                let numeric = (loss_plus - loss_minus) / (2.0 * epsilon);
+                assert!((numeric - grads.d_key.get(i, j)).abs() < 1e-2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_head_attention_backward() {
+        let mut attention = create_multi_head_attention(4, 2);
+        let input = sample_matrix(3, 4, 0.4);
+        let d_output = sample_matrix(3, 4, 1.9);
+        let mask = causal_mask(3);
+        attention.forward(&input, Some(&mask));
+        let d_input = attention.backward(&d_output);
+
+        let epsilon = 1e-2;
+        for i in 0..3 {
+            for j in 0..4 {
+                let mut plus = input.copy();
+                plus.set(i, j, input.get(i, j) + epsilon);
+                let mut minus = input.copy();
+                minus.set(i, j, input.get(i, j) - epsilon);
+                let loss_plus = weighted_sum(&attention.forward(&plus, Some(&mask)), &d_output);
+                let loss_minus = weighted_sum(&attention.forward(&minus, Some(&mask)), &d_output);
+                let numeric = (loss_plus - loss_minus) / (2.0 * epsilon);
+                assert!((numeric - d_input.get(i, j)).abs() < 2e-2 * (1.0 + numeric.abs()));
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+#[cfg(test)]
+mod augment_tests {
+    use cranium_rs::augment::*;
+    use cranium_rs::dataset::*;
+    use cranium_rs::error::Error;
+    use cranium_rs::function::*;
+    use cranium_rs::network::*;
+
+    fn ramp(rows: usize, cols: usize) -> DataSet {
+        create_dataset(rows, cols, (0..rows).map(|i| (0..cols).map(|j| (i * cols + j + 1) as f32).collect()).collect())
+    }
+
+    fn augmented(transforms: Vec<Transform>, seed: u64, features: &DataSet) -> (DataSet, DataSet) {
+        let mut features = features.clone();
+        let mut targets = one_hot(&(0..features.rows).map(|i| i % 2).collect::<Vec<usize>>(), 2);
+        let mut augmentation = create_augmentation(transforms, seed).unwrap();
+        augmentation.set_training(true);
+        augmentation.apply(&mut features, &mut targets);
+        (features, targets)
+    }
+
+    #[test]
+    fn test_inert_outside_training() {
+        let features = ramp(4, 3);
+        let mut copy = features.clone();
+        let mut targets = one_hot(&[0, 1, 0, 1], 2);
+        let mut augmentation = create_augmentation(vec![Transform::GaussianNoise(1.0), Transform::RandomMask(1.0)], 0).unwrap();
+        assert!(!augmentation.is_training());
+        augmentation.apply(&mut copy, &mut targets);
+        assert_eq!(copy.data, features.data);
+    }
+
+    #[test]
+    fn test_gaussian_noise_is_seeded() {
+        let features = create_dataset(200, 10, vec![vec![0.0; 10]; 200]);
+        let (first, _) = augmented(vec![Transform::GaussianNoise(0.5)], 3, &features);
+        let (second, _) = augmented(vec![Transform::GaussianNoise(0.5)], 3, &features);
+        let (other, _) = augmented(vec![Transform::GaussianNoise(0.5)], 4, &features);
+        assert_eq!(first.data, second.data);
+        assert_ne!(first.data, other.data);
+
+        let values: Vec<f32> = first.data.iter().flatten().cloned().collect();
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        let std_dev = (values.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / values.len() as f32).sqrt();
+        assert!(mean.abs() < 0.05);
+        assert!((std_dev - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_random_mask() {
+        let features = ramp(50, 20);
+        assert!(augmented(vec![Transform::RandomMask(1.0)], 0, &features).0.data.iter().flatten().all(|&x| x == 0.0));
+        assert_eq!(augmented(vec![Transform::RandomMask(0.0)], 0, &features).0.data, features.data);
+
+        let (masked, _) = augmented(vec![Transform::RandomMask(0.3)], 0, &features);
+        let zeros = masked.data.iter().flatten().filter(|&&x| x == 0.0).count() as f32 / 1000.0;
+        assert!((zeros - 0.3).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_mixup_blends_features_and_targets_together() {
+        let targets = one_hot(&(0..8).map(|i| i % 2).collect::<Vec<usize>>(), 2);
+        let (features, mixed_targets) = augmented(vec![Transform::Mixup(0.4)], 1, &targets);
+        assert_eq!(features.data, mixed_targets.data);
+        assert_ne!(features.data, targets.data);
+        for row in mixed_targets.data.iter() {
+            assert!((row.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+            assert!(row.iter().all(|&x| (0.0..=1.0).contains(&x)));
+        }
+    }
+
+    #[test]
+    fn test_horizontal_flip() {
+        let shape = ImageShape {channels: 2, height: 2, width: 3};
+        let features = ramp(1, 12);
+        let (flipped, _) = augmented(vec![Transform::HorizontalFlip {shape, probability: 1.0}], 0, &features);
+        assert_eq!(flipped.data[0], vec![3.0, 2.0, 1.0, 6.0, 5.0, 4.0, 9.0, 8.0, 7.0, 12.0, 11.0, 10.0]);
+        let (kept, _) = augmented(vec![Transform::HorizontalFlip {shape, probability: 0.0}], 0, &features);
+        assert_eq!(kept.data, features.data);
+    }
+
+    #[test]
+    fn test_random_shift() {
+        let shape = ImageShape {channels: 1, height: 4, width: 4};
+        let features = ramp(20, 16);
+        assert_eq!(augmented(vec![Transform::RandomShift {shape, max_shift: 0}], 0, &features).0.data, features.data);
+
+        let (shifted, _) = augmented(vec![Transform::RandomShift {shape, max_shift: 1}], 2, &features);
+        assert_ne!(shifted.data, features.data);
+        for (row, original) in shifted.data.iter().zip(features.data.iter()) {
+            assert!(row.iter().all(|x| *x == 0.0 || original.contains(x)));
+            assert!(row.iter().filter(|&&x| x != 0.0).count() >= 9);
+        }
+    }
+
+    #[test]
+    fn test_random_crop() {
+        let shape = ImageShape {channels: 1, height: 3, width: 3};
+        let features = ramp(5, 9);
+        assert_eq!(augmented(vec![Transform::RandomCrop {shape, height: 3, width: 3}], 0, &features).0.data, features.data);
+
+        let (cropped, _) = augmented(vec![Transform::RandomCrop {shape, height: 1, width: 1}], 0, &features);
+        for (row, original) in cropped.data.iter().zip(features.data.iter()) {
+            assert!(row.iter().all(|&x| x == row[0]));
+            assert!(original.contains(&row[0]));
+        }
+    }
+
+    #[test]
+    fn test_training_with_augmentation() {
+        let mut features = Vec::new();
+        let mut labels = Vec::new();
+        for i in 0..20 {
+            let offset = if i % 2 == 0 { 1.0 } else { -1.0 };
+            features.push(vec![offset, offset]);
+            labels.push(i % 2);
+        }
+        let features = create_dataset(20, 2, features);
+        let classes = one_hot(&labels, 2);
+
+        let mut network = create_network(2, 1, vec![4], vec![Some(tanh)], 2, Some(softmax));
+        let mut params = create_parameter_set(features.clone(), classes.clone(), LossFunction::CrossEntropy, 10, 0.5, 0.0, 0.0, 0.0, 100, false, false);
+        params.config.augmentation = Some(create_augmentation(vec![Transform::GaussianNoise(0.2), Transform::Mixup(0.2)], 7).unwrap());
+        network.batch_gradient_descent(&mut params).unwrap();
+
+        assert!(!params.config.augmentation.as_ref().unwrap().is_training());
+        let accuracy = network.accuracy(std::rc::Rc::new(std::cell::RefCell::new(features)), std::rc::Rc::new(std::cell::RefCell::new(classes)));
+        assert_eq!(accuracy, 1.0);
+    }
+
+    #[test]
+    fn test_invalid_transforms_are_rejected() {
+        let shape = ImageShape {channels: 1, height: 3, width: 3};
+        for transform in [
+            Transform::GaussianNoise(-1.0),
+            Transform::GaussianNoise(f32::NAN),
+            Transform::Mixup(0.0),
+            Transform::Mixup(f32::INFINITY),
+            Transform::RandomCrop {shape, height: 4, width: 3},
+            Transform::RandomCrop {shape, height: 3, width: 0},
+        ] {
+            assert!(matches!(create_augmentation(vec![transform], 0), Err(Error::InvalidConfig(_))));
+        }
+    }
+
+    #[test]
+    fn test_image_shape_must_match_features() {
+        let features = ramp(10, 4);
+        let classes = one_hot(&(0..10).map(|i| i % 2).collect::<Vec<usize>>(), 2);
+        let shape = ImageShape {channels: 1, height: 3, width: 3};
+        let mut network = create_network(4, 0, vec![], vec![], 2, Some(softmax));
+        let mut params = create_parameter_set(features, classes, LossFunction::CrossEntropy, 5, 0.1, 0.0, 0.0, 0.0, 1, false, false);
+        params.config.augmentation = Some(create_augmentation(vec![Transform::HorizontalFlip {shape, probability: 1.0}], 0).unwrap());
+
+        assert!(matches!(network.batch_gradient_descent(&mut params), Err(Error::ShapeMismatch {expected: (1, 4), found: (1, 9)})));
+    }
+}
@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod autograd_tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use cranium_rs::matrix::*;
+    use cranium_rs::autograd::*;
+    use cranium_rs::error::Error;
+    use cranium_rs::function::*;
+
+    fn sample_matrix(rows: usize, cols: usize, seed: f32) -> Matrix {
+        let mut matrix = Matrix::create_zero_matrix(rows, cols);
+        for i in 0..rows {
+            for j in 0..cols {
+                matrix.set(i, j, ((i * cols + j) as f32 * 0.37 + seed).sin());
+            }
+        }
+        matrix
+    }
+
+    fn mlp_loss(x: &Matrix, w1: &Matrix, b1: &Matrix, w2: &Matrix, target: &Matrix) -> f32 {
+        let tape = create_tape();
+        let hidden = tape.var(x.copy()).multiply(&tape.var(w1.copy())).add_to_each_row(&tape.var(b1.copy())).tanh();
+        let output = hidden.multiply(&tape.var(w2.copy())).softmax();
+        output.cross_entropy(target).value().get(0, 0)
+    }
+
+    #[test]
+    fn test_elementwise_gradients() {
+        let tape = create_tape();
+        let a = tape.var(sample_matrix(2, 3, 0.1));
+        let b = tape.var(sample_matrix(3, 2, 0.9));
+        let loss = a.hadamard(&b.transpose()).scalar_multiply(3.0).add(&a).sum();
+        let grads = loss.backward();
+
+        let expected_a = b.value().transpose();
+        let ga = grads.get(&a);
+        let gb = grads.get(&b);
+        for i in 0..2 {
+            for j in 0..3 {
+                assert!((ga.get(i, j) - (3.0 * expected_a.get(i, j) + 1.0)).abs() < 1e-5);
+                assert!((gb.get(j, i) - 3.0 * a.value().get(i, j)).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unused_variable_gradient() {
+        let tape = create_tape();
+        let a = tape.var(sample_matrix(2, 2, 0.1));
+        let b = tape.var(sample_matrix(2, 2, 0.2));
+        let grads = a.sum().backward();
+        assert!(grads.get(&b).equals(&Matrix::create_zero_matrix(2, 2)));
+
+        let later = tape.var(sample_matrix(3, 1, 0.3));
+        assert!(grads.get(&later).equals(&Matrix::create_zero_matrix(3, 1)));
+    }
+
+    fn square(input: Rc<RefCell<Matrix>>) {
+        input.borrow_mut().transform(|x| x * x);
+    }
+
+    #[test]
+    fn test_custom_activation_needs_a_derivative() {
+        let tape = create_tape();
+        let a = tape.var(sample_matrix(2, 3, 0.4));
+        assert!(matches!(a.activate(square), Err(Error::InvalidConfig(_))));
+        assert!(a.activate(sigmoid).is_ok());
+
+        // For y = x^2 on positive inputs, dy/dx = 2x = 2 sqrt(y).
+        let mut inputs = sample_matrix(2, 3, 0.4);
+        inputs.transform(|x| x.abs() + 0.5);
+        let positive = tape.var(inputs);
+        let grads = positive.activate_with(square, |y| 2.0 * y.sqrt()).sum().backward();
+        let expected = positive.value();
+        let g = grads.get(&positive);
+        for i in 0..2 {
+            for j in 0..3 {
+                assert!((g.get(i, j) - 2.0 * expected.get(i, j)).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_softmax_cross_entropy_gradient() {
+        let tape = create_tape();
+        let logits = tape.var(sample_matrix(3, 4, 0.5));
+        let target = Matrix::create_matrix(3, 4, vec![vec![1.0, 0.0, 0.0, 0.0], vec![0.0, 0.0, 1.0, 0.0], vec![0.0, 0.0, 0.0, 1.0]]);
+        let probabilities = logits.softmax();
+        let grads = probabilities.cross_entropy(&target).backward();
+
+        let expected = probabilities.value();
+        let g = grads.get(&logits);
+        for i in 0..3 {
+            for j in 0..4 {
+                assert!((g.get(i, j) - (expected.get(i, j) - target.get(i, j)) / 3.0).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mlp_gradients_match_finite_differences() {
+        let x = sample_matrix(4, 3, 0.2);
+        let w1 = sample_matrix(3, 5, 1.1);
+        let b1 = sample_matrix(1, 5, 2.3);
+        let w2 = sample_matrix(5, 2, 3.7);
+        let target = Matrix::create_matrix(4, 2, vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![0.0, 1.0], vec![1.0, 0.0]]);
+
+        let tape = create_tape();
+        let w1_var = tape.var(w1.copy());
+        let b1_var = tape.var(b1.copy());
+        let hidden = tape.var(x.copy()).multiply(&w1_var).add_to_each_row(&b1_var).tanh();
+        let loss = hidden.multiply(&tape.var(w2.copy())).softmax().cross_entropy(&target);
+        let grads = loss.backward();
+
+        let epsilon = 1e-2;
+        let gw1 = grads.get(&w1_var);
+        for i in 0..3 {
+            for j in 0..5 {
+                let mut plus = w1.copy();
+                plus.set(i, j, w1.get(i, j) + epsilon);
+                let mut minus = w1.copy();
+                minus.set(i, j, w1.get(i, j) - epsilon);
+                let numeric = (mlp_loss(&x, &plus, &b1, &w2, &target) - mlp_loss(&x, &minus, &b1, &w2, &target)) / (2.0 * epsilon);
+                assert!((numeric - gw1.get(i, j)).abs() < 1e-2);
+            }
+        }
+        let gb1 = grads.get(&b1_var);
+        for j in 0..5 {
+            let mut plus = b1.copy();
+            plus.set(0, j, b1.get(0, j) + epsilon);
+            let mut minus = b1.copy();
+            minus.set(0, j, b1.get(0, j) - epsilon);
+            let numeric = (mlp_loss(&x, &w1, &plus, &w2, &target) - mlp_loss(&x, &w1, &minus, &w2, &target)) / (2.0 * epsilon);
+            assert!((numeric - gb1.get(0, j)).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn test_training_with_tape() {
+        let x = sample_matrix(6, 2, 0.4);
+        let mut target = Matrix::create_zero_matrix(6, 1);
+        for i in 0..6 {
+            target.set(i, 0, x.get(i, 0) - 0.5 * x.get(i, 1));
+        }
+        let mut w = Matrix::create_zero_matrix(2, 1);
+        let mut b = Matrix::create_zero_matrix(1, 1);
+
+        let mut losses = Vec::new();
+        for _ in 0..1000 {
+            let tape = create_tape();
+            let w_var = tape.var(w.copy());
+            let b_var = tape.var(b.copy());
+            let loss = tape.var(x.copy()).multiply(&w_var).add_to_each_row(&b_var).mean_squared_error(&target);
+            losses.push(loss.value().get(0, 0));
+            let grads = loss.backward();
+            let mut step = grads.get(&w_var);
+            step.scalar_multiply(-0.5);
+            step.add_to(&mut w);
+            let mut step = grads.get(&b_var);
+            step.scalar_multiply(-0.5);
+            step.add_to(&mut b);
+        }
+
+        assert!(losses[losses.len() - 1] < 1e-2 * losses[0]);
+    }
+}
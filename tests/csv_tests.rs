@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod csv_tests {
+    use cranium_rs::csv::*;
+    use cranium_rs::dataset::*;
+    use cranium_rs::error::Error;
+
+    const IRIS: &str = "sepal,petal,label\n5.1,1.4,0\n6.2,4.5,1\n5.9,,2\n";
+
+    #[test]
+    fn test_read_csv_with_header() {
+        let options = CsvOptions {has_header: true, missing: MissingValues::Fill(-1.0), ..CsvOptions::default()};
+        let dataset = DataSet::read_csv(IRIS.as_bytes(), &options).unwrap();
+
+        assert_eq!(dataset.rows, 3);
+        assert_eq!(dataset.cols, 3);
+        assert_eq!(dataset.data[2], vec![5.9, -1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_read_csv_delimiter_and_columns() {
+        let text = "1;2;3\n4;5;6\n";
+        let options = CsvOptions {delimiter: ';', columns: vec![Column::Index(2), Column::Index(0)], ..CsvOptions::default()};
+        let dataset = DataSet::read_csv(text.as_bytes(), &options).unwrap();
+
+        assert_eq!(dataset.cols, 2);
+        assert_eq!(dataset.data, vec![vec![3.0, 1.0], vec![6.0, 4.0]]);
+    }
+
+    #[test]
+    fn test_missing_value_policies() {
+        let text = "1,NA\n3,4\n5,6\n";
+        let mut options = CsvOptions::default();
+
+        let error = DataSet::read_csv(text.as_bytes(), &options).unwrap_err();
+        assert!(matches!(error, Error::Parse {row: 1, column: 2, ..}));
+
+        options.missing = MissingValues::SkipRow;
+        let dataset = DataSet::read_csv(text.as_bytes(), &options).unwrap();
+        assert_eq!(dataset.data, vec![vec![3.0, 4.0], vec![5.0, 6.0]]);
+
+        options.missing = MissingValues::ColumnMean;
+        let dataset = DataSet::read_csv(text.as_bytes(), &options).unwrap();
+        assert_eq!(dataset.data[0], vec![1.0, 5.0]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let options = CsvOptions {has_header: true, ..CsvOptions::default()};
+        let error = DataSet::read_csv("a,b\n1,2\n3,x\n".as_bytes(), &options).unwrap_err();
+        assert!(matches!(error, Error::Parse {row: 3, column: 2, ..}));
+
+        let error = DataSet::read_csv("a,b\n1,2\n3\n".as_bytes(), &options).unwrap_err();
+        assert!(matches!(error, Error::Parse {row: 3, ..}));
+
+        let options = CsvOptions {has_header: true, columns: vec![Column::Name("c".to_string())], ..CsvOptions::default()};
+        assert!(DataSet::read_csv("a,b\n1,2\n".as_bytes(), &options).is_err());
+    }
+
+    #[test]
+    fn test_quoted_fields_keep_their_delimiters() {
+        let text = "\"name, first\",\"say \"\"hi\"\"\",value\n1,2,\"3\"\n";
+        let options = CsvOptions {has_header: true, columns: vec![Column::Name("name, first".to_string()), Column::Name("say \"hi\"".to_string())], ..CsvOptions::default()};
+        let dataset = DataSet::read_csv(text.as_bytes(), &options).unwrap();
+        assert_eq!(dataset.data, vec![vec![1.0, 2.0]]);
+
+        let dataset = DataSet::read_csv("\"1,5\",2\n".as_bytes(), &CsvOptions::default());
+        assert!(matches!(dataset, Err(Error::Parse {row: 1, column: 1, ..})));
+    }
+
+    #[test]
+    fn test_empty_input_is_rejected() {
+        let options = CsvOptions {has_header: true, ..CsvOptions::default()};
+        assert!(matches!(DataSet::read_csv("".as_bytes(), &CsvOptions::default()), Err(Error::EmptyDataset)));
+        assert!(matches!(DataSet::read_csv("a,b\n\n".as_bytes(), &options), Err(Error::EmptyDataset)));
+        assert!(matches!(DataSet::read_csv_split("".as_bytes(), &options, &[]), Err(Error::EmptyDataset)));
+    }
+
+    #[test]
+    fn test_read_csv_split() {
+        let options = CsvOptions {has_header: true, missing: MissingValues::ColumnMean, ..CsvOptions::default()};
+        let (features, targets) = DataSet::read_csv_split(IRIS.as_bytes(), &options, &[Column::Name("label".to_string())]).unwrap();
+
+        assert_eq!(features.cols, 2);
+        assert_eq!(targets.cols, 1);
+        assert_eq!(features.data[2], vec![5.9, 2.95]);
+        assert_eq!(targets.data, vec![vec![0.0], vec![1.0], vec![2.0]]);
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let dataset = create_dataset(2, 3, vec![vec![1.5, -2.0, 0.0], vec![3.25, 4.0, 1e-3]]);
+        let path = std::env::temp_dir().join(format!("cranium_csv_round_trip_{}.csv", std::process::id()));
+        let header = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        dataset.to_csv(&path, '\t', Some(&header)).unwrap();
+
+        let options = CsvOptions {delimiter: '\t', has_header: true, ..CsvOptions::default()};
+        let loaded = DataSet::from_csv(&path, &options).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.data, dataset.data);
+        assert!(matches!(DataSet::from_csv(&path, &options), Err(Error::Io(_))));
+    }
+}
@@ -1,6 +1,17 @@
 #[cfg(test)]
 mod dataset_tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
     use cranium_rs::dataset::*;
+    use cranium_rs::error::Error;
+
+    // Features hold the row index; targets hold its negation.
+    fn indexed(rows: usize) -> (DataSet, DataSet) {
+        (
+            create_dataset(rows, 1, (0..rows).map(|i| vec![i as f32]).collect()),
+            create_dataset(rows, 1, (0..rows).map(|i| vec![-(i as f32)]).collect()),
+        )
+    }
 
     #[test]
     fn test_create_dataset() {
@@ -41,11 +52,22 @@ mod dataset_tests {
     }
 
     #[test]
+    fn test_split_rows_uses_offset() {
+        let (dataset, _) = indexed(10);
+        let batches = create_batches(&dataset, 3);
+        let split = split_rows(&batches[1]);
+        assert_eq!(split.iter().map(|row| row.row_idx).collect::<Vec<usize>>(), vec![4, 5, 6]);
+        assert_eq!(batches[2].rows(), &dataset.data[7..]);
+        assert_eq!(batches[2].to_dataset().rows, 3);
+    }
+
+    #[test]
+    #[allow(clippy::needless_range_loop)]
     fn test_shuffle_together() {
-        let rows = 3;
+        let rows = 20;
         let cols = 2;
-        let data_a = vec![vec![0.0, 3.0], vec![1.0, 4.0], vec![2.0, 5.0]];
-        let data_b = vec![vec![6.0, 7.0], vec![8.0, 9.0], vec![10.0, 11.0]];
+        let data_a: Vec<Vec<f32>> = (0..rows).map(|i| vec![i as f32, (i + rows) as f32]).collect();
+        let data_b: Vec<Vec<f32>> = (0..rows).map(|i| vec![(2 * i) as f32, (2 * i + 1) as f32]).collect();
 
         let mut dataset_a = create_dataset(rows, cols, data_a.clone());
         let mut dataset_b = create_dataset(rows, cols, data_b.clone());
@@ -76,4 +98,131 @@ mod dataset_tests {
         }
         assert_eq!(reconstruction, data_b);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_shuffle_together_is_a_permutation() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for rows in 1..30 {
+            let (mut a, mut b) = indexed(rows);
+            shuffle_together_with(&mut a, &mut b, &mut rng);
+            let mut seen: Vec<usize> = a.data.iter().map(|row| row[0] as usize).collect();
+            seen.sort();
+            assert_eq!(seen, (0..rows).collect::<Vec<usize>>());
+            assert!(a.data.iter().zip(b.data.iter()).all(|(x, y)| x[0] == -y[0]));
+        }
+    }
+
+    #[test]
+    fn test_batch_iter_in_order() {
+        let (features, targets) = indexed(7);
+        let batches: Vec<(DataSet, DataSet)> = create_batch_iter(&features, &targets, 3, false).collect();
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[0].0.data, vec![vec![0.0], vec![1.0], vec![2.0]]);
+        assert_eq!(batches[2].0.rows, 1);
+        assert_eq!(batches[2].1.data, vec![vec![-6.0]]);
+
+        assert_eq!(create_batch_iter(&features, &targets, 3, true).count(), 2);
+        assert_eq!(create_batch_iter(&features, &targets, 3, true).num_batches(), 2);
+        assert_eq!(num_batches(7, 3, false), 3);
+        assert_eq!(num_batches(6, 3, false), 2);
+    }
+
+    #[test]
+    fn test_batch_views_borrow_rows() {
+        let (features, targets) = indexed(5);
+        let mut iter = create_ordered_batch_iter(&features, &targets, 2, false, vec![4, 0, 3, 1, 2]);
+        let view = iter.next_view().unwrap();
+        assert_eq!(view.len(), 2);
+        assert_eq!(view.feature_row(0), &[4.0]);
+        assert_eq!(view.target_row(1), &[-0.0]);
+        assert_eq!(view.features().data, vec![vec![4.0], vec![0.0]]);
+        assert_eq!(iter.next_view().unwrap().indices, &[3, 1]);
+        assert_eq!(iter.next_view().unwrap().targets().data, vec![vec![-2.0]]);
+        assert!(iter.next_view().is_none());
+    }
+
+    #[test]
+    fn test_shuffled_epochs_differ() {
+        let (features, targets) = indexed(50);
+        let mut rng = StdRng::seed_from_u64(9);
+        let first = create_shuffled_batch_iter(&features, &targets, 10, false, &mut rng).order().to_vec();
+        let second = create_shuffled_batch_iter(&features, &targets, 10, false, &mut rng).order().to_vec();
+        assert_ne!(first, second);
+
+        let mut again = StdRng::seed_from_u64(9);
+        assert_eq!(create_shuffled_batch_iter(&features, &targets, 10, false, &mut again).order(), &first[..]);
+    }
+
+    // Property: across random sizes, every row appears in exactly one batch per
+    // epoch, features stay paired with their targets, and only the final batch
+    // may be short (and is dropped under drop_last).
+    #[test]
+    fn test_every_row_once_per_epoch() {
+        let mut rng = StdRng::seed_from_u64(2024);
+        for _ in 0..200 {
+            let rows = rng.gen_range(1..60);
+            let batch_size = rng.gen_range(1..=rows + 3);
+            let drop_last = rng.gen_bool(0.5);
+            let (features, targets) = indexed(rows);
+
+            let mut counts = vec![0; rows];
+            let mut batches = 0;
+            for (batch_features, batch_targets) in create_shuffled_batch_iter(&features, &targets, batch_size, drop_last, &mut rng) {
+                assert_eq!(batch_features.rows, batch_targets.rows);
+                assert!(batch_features.rows <= batch_size);
+                if drop_last {
+                    assert_eq!(batch_features.rows, batch_size);
+                }
+                for (f, t) in batch_features.data.iter().zip(batch_targets.data.iter()) {
+                    assert_eq!(f[0], -t[0]);
+                    counts[f[0] as usize] += 1;
+                }
+                batches += 1;
+            }
+
+            assert_eq!(batches, num_batches(rows, batch_size, drop_last));
+            let covered = counts.iter().filter(|&&c| c == 1).count();
+            assert!(counts.iter().all(|&c| c <= 1));
+            if drop_last {
+                assert_eq!(covered, rows - rows % batch_size);
+            } else {
+                assert_eq!(covered, rows);
+            }
+        }
+    }
+
+    #[test]
+    fn test_balanced_class_weights() {
+        let classes = one_hot(&[0, 0, 0, 1, 2, 2], 4);
+        assert_eq!(balanced_class_weights(&classes), vec![6.0 / 9.0, 2.0, 1.0, 0.0]);
+        assert_eq!(class_sample_weights(&classes, &[1.0, 2.0, 3.0, 4.0]), vec![1.0, 1.0, 1.0, 2.0, 3.0, 3.0]);
+    }
+
+    #[test]
+    fn test_weighted_sample() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let drawn = weighted_sample(&[0.0, 1.0, 3.0], 4000, &mut rng).unwrap();
+        assert!(drawn.iter().all(|&i| i != 0));
+        let threes = drawn.iter().filter(|&&i| i == 2).count() as f32 / 4000.0;
+        assert!((threes - 0.75).abs() < 0.03);
+
+        assert!(matches!(weighted_sample(&[0.0, 0.0], 4, &mut rng), Err(Error::InvalidConfig(_))));
+        assert!(matches!(weighted_sample(&[1.0, -1.0], 4, &mut rng), Err(Error::InvalidConfig(_))));
+        assert!(matches!(weighted_sample(&[1.0, f32::NAN], 4, &mut rng), Err(Error::InvalidConfig(_))));
+        assert!(matches!(weighted_sample(&[1.0, f32::INFINITY], 4, &mut rng), Err(Error::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_balanced_sample_equalises_classes() {
+        let labels: Vec<usize> = (0..100).map(|i| if i < 90 { 0 } else { 1 }).collect();
+        let classes = one_hot(&labels, 2);
+        let mut rng = StdRng::seed_from_u64(6);
+        let drawn = balanced_sample(&classes, 4000, &mut rng).unwrap();
+        let rare = drawn.iter().filter(|&&i| labels[i] == 1).count() as f32 / 4000.0;
+        assert!((rare - 0.5).abs() < 0.03);
+
+        let order = Sampler::Balanced.order(&classes, &mut rng).unwrap();
+        assert_eq!(order.len(), 100);
+        assert_eq!(Sampler::Sequential.order(&classes, &mut rng).unwrap(), (0..100).collect::<Vec<usize>>());
+    }
+}
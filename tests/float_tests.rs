@@ -0,0 +1,42 @@
+#[cfg(test)]
+mod float_tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use cranium_rs::float::Float;
+    use cranium_rs::function::*;
+    use cranium_rs::matrix::*;
+
+    #[test]
+    fn test_activations_by_type() {
+        assert_eq!(get_function_name(sigmoid), "sigmoid");
+        assert_eq!(get_function_name(double::softmax), "softmax");
+        assert_eq!(get_function_name::<f64>(double::relu), "relu");
+        assert!(std::ptr::fn_addr_eq(get_function_by_name::<f64>("tanh"), double::tanh as Activation<f64>));
+        assert!(std::ptr::fn_addr_eq(get_function_by_name::<f32>("tanh"), tanh as Activation));
+        assert!(std::ptr::fn_addr_eq(get_function_by_name::<f64>("unknown"), double::linear as Activation<f64>));
+        assert_eq!(f32::activations().len(), f64::activations().len());
+    }
+
+    #[test]
+    fn test_activations_agree_across_types() {
+        let single: Matrix = Matrix::create_matrix(1, 3, vec![vec![-1.5, 0.25, 2.0]]);
+        for name in ["sigmoid", "relu", "tanh", "softmax", "linear"] {
+            let a = Rc::new(RefCell::new(single.copy()));
+            let b = Rc::new(RefCell::new(single.cast::<f64>()));
+            get_function_by_name::<f32>(name)(a.clone());
+            get_function_by_name::<f64>(name)(b.clone());
+            for (x, y) in a.borrow().data[0].iter().zip(b.borrow().data[0].iter()) {
+                assert!((*x as f64 - y).abs() < 1e-6, "{} differs: {} vs {}", name, x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(<f64 as Float>::from_f32(0.5), 0.5);
+        assert_eq!(<f32 as Float>::from_usize(3), 3.0);
+        assert_eq!(Float::to_f64(0.1_f32), 0.1_f32 as f64);
+        assert!(!Float::is_finite(f64::NAN));
+        assert_eq!(<f64 as Float>::ONE + <f64 as Float>::ZERO, 1.0);
+    }
+}
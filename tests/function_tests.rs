@@ -0,0 +1,29 @@
+#[cfg(test)]
+mod function_tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use cranium_rs::function::*;
+    use cranium_rs::matrix::*;
+
+    #[test]
+    fn test_softmax_of_large_scores_is_finite() {
+        let input = Rc::new(RefCell::new(Matrix::create_matrix(1, 3, vec![vec![1000.0, 999.0, 0.0]])));
+        softmax(input.clone());
+        let output = input.borrow();
+
+        assert!(output.data[0].iter().all(|x| x.is_finite()));
+        assert!((output.data[0].iter().sum::<f32>() - 1.0).abs() < 1e-6);
+        assert!(output.get(0, 0) > output.get(0, 1));
+    }
+
+    #[test]
+    fn test_box_muller_is_standard_normal() {
+        let samples: Vec<f32> = (0..20000).map(|_| box_muller(0.0)).collect();
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        let variance = samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / samples.len() as f32;
+
+        assert!(samples.iter().all(|x| x.is_finite()));
+        assert!(mean.abs() < 0.05);
+        assert!((variance - 1.0).abs() < 0.1);
+    }
+}
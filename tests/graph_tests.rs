@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod graph_tests {
+    use cranium_rs::matrix::*;
+    use cranium_rs::function::*;
+    use cranium_rs::graph::*;
+
+    fn weighted_sum(output: &Matrix, weights: &Matrix) -> f32 {
+        output.hadamard(weights).data.iter().flatten().sum()
+    }
+
+    fn sample_matrix(rows: usize, cols: usize, seed: f32) -> Matrix {
+        let mut matrix = Matrix::create_zero_matrix(rows, cols);
+        for i in 0..rows {
+            for j in 0..cols {
+                matrix.set(i, j, ((i * cols + j) as f32 * 0.37 + seed).sin());
+            }
+        }
+        matrix
+    }
+
+    #[test]
+    fn test_build_shape_mismatch() {
+        let mut builder = create_graph_builder();
+        let input = builder.input(3);
+        let hidden = builder.dense(input, 4, Some(relu));
+        let sum = builder.merge(Merge::Add, vec![input, hidden]);
+        assert!(builder.build(sum).is_err());
+    }
+
+    #[test]
+    fn test_build_cycle() {
+        let mut builder = create_graph_builder();
+        let input = builder.input(3);
+        let first = builder.add_node(NodeSpec::Merge(Merge::Add), vec![input, 2]);
+        let second = builder.dense(first, 3, None);
+        assert!(builder.build(second).is_err());
+    }
+
+    #[test]
+    fn test_build_out_of_order() {
+        let mut builder = create_graph_builder();
+        let hidden = builder.add_node(NodeSpec::Dense(5, Some(tanh)), vec![1]);
+        let input = builder.input(2);
+        let concat = builder.merge(Merge::Concatenate, vec![input, hidden]);
+        let graph = builder.build(concat).unwrap();
+
+        assert_eq!(graph.nodes()[concat].size, 7);
+        assert_eq!(graph.input_nodes(), &[input]);
+    }
+
+    #[test]
+    fn test_skip_connection_forward() {
+        let mut builder = create_graph_builder();
+        let input = builder.input(3);
+        let hidden = builder.dense(input, 3, None);
+        let sum = builder.merge(Merge::Add, vec![input, hidden]);
+        let mut graph = builder.build(sum).unwrap();
+
+        let x = sample_matrix(2, 3, 0.1);
+        let output = graph.forward_pass(&[&x]);
+        let hidden_output = graph.nodes()[hidden].output.copy();
+        assert!(output.equals(&x.add(&hidden_output)));
+    }
+
+    #[test]
+    fn test_backward_pass() {
+        let mut builder = create_graph_builder();
+        let a = builder.input(3);
+        let b = builder.input(2);
+        let hidden_a = builder.dense(a, 4, Some(tanh));
+        let hidden_b = builder.dense(b, 4, Some(sigmoid));
+        let product = builder.merge(Merge::Multiply, vec![hidden_a, hidden_b]);
+        let concat = builder.merge(Merge::Concatenate, vec![product, a]);
+        let output = builder.dense(concat, 3, Some(softmax));
+        let mut graph = builder.build(output).unwrap();
+
+        let x_a = sample_matrix(2, 3, 0.2);
+        let x_b = sample_matrix(2, 2, 1.4);
+        let d_output = sample_matrix(2, 3, 2.5);
+        graph.forward_pass(&[&x_a, &x_b]);
+        let grads = graph.backward_pass(&d_output);
+
+        let epsilon = 1e-2;
+        for (k, x) in [&x_a, &x_b].iter().enumerate() {
+            for i in 0..x.rows {
+                for j in 0..x.cols {
+                    let mut plus = x.copy();
+                    plus.set(i, j, x.get(i, j) + epsilon);
+                    let mut minus = x.copy();
+                    minus.set(i, j, x.get(i, j) - epsilon);
+                    let (plus_inputs, minus_inputs) = if k == 0 {
+                        ([&plus, &x_b], [&minus, &x_b])
+                    } else {
+                        ([&x_a, &plus], [&x_a, &minus])
+                    };
+                    let loss_plus = weighted_sum(&graph.forward_pass(&plus_inputs), &d_output);
+                    let loss_minus = weighted_sum(&graph.forward_pass(&minus_inputs), &d_output);
+                    let numeric = (loss_plus - loss_minus) / (2.0 * epsilon);
+                    assert!((numeric - grads[k].get(i, j)).abs() < 2e-2 * (1.0 + numeric.abs()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_residual_training() {
+        let mut builder = create_graph_builder();
+        let input = builder.input(2);
+        let hidden = builder.dense(input, 8, Some(tanh));
+        let projected = builder.dense(hidden, 2, None);
+        let residual = builder.merge(Merge::Add, vec![input, projected]);
+        let output = builder.dense(residual, 1, None);
+        let mut graph = builder.build(output).unwrap();
+
+        let x = sample_matrix(8, 2, 0.3);
+        let mut y = Matrix::create_zero_matrix(8, 1);
+        for i in 0..8 {
+            y.set(i, 0, 2.0 * x.get(i, 0) - x.get(i, 1) + 0.5);
+        }
+        let mut losses = Vec::new();
+        for _ in 0..500 {
+            let prediction = graph.forward_pass(&[&x]);
+            let mut d_output = prediction.copy();
+            for i in 0..8 {
+                d_output.set(i, 0, prediction.get(i, 0) - y.get(i, 0));
+            }
+            losses.push(d_output.data.iter().map(|row| 0.5 * row[0] * row[0]).sum::<f32>());
+            graph.zero_gradients();
+            graph.backward_pass(&d_output);
+            graph.update(0.02);
+        }
+
+        assert!(losses[losses.len() - 1] < 0.1 * losses[0]);
+    }
+}
@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod idx_tests {
+    use std::fs::File;
+    use cranium_rs::dataset::*;
+    use cranium_rs::error::Error;
+    use cranium_rs::idx::*;
+
+    fn fixture(name: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    // A 1-dimensional IDX file of big-endian f32 values.
+    fn float_labels(name: &str, labels: &[f32]) -> std::path::PathBuf {
+        let mut bytes = vec![0, 0, 0x0D, 1];
+        bytes.extend_from_slice(&(labels.len() as u32).to_be_bytes());
+        for label in labels {
+            bytes.extend_from_slice(&label.to_be_bytes());
+        }
+        let path = std::env::temp_dir().join(format!("cranium_idx_{}_{}", std::process::id(), name));
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_read_idx_images() {
+        let images = DataSet::from_idx_images(fixture("images.idx3-ubyte"), false).unwrap();
+
+        assert_eq!(images.rows, 3);
+        assert_eq!(images.cols, 4);
+        assert_eq!(images.data[0], vec![0.0, 255.0, 128.0, 64.0]);
+        assert_eq!(images.data[2], vec![255.0, 255.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_read_idx_images_normalized() {
+        let images = DataSet::from_idx_images(fixture("images.idx3-ubyte"), true).unwrap();
+
+        assert_eq!(images.data[0][0], 0.0);
+        assert_eq!(images.data[0][1], 1.0);
+        assert!(images.data.iter().flatten().all(|&x| (0.0..=1.0).contains(&x)));
+    }
+
+    #[test]
+    fn test_read_idx_labels() {
+        let labels = DataSet::from_idx_labels(fixture("labels.idx1-ubyte"), None).unwrap();
+        assert_eq!(labels.cols, 3);
+        assert_eq!(labels.data, vec![vec![0.0, 0.0, 1.0], vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0]]);
+
+        let labels = DataSet::from_idx_labels(fixture("labels.idx1-ubyte"), Some(10)).unwrap();
+        assert_eq!(labels.cols, 10);
+        assert!(DataSet::from_idx_labels(fixture("labels.idx1-ubyte"), Some(2)).is_err());
+    }
+
+    #[test]
+    fn test_read_idx_floats() {
+        let array = read_idx(File::open(fixture("floats.idx2-float")).unwrap()).unwrap();
+
+        assert_eq!(array.dims, vec![2, 3]);
+        assert_eq!(array.data, vec![1.5, -2.0, 0.25, 3.0, 4.0, -0.5]);
+    }
+
+    #[test]
+    fn test_read_idx_errors() {
+        assert!(DataSet::from_idx_labels(fixture("truncated.idx1-ubyte"), None).is_err());
+        assert!(DataSet::from_idx_labels(fixture("images.idx3-ubyte"), None).is_err());
+        assert!(DataSet::from_idx_images(fixture("labels.idx1-ubyte"), false).is_err());
+        assert!(DataSet::from_idx_images(fixture("sample.libsvm"), false).is_err());
+    }
+
+    #[test]
+    fn test_read_idx_rejects_oversized_headers() {
+        // 4 dimensions of 2^32 - 1 overflow usize.
+        let mut overflow = vec![0, 0, 0x08, 4];
+        for _ in 0..4 {
+            overflow.extend_from_slice(&u32::MAX.to_be_bytes());
+        }
+        assert!(matches!(read_idx(overflow.as_slice()), Err(Error::Parse {..})));
+
+        // A header claiming far more data than follows must not allocate it.
+        let mut huge = vec![0, 0, 0x0E, 2];
+        huge.extend_from_slice(&u32::MAX.to_be_bytes());
+        huge.extend_from_slice(&1024u32.to_be_bytes());
+        huge.extend_from_slice(&[0; 16]);
+        assert!(matches!(read_idx(huge.as_slice()), Err(Error::Parse {..})));
+    }
+
+    #[test]
+    fn test_read_idx_labels_rejects_non_class_values() {
+        let negative = float_labels("negative", &[0.0, 1.0, -1.0]);
+        assert!(matches!(DataSet::from_idx_labels(&negative, None), Err(Error::Parse {row: 3, column: 1, ..})));
+        let fractional = float_labels("fractional", &[0.5, 1.0]);
+        assert!(matches!(DataSet::from_idx_labels(&fractional, None), Err(Error::Parse {row: 1, ..})));
+        let large = float_labels("large", &[0.0, 2.0]);
+        assert!(matches!(DataSet::from_idx_labels(&large, Some(2)), Err(Error::Parse {row: 2, ..})));
+        for path in [negative, fractional, large] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
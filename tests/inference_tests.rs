@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod inference_tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use cranium_rs::dataset::*;
+    use cranium_rs::error::Error;
+    use cranium_rs::function::*;
+    use cranium_rs::inference::*;
+    use cranium_rs::matrix::*;
+    use cranium_rs::network::*;
+
+    fn features() -> DataSet {
+        let rows: Vec<Vec<f32>> = (0..6).map(|i| vec![i as f32 * 0.3 - 1.0, (i as f32).sin(), 0.5]).collect();
+        create_dataset(6, 3, rows)
+    }
+
+    fn is_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_model_is_send_and_sync() {
+        is_send_sync::<InferenceModel>();
+        is_send_sync::<InferenceModel<f64>>();
+        is_send_sync::<Scratch>();
+    }
+
+    #[test]
+    fn test_predict_matches_forward_pass() {
+        let mut network = create_network(3, 2, vec![5, 4], vec![Some(relu), Some(sigmoid)], 2, Some(softmax));
+        let model = network.to_inference_model();
+        assert_eq!((model.num_inputs(), model.num_outputs()), (3, 2));
+
+        network.forward_pass(Rc::new(RefCell::new(features())));
+        let mut scratch = create_scratch(&model, 6);
+        let output = model.predict(&features(), &mut scratch).unwrap();
+        assert_eq!(output, &*network.get_output().borrow());
+    }
+
+    #[test]
+    fn test_predict_resizes_scratch() {
+        let network = create_network(3, 1, vec![4], vec![Some(tanh)], 1, None);
+        let model = network.to_inference_model();
+        let mut scratch = create_scratch(&model, 1);
+        let single = select_rows(&features(), &[2]);
+        let expected = model.predict(&single, &mut scratch).unwrap().clone();
+
+        let all = model.predict(&features(), &mut scratch).unwrap();
+        assert_eq!(all.rows, 6);
+        assert_eq!(all.data[2], expected.data[0]);
+    }
+
+    #[test]
+    fn test_concurrent_predictions_share_one_model() {
+        let network = create_network(3, 1, vec![8], vec![Some(relu)], 2, Some(softmax));
+        let model = Arc::new(network.to_inference_model());
+        let mut scratch = create_scratch(&model, 6);
+        let expected = model.predict(&features(), &mut scratch).unwrap().clone();
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                let model = Arc::clone(&model);
+                let expected = &expected;
+                scope.spawn(move || {
+                    let mut scratch = create_scratch(&model, 6);
+                    for _ in 0..10 {
+                        assert_eq!(model.predict(&features(), &mut scratch).unwrap(), expected);
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_predict_errors() {
+        let network = create_network(3, 0, vec![], vec![], 2, Some(softmax));
+        let model = network.to_inference_model();
+        let mut scratch = create_scratch(&model, 1);
+        let wide: Matrix = Matrix::create_zero_matrix(2, 4);
+        let empty: Matrix = Matrix {rows: 0, cols: 3, data: Vec::new()};
+
+        assert!(matches!(model.predict(&wide, &mut scratch), Err(Error::ShapeMismatch {..})));
+        assert!(matches!(model.predict(&empty, &mut scratch), Err(Error::EmptyDataset)));
+    }
+
+    #[test]
+    fn test_infer_leaves_network_state_alone() {
+        let mut network = create_network(3, 1, vec![4], vec![Some(tanh)], 2, Some(softmax));
+        let single = select_rows(&features(), &[0]);
+        network.forward_pass(Rc::new(RefCell::new(single)));
+        let before = network.get_output().borrow().clone();
+
+        let output = network.infer(&features()).unwrap();
+        assert_eq!((output.rows, output.cols), (6, 2));
+        assert_eq!(*network.get_output().borrow(), before);
+
+        network.forward_pass(Rc::new(RefCell::new(features())));
+        assert_eq!(output, *network.get_output().borrow());
+        assert_eq!(network.predict_classes(&features()).unwrap(), network.predict().iter().map(|&c| c as usize).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_chunked_inference_matches_infer() {
+        let network = create_network(3, 2, vec![5, 3], vec![Some(relu), Some(sigmoid)], 3, Some(softmax));
+        let expected = network.infer(&features()).unwrap();
+        for chunk_size in [1, 4, 6, 10] {
+            assert_eq!(network.infer_chunked(&features(), chunk_size).unwrap(), expected);
+            assert_eq!(network.predict_classes_chunked(&features(), chunk_size).unwrap(), class_indices(&expected));
+        }
+        assert_eq!(network.infer_rows(&features().data[2..4]).unwrap().data, expected.data[2..4]);
+    }
+
+    #[test]
+    fn test_predict_proba() {
+        let softmax_network = create_network(3, 0, vec![], vec![], 3, Some(softmax));
+        assert_eq!(softmax_network.predict_proba(&features()).unwrap(), softmax_network.infer(&features()).unwrap());
+
+        let linear_network = create_network(3, 0, vec![], vec![], 3, None);
+        let probabilities = linear_network.predict_proba(&features()).unwrap();
+        for (row, raw) in probabilities.data.iter().zip(linear_network.infer(&features()).unwrap().data.iter()) {
+            assert!((row.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+            assert_eq!(class_indices(&create_dataset(1, 3, vec![row.clone()])), class_indices(&create_dataset(1, 3, vec![raw.clone()])));
+        }
+    }
+
+    #[test]
+    fn test_inference_rejects_bad_input() {
+        let network = create_network(3, 0, vec![], vec![], 2, Some(softmax));
+        let wide: Matrix = Matrix::create_zero_matrix(2, 4);
+        let empty: Matrix = create_dataset(0, 3, Vec::new());
+        assert!(matches!(network.infer(&wide), Err(Error::ShapeMismatch {..})));
+        assert!(matches!(network.infer(&empty), Err(Error::EmptyDataset)));
+        assert!(matches!(network.infer_rows(&[]), Err(Error::EmptyDataset)));
+        assert!(matches!(network.infer_rows(&[vec![0.0; 3], vec![0.0; 2]]), Err(Error::ShapeMismatch {..})));
+        assert!(matches!(network.infer_chunked(&empty, 4), Err(Error::EmptyDataset)));
+        assert!(matches!(network.infer_chunked(&features(), 0), Err(Error::InvalidConfig(_))));
+        assert!(matches!(network.predict_classes(&wide), Err(Error::ShapeMismatch {..})));
+        assert!(matches!(network.predict_classes_chunked(&wide, 1), Err(Error::ShapeMismatch {..})));
+        assert!(matches!(network.predict_proba(&empty), Err(Error::EmptyDataset)));
+        assert!(network.infer(&features()).is_ok());
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod libsvm_tests {
+    use cranium_rs::dataset::*;
+    use cranium_rs::error::Error;
+    use cranium_rs::libsvm::*;
+
+    fn fixture(name: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    #[test]
+    fn test_from_libsvm() {
+        let (features, classes) = DataSet::from_libsvm(fixture("sample.libsvm"), None).unwrap();
+
+        assert_eq!(features.rows, 4);
+        assert_eq!(features.cols, 3);
+        assert_eq!(features.data[0], vec![0.5, 0.0, -1.25]);
+        assert_eq!(features.data[2], vec![1.0, 0.0, 4.0]);
+        assert_eq!(features.data[3], vec![0.0, 0.0, 0.0]);
+        assert_eq!(classes.data, vec![vec![0.0, 1.0], vec![1.0, 0.0], vec![0.0, 1.0], vec![1.0, 0.0]]);
+    }
+
+    #[test]
+    fn test_read_libsvm_num_features() {
+        let (features, labels) = read_libsvm("3 2:1.5\n1 1:2\n".as_bytes(), Some(5)).unwrap();
+
+        assert_eq!(features.cols, 5);
+        assert_eq!(features.data[0], vec![0.0, 1.5, 0.0, 0.0, 0.0]);
+        assert_eq!(labels, vec![3.0, 1.0]);
+        assert!(read_libsvm("1 6:1\n".as_bytes(), Some(5)).is_err());
+    }
+
+    #[test]
+    fn test_read_libsvm_errors() {
+        let error = read_libsvm("1 1:2\n1 0:3\n".as_bytes(), None).unwrap_err();
+        assert!(matches!(error, Error::Parse {row: 2, column: 2, ..}));
+
+        let error = read_libsvm("1 1:2 2-3\n".as_bytes(), None).unwrap_err();
+        assert!(matches!(error, Error::Parse {row: 1, column: 3, ..}));
+
+        let error = read_libsvm("yes 1:2\n".as_bytes(), None).unwrap_err();
+        assert!(matches!(error, Error::Parse {row: 1, column: 1, ..}));
+
+        let error = read_libsvm("1 1:2\nnan 1:3\n".as_bytes(), None).unwrap_err();
+        assert!(matches!(error, Error::Parse {row: 2, column: 1, ..}));
+        let error = read_libsvm("-inf 1:2\n".as_bytes(), None).unwrap_err();
+        assert!(matches!(error, Error::Parse {row: 1, column: 1, ..}));
+    }
+}
@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod linalg_tests {
+    use cranium_rs::error::Error;
+    use cranium_rs::matrix::*;
+
+    fn spd() -> Matrix<f64> {
+        Matrix::from_slice(3, 3, &[4.0, 12.0, -16.0, 12.0, 37.0, -43.0, -16.0, -43.0, 98.0])
+    }
+
+    fn diagonal(values: &[f64]) -> Matrix<f64> {
+        Matrix::from_fn(values.len(), values.len(), |i, j| if i == j { values[i] } else { 0.0 })
+    }
+
+    fn is_orthonormal(columns: &Matrix<f64>) -> bool {
+        columns.transpose().matmul(columns).approx_eq(&Matrix::identity(columns.cols), 1e-10)
+    }
+
+    #[test]
+    fn test_lu() {
+        let a: Matrix<f64> = Matrix::from_slice(3, 3, &[0.0, 2.0, 1.0, 1.0, 1.0, 1.0, 2.0, 1.0, 3.0]);
+        let lu = a.lu().unwrap();
+        let permuted = Matrix::from_fn(3, 3, |i, j| a.get(lu.permutation[i], j));
+        assert!(lu.lower.matmul(&lu.upper).approx_eq(&permuted, 1e-12));
+        assert!((0..3).all(|i| lu.lower.get(i, i) == 1.0 && (i + 1..3).all(|j| lu.lower.get(i, j) == 0.0)));
+        assert!((a.determinant().unwrap() - -3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_solve_and_inverse() {
+        let a: Matrix<f64> = Matrix::from_slice(2, 2, &[2.0, 1.0, 1.0, 3.0]);
+        let b: Matrix<f64> = Matrix::from_slice(2, 1, &[3.0, 5.0]);
+        let x = a.solve(&b).unwrap();
+        assert!(x.approx_eq(&Matrix::from_slice(2, 1, &[0.8, 1.4]), 1e-12));
+        assert!(a.matmul(&a.inverse().unwrap()).approx_eq(&Matrix::identity(2), 1e-12));
+
+        let single: Matrix = Matrix::from_slice(2, 2, &[4.0, 7.0, 2.0, 6.0]);
+        assert!(single.inverse().unwrap().approx_eq(&Matrix::from_slice(2, 2, &[0.6, -0.7, -0.2, 0.4]), 1e-5));
+    }
+
+    #[test]
+    fn test_singular() {
+        let a: Matrix<f64> = Matrix::from_slice(3, 3, &[1.0, 2.0, 3.0, 2.0, 4.0, 6.0, 1.0, 0.0, 1.0]);
+        assert!(matches!(a.inverse(), Err(Error::Singular)));
+        assert!(matches!(a.solve(&Matrix::ones(3, 1)), Err(Error::Singular)));
+        assert_eq!(a.determinant().unwrap(), 0.0);
+        assert!(matches!(Matrix::<f64>::zeros(2, 3).lu(), Err(Error::ShapeMismatch {expected: (2, 2), found: (2, 3)})));
+        assert_eq!(Error::Singular.to_string(), "Matrix is singular");
+    }
+
+    #[test]
+    fn test_qr() {
+        for a in [spd(), Matrix::from_fn(4, 2, |i, j| (i * 3 + j * j) as f64 + 0.5), Matrix::from_fn(2, 4, |i, j| (i + 2 * j) as f64 - 1.0)] {
+            let qr = a.qr().unwrap();
+            let k = a.rows.min(a.cols);
+            assert_eq!((qr.q.rows, qr.q.cols, qr.r.rows, qr.r.cols), (a.rows, k, k, a.cols));
+            assert!(qr.q.matmul(&qr.r).approx_eq(&a, 1e-10));
+            assert!(is_orthonormal(&qr.q));
+            assert!((0..k).all(|i| (0..i).all(|j| qr.r.get(i, j) == 0.0)));
+        }
+    }
+
+    #[test]
+    fn test_cholesky() {
+        let l = spd().cholesky().unwrap();
+        assert!(l.approx_eq(&Matrix::from_slice(3, 3, &[2.0, 0.0, 0.0, 6.0, 1.0, 0.0, -8.0, 5.0, 3.0]), 1e-12));
+        assert!(l.matmul(&l.transpose()).approx_eq(&spd(), 1e-12));
+
+        let indefinite: Matrix<f64> = Matrix::from_slice(2, 2, &[1.0, 2.0, 2.0, 1.0]);
+        assert!(matches!(indefinite.cholesky(), Err(Error::NotPositiveDefinite)));
+    }
+
+    #[test]
+    fn test_symmetric_eigen() {
+        let a: Matrix<f64> = Matrix::from_slice(3, 3, &[2.0, 1.0, 0.0, 1.0, 2.0, 0.0, 0.0, 0.0, 5.0]);
+        let eigen = a.symmetric_eigen().unwrap();
+        for (value, expected) in eigen.values.iter().zip([5.0, 3.0, 1.0]) {
+            assert!((value - expected).abs() < 1e-12);
+        }
+        assert!(is_orthonormal(&eigen.vectors));
+        let rebuilt = eigen.vectors.matmul(&diagonal(&eigen.values)).matmul(&eigen.vectors.transpose());
+        assert!(rebuilt.approx_eq(&a, 1e-12));
+
+        let eigen = spd().symmetric_eigen().unwrap();
+        for (i, &value) in eigen.values.iter().enumerate() {
+            let vector = Matrix::from_fn(3, 1, |r, _| eigen.vectors.get(r, i));
+            assert!(spd().matmul(&vector).approx_eq(&(&vector * value), 1e-9));
+        }
+    }
+
+    #[test]
+    fn test_svd() {
+        for a in [spd(), Matrix::from_fn(5, 3, |i, j| ((i * 7 + j * 3) % 5) as f64 - 2.0), Matrix::from_fn(2, 3, |i, j| (i + j) as f64)] {
+            let svd = a.svd().unwrap();
+            let k = a.rows.min(a.cols);
+            assert_eq!((svd.u.rows, svd.u.cols, svd.v.rows, svd.v.cols), (a.rows, k, a.cols, k));
+            assert!(svd.singular_values.windows(2).all(|w| w[0] >= w[1]));
+            let rebuilt = svd.u.matmul(&diagonal(&svd.singular_values)).matmul(&svd.v.transpose());
+            assert!(rebuilt.approx_eq(&a, 1e-10));
+            assert!(is_orthonormal(&svd.v));
+        }
+
+        let rank_one: Matrix<f64> = Matrix::from_slice(2, 2, &[1.0, 2.0, 2.0, 4.0]);
+        let svd = rank_one.svd().unwrap();
+        assert!((svd.singular_values[0] - 5.0).abs() < 1e-12);
+        assert!(svd.singular_values[1].abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_qr_and_svd_reject_empty_matrices() {
+        for (rows, cols) in [(0, 3), (3, 0), (0, 0)] {
+            let empty: Matrix<f64> = Matrix {rows, cols, data: vec![Vec::new(); rows]};
+            assert!(matches!(empty.qr(), Err(Error::EmptyDataset)));
+            assert!(matches!(empty.svd(), Err(Error::EmptyDataset)));
+        }
+
+        let row: Matrix<f64> = Matrix::from_slice(1, 3, &[3.0, 0.0, 4.0]);
+        let qr = row.qr().unwrap();
+        assert!(qr.q.matmul(&qr.r).approx_eq(&row, 1e-12));
+        assert!((row.svd().unwrap().singular_values[0] - 5.0).abs() < 1e-12);
+    }
+}
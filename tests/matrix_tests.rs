@@ -1,5 +1,6 @@
 #[cfg(test)]
 mod matrix_tests {
+    use cranium_rs::error::Error;
     use cranium_rs::matrix::*;
 
     #[test]
@@ -319,6 +320,7 @@ mod matrix_tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_equals() {
         let rows = 3;
         let cols = 2;
@@ -334,5 +336,155 @@ mod matrix_tests {
         assert_eq!(matrix2.equals(&matrix3), false);
 
     }
-    
+
+    #[test]
+    fn test_try_create_matrix() {
+        assert!(matches!(Matrix::<f32>::try_create_matrix(0, 2, vec![]), Err(Error::EmptyDataset)));
+        assert!(matches!(Matrix::<f32>::try_create_zero_matrix(2, 0), Err(Error::EmptyDataset)));
+        assert!(matches!(
+            Matrix::try_create_matrix(2, 2, vec![vec![1.0, 2.0], vec![3.0]]),
+            Err(Error::ShapeMismatch {expected: (2, 2), found: (2, 1)})));
+        assert!(matches!(Matrix::try_create_matrix(2, 1, vec![vec![1.0]]), Err(Error::ShapeMismatch {found: (1, 1), ..})));
+        assert_eq!(Matrix::try_create_matrix(1, 2, vec![vec![1.0, 2.0]]).unwrap().data, vec![vec![1.0, 2.0]]);
+    }
+
+    #[test]
+    fn test_try_operations_report_shapes() {
+        let a = Matrix::create_matrix(2, 3, vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
+        let b = Matrix::create_zero_matrix(2, 2);
+        assert!(matches!(a.try_multiply(&b), Err(Error::ShapeMismatch {expected: (3, 2), found: (2, 2)})));
+        assert!(matches!(a.try_add(&b), Err(Error::ShapeMismatch {expected: (2, 3), found: (2, 2)})));
+        assert!(matches!(a.try_hadamard(&b), Err(Error::ShapeMismatch { .. })));
+        assert!(matches!(a.try_add_to_each_row(&a), Err(Error::ShapeMismatch {expected: (1, 3), ..})));
+        assert!(a.try_copy_into(&mut Matrix::create_zero_matrix(3, 2)).is_err());
+        assert!(a.try_transpose_into(&mut Matrix::create_zero_matrix(3, 2)).is_ok());
+
+        let mut into = Matrix::create_zero_matrix(2, 2);
+        assert!(a.try_multiply_into(&a.transpose(), &mut into).is_ok());
+        assert_eq!(into.data, a.multiply(&a.transpose()).data);
+        assert_eq!(a.try_add(&a).unwrap().data, a.add(&a).data);
+
+        let message = a.try_add(&b).unwrap_err().to_string();
+        assert_eq!(message, "Shape mismatch: expected 2x3, found 2x2");
+    }
+
+    #[test]
+    fn test_f64_matrix() {
+        let a: Matrix<f64> = Matrix::create_matrix(1, 2, vec![vec![1.0 + 1e-12, 1.0]]);
+        let b: Matrix<f64> = Matrix::create_matrix(2, 1, vec![vec![1.0], vec![-1.0]]);
+        let product = a.multiply(&b);
+        assert!(product.get(0, 0) > 0.0);
+        assert_eq!(a.cast::<f32>().multiply(&b.cast()).get(0, 0), 0.0);
+    }
+
+    #[test]
+    fn test_cast() {
+        let a: Matrix = Matrix::create_matrix(2, 2, vec![vec![0.1, -2.5], vec![3.0, 1e-8]]);
+        let wide = a.cast::<f64>();
+        assert_eq!(wide.get(0, 0), 0.1_f32 as f64);
+        assert_eq!((wide.rows, wide.cols), (2, 2));
+        assert_eq!(wide.cast::<f32>().data, a.data);
+    }
+
+    #[test]
+    fn test_operators() {
+        let a: Matrix = Matrix::from_slice(2, 2, &[1.0, 2.0, 3.0, 4.0]);
+        let b: Matrix = Matrix::ones(2, 2);
+        assert_eq!((&a + &b).data, vec![vec![2.0, 3.0], vec![4.0, 5.0]]);
+        assert_eq!((&a - &b).data, vec![vec![0.0, 1.0], vec![2.0, 3.0]]);
+        assert_eq!((&a * 2.0).data, vec![vec![2.0, 4.0], vec![6.0, 8.0]]);
+        assert_eq!(2.0 * &a, &a * 2.0);
+        assert_eq!((-&a).data, vec![vec![-1.0, -2.0], vec![-3.0, -4.0]]);
+        assert_eq!(a.clone() + b.clone() - b.clone(), a);
+
+        let mut c = a.clone();
+        c += &b;
+        c -= &a;
+        c *= 3.0;
+        assert_eq!(c, &b * 3.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_operator_shape_mismatch() {
+        let _ = &Matrix::<f32>::zeros(2, 2) + &Matrix::zeros(2, 3);
+    }
+
+    #[test]
+    fn test_index() {
+        let mut a: Matrix = Matrix::zeros(2, 3);
+        a[(1, 2)] = 5.0;
+        assert_eq!(a[(1, 2)], 5.0);
+        assert_eq!(a.get(1, 2), 5.0);
+    }
+
+    #[test]
+    fn test_constructors() {
+        let identity: Matrix = Matrix::identity(3);
+        let a: Matrix = Matrix::from_fn(3, 2, |i, j| (i * 2 + j) as f32);
+        assert_eq!(a.data, vec![vec![0.0, 1.0], vec![2.0, 3.0], vec![4.0, 5.0]]);
+        assert_eq!(identity.matmul(&a), a);
+        assert_eq!(Matrix::<f64>::ones(1, 2).data, vec![vec![1.0, 1.0]]);
+
+        let random: Matrix = Matrix::random(4, 5);
+        assert_eq!((random.rows, random.cols), (4, 5));
+        assert!(random.data.iter().flatten().all(|&x| (0.0..1.0).contains(&x)));
+    }
+
+    #[test]
+    fn test_dot_and_approx_eq() {
+        let a: Matrix = Matrix::from_slice(1, 3, &[1.0, 2.0, 3.0]);
+        assert_eq!(a.dot(&a), 14.0);
+        let b = &a + &Matrix::from_slice(1, 3, &[1e-4, -1e-4, 0.0]);
+        assert!(a.approx_eq(&b, 1e-3));
+        assert!(!a.approx_eq(&b, 1e-5));
+        assert!(!a.approx_eq(&a.transpose(), 1.0));
+    }
+
+    #[test]
+    fn test_display() {
+        let a: Matrix = Matrix::from_slice(2, 2, &[1.0, -2.5, 10.0, 0.25]);
+        assert_eq!(format!("{:.1}", a), "[[ 1.0, -2.5],\n [10.0,  0.2]]");
+        assert_eq!(format!("{}", Matrix::<f64>::identity(1)), "[[1.0000]]");
+    }
+
+    #[test]
+    fn test_broadcasting() {
+        let a: Matrix = Matrix::from_slice(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let row: Matrix = Matrix::from_slice(1, 3, &[1.0, 2.0, 3.0]);
+        let col: Matrix = Matrix::from_slice(2, 1, &[10.0, 20.0]);
+        assert_eq!(a.broadcast_add(&row).data, vec![vec![2.0, 4.0, 6.0], vec![5.0, 7.0, 9.0]]);
+        assert_eq!(a.broadcast_sub(&col).data, vec![vec![-9.0, -8.0, -7.0], vec![-16.0, -15.0, -14.0]]);
+        assert_eq!(a.broadcast_mul(&col).data, vec![vec![10.0, 20.0, 30.0], vec![80.0, 100.0, 120.0]]);
+        assert_eq!(a.broadcast_div(&row).data, vec![vec![1.0, 1.0, 1.0], vec![4.0, 2.5, 2.0]]);
+        assert_eq!(a.broadcast_mul(&Matrix::from_slice(1, 1, &[2.0])), &a * 2.0);
+        assert_eq!(a.broadcast_add(&a), &a + &a);
+        assert_eq!(a.broadcast_add(&row), a.add_to_each_row(&row));
+        assert!(matches!(a.try_broadcast_add(&a.transpose()), Err(Error::ShapeMismatch {expected: (2, 3), found: (3, 2)})));
+    }
+
+    #[test]
+    fn test_reductions() {
+        let a: Matrix = Matrix::from_slice(2, 3, &[1.0, 5.0, 3.0, 4.0, 2.0, 6.0]);
+        assert_eq!(a.sum(Axis::Rows).data, vec![vec![5.0, 7.0, 9.0]]);
+        assert_eq!(a.sum(Axis::Cols).data, vec![vec![9.0], vec![12.0]]);
+        assert_eq!(a.mean(Axis::Cols).data, vec![vec![3.0], vec![4.0]]);
+        assert_eq!(a.max(Axis::Rows).data, vec![vec![4.0, 5.0, 6.0]]);
+        assert_eq!(a.min(Axis::Cols).data, vec![vec![1.0], vec![2.0]]);
+        assert_eq!(a.argmax(Axis::Rows), vec![1, 0, 1]);
+        assert_eq!(a.argmax(Axis::Cols), vec![1, 2]);
+        assert_eq!(a.variance(Axis::Rows).data, vec![vec![2.25, 2.25, 2.25]]);
+        assert_eq!(Matrix::<f32>::from_slice(1, 2, &[3.0, 4.0]).norm(Axis::Cols).data, vec![vec![5.0]]);
+    }
+
+    #[test]
+    fn test_standardise_columns() {
+        // Standardising every column, as a normalisation layer would.
+        let a: Matrix<f64> = Matrix::from_slice(3, 2, &[1.0, 10.0, 2.0, 20.0, 3.0, 30.0]);
+        let mut std_dev = a.variance(Axis::Rows);
+        std_dev.transform(f64::sqrt);
+        let scaled = a.broadcast_sub(&a.mean(Axis::Rows)).broadcast_div(&std_dev);
+        assert!(scaled.mean(Axis::Rows).approx_eq(&Matrix::zeros(1, 2), 1e-12));
+        assert!(scaled.variance(Axis::Rows).approx_eq(&Matrix::ones(1, 2), 1e-12));
+    }
 }
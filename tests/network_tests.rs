@@ -0,0 +1,440 @@
+#[cfg(test)]
+mod network_tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use cranium_rs::dataset::*;
+    use cranium_rs::error::Error;
+    use cranium_rs::function::*;
+    use cranium_rs::matrix::*;
+    use cranium_rs::network::*;
+    use cranium_rs::workspace::*;
+
+    fn blobs() -> (DataSet, DataSet) {
+        let mut features = Vec::new();
+        let mut classes = Vec::new();
+        for i in 0..20 {
+            let offset = if i % 2 == 0 { 1.0 } else { -1.0 };
+            let jitter = (i as f32 * 0.7).sin() * 0.3;
+            features.push(vec![offset + jitter, offset - jitter]);
+            classes.push(if i % 2 == 0 { vec![1.0, 0.0] } else { vec![0.0, 1.0] });
+        }
+        (create_dataset(20, 2, features), create_dataset(20, 2, classes))
+    }
+
+    fn regression() -> (DataSet, DataSet) {
+        let features: Vec<Vec<f32>> = (0..10).map(|i| vec![i as f32, (i * i) as f32]).collect();
+        let targets: Vec<Vec<f32>> = (0..10).map(|i| vec![3.0 * i as f32]).collect();
+        (create_dataset(10, 2, features), create_dataset(10, 1, targets))
+    }
+
+    #[test]
+    fn test_training_learns_blobs() {
+        let (features, classes) = blobs();
+        let mut network = create_network(2, 1, vec![4], vec![Some(tanh)], 2, Some(softmax));
+        let mut params = create_parameter_set(features.clone(), classes.clone(), LossFunction::CrossEntropy, 20, 0.5, 0.0, 0.0, 0.0, 100, false, false);
+        network.batch_gradient_descent(&mut params).unwrap();
+
+        let accuracy = network.accuracy(Rc::new(RefCell::new(features)), Rc::new(RefCell::new(classes)));
+        assert_eq!(accuracy, 1.0);
+    }
+
+    #[test]
+    fn test_clip_by_value() {
+        let mut weights = vec![Matrix::create_matrix(1, 3, vec![vec![-5.0, 0.5, 5.0]])];
+        let mut biases = vec![Matrix::create_matrix(1, 1, vec![vec![2.0]])];
+        GradientClipping::Value(1.0).apply(&mut weights, &mut biases);
+
+        assert_eq!(weights[0].data, vec![vec![-1.0, 0.5, 1.0]]);
+        assert_eq!(biases[0].data, vec![vec![1.0]]);
+    }
+
+    #[test]
+    fn test_clip_by_global_norm() {
+        let mut weights: Vec<Matrix> = vec![Matrix::create_matrix(1, 2, vec![vec![3.0, 0.0]])];
+        let mut biases: Vec<Matrix> = vec![Matrix::create_matrix(1, 1, vec![vec![4.0]])];
+        assert_eq!(global_norm(&weights, &biases), 5.0);
+
+        GradientClipping::GlobalNorm(1.0).apply(&mut weights, &mut biases);
+        assert!((global_norm(&weights, &biases) - 1.0).abs() < 1e-6);
+        assert!((weights[0].get(0, 0) - 0.6).abs() < 1e-6);
+
+        GradientClipping::GlobalNorm(10.0).apply(&mut weights, &mut biases);
+        assert!((global_norm(&weights, &biases) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_divergence_halts() {
+        let (features, targets) = regression();
+        let mut network = create_network(2, 0, vec![], vec![], 1, None);
+        let mut params = create_parameter_set(features, targets, LossFunction::MeanSquaredError, 10, 1e6, 0.0, 0.0, 0.0, 50, false, false);
+        let result = network.batch_gradient_descent(&mut params);
+
+        assert!(matches!(result, Err(Error::Diverged { .. })));
+    }
+
+    #[test]
+    fn test_divergence_rollback() {
+        let (features, targets) = regression();
+        let mut network = create_network(2, 0, vec![], vec![], 1, None);
+        let mut params = create_parameter_set(features.clone(), targets, LossFunction::MeanSquaredError, 10, 1e6, 0.0, 0.0, 0.0, 200, false, false);
+        params.config.divergence = DivergencePolicy::Rollback;
+        network.batch_gradient_descent(&mut params).unwrap();
+
+        network.forward_pass(Rc::new(RefCell::new(features.clone())));
+        assert!(network.get_output().borrow().data.iter().flatten().all(|x| x.is_finite()));
+
+        // A finite step, then a batch whose loss overflows: the step before
+        // the divergence is undone and the learning rate halved.
+        let mut config = create_training_config(LossFunction::MeanSquaredError, 10, 0.001, 0.0, 0.0, 0.0, 1, false, false);
+        config.divergence = DivergencePolicy::Rollback;
+        let mut workspace = create_workspace(&network);
+        let before = (network.weights(0).copy(), network.bias(0).copy());
+        let targets = regression().1;
+        network.train_step(&mut workspace, &features, &targets, None, &mut config).unwrap();
+        assert_ne!(network.weights(0).data, before.0.data);
+
+        let mut huge = features.clone();
+        huge.transform(|x| x * 1e30);
+        let loss = network.train_step(&mut workspace, &huge, &targets, None, &mut config).unwrap();
+        assert!(!loss.is_finite());
+        assert_eq!(network.weights(0).data, before.0.data);
+        assert_eq!(network.bias(0).data, before.1.data);
+        assert_eq!(workspace.learning_rate_scale(), 0.5);
+    }
+
+    #[test]
+    fn test_clipping_prevents_divergence() {
+        let (features, targets) = regression();
+        let mut network = create_network(2, 0, vec![], vec![], 1, None);
+        let mut params = create_parameter_set(features, targets, LossFunction::MeanSquaredError, 10, 0.01, 0.0, 0.0, 0.0, 50, false, false);
+        params.config.clipping = Some(GradientClipping::GlobalNorm(1.0));
+
+        assert!(network.batch_gradient_descent(&mut params).is_ok());
+    }
+
+    fn output_range(network: &mut Network, features: DataSet) -> f32 {
+        network.forward_pass(Rc::new(RefCell::new(features)));
+        let output = network.get_output();
+        let values: Vec<f32> = output.borrow().data.iter().flatten().cloned().collect();
+        values.iter().cloned().fold(f32::MIN, f32::max) - values.iter().cloned().fold(f32::MAX, f32::min)
+    }
+
+    #[test]
+    fn test_regularization_gradient() {
+        let weights = Matrix::create_matrix(1, 3, vec![vec![-2.0, 0.0, 4.0]]);
+
+        assert_eq!(Regularization::None.gradient(&weights).data, vec![vec![0.0, 0.0, 0.0]]);
+        assert_eq!(Regularization::L1(0.5).gradient(&weights).data, vec![vec![-0.5, 0.0, 0.5]]);
+        assert_eq!(Regularization::L2(0.5).gradient(&weights).data, vec![vec![-1.0, 0.0, 2.0]]);
+        assert_eq!(Regularization::ElasticNet(0.5, 0.5).gradient(&weights).data, vec![vec![-1.5, 0.0, 2.5]]);
+        assert_eq!(Regularization::L1(0.5).penalty(&weights), 3.0);
+        assert_eq!(Regularization::L2(0.5).penalty(&weights), 5.0);
+    }
+
+    #[test]
+    fn test_regularization_for() {
+        let (features, targets) = regression();
+        let mut params = create_parameter_set(features, targets, LossFunction::MeanSquaredError, 10, 0.1, 0.0, 0.01, 0.0, 1, false, false);
+        params.config.layer_regularization = vec![Regularization::L1(0.1)];
+
+        assert_eq!(params.config.regularization_for(0), Regularization::L1(0.1));
+        assert_eq!(params.config.regularization_for(1), Regularization::L2(0.01));
+        params.config.regularization = 0.0;
+        assert_eq!(params.config.regularization_for(1), Regularization::None);
+    }
+
+    #[test]
+    fn test_loss_penalty_follows_layer_regularization() {
+        let (features, targets) = regression();
+        let network = create_network(features.cols, 1, vec![3], vec![Some(tanh)], targets.cols, None);
+        let mut config = create_training_config(LossFunction::MeanSquaredError, 10, 0.1, 0.0, 0.01, 0.0, 1, false, false);
+        config.layer_regularization = vec![Regularization::L1(0.1), Regularization::None];
+        let prediction: Matrix = Matrix::create_zero_matrix(1, network.layer_sizes()[2]);
+        let actual = Rc::new(RefCell::new(prediction.copy()));
+
+        let expected = Regularization::L1(0.1).penalty(network.weights(0));
+        assert_eq!(network.regularization_penalty(&config), expected);
+        assert_eq!(network.mean_squared_error_weighted(&prediction, actual.clone(), &config), expected);
+        assert_eq!(network.mean_squared_error(&prediction, actual.clone(), 0.0), 0.0);
+
+        config.regularize_bias = true;
+        let with_bias = expected + Regularization::L1(0.1).penalty(network.bias(0));
+        assert_eq!(network.cross_entropy_loss_weighted(&prediction, actual, &config), with_bias);
+    }
+
+    #[test]
+    fn test_weighted_loss_scales_rows_by_class_weight() {
+        let network = create_network(2, 0, vec![], vec![], 2, Some(softmax));
+        let mut config = create_training_config(LossFunction::MeanSquaredError, 1, 0.1, 0.0, 0.0, 0.0, 1, false, false);
+        config.class_weights = Some(vec![1.0, 3.0]);
+        let prediction: Matrix = Matrix::create_matrix(2, 2, vec![vec![0.0, 0.0], vec![0.0, 0.0]]);
+        let actual: Matrix = Matrix::create_matrix(2, 2, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+        let actual = Rc::new(RefCell::new(actual));
+
+        assert_eq!(network.mean_squared_error(&prediction, actual.clone(), 0.0), 0.5);
+        assert_eq!(network.mean_squared_error_weighted(&prediction, actual, &config), 1.0);
+    }
+
+    #[test]
+    fn test_apply_max_norm() {
+        let mut weights: Matrix = Matrix::create_matrix(2, 2, vec![vec![3.0, 0.1], vec![4.0, 0.2]]);
+        apply_max_norm(&mut weights, 1.0);
+
+        assert!((weights.get(0, 0) - 0.6).abs() < 1e-6);
+        assert!((weights.get(1, 0) - 0.8).abs() < 1e-6);
+        assert_eq!(weights.get(0, 1), 0.1);
+        assert_eq!(weights.get(1, 1), 0.2);
+    }
+
+    #[test]
+    fn test_weight_decay_shrinks_weights() {
+        let features = create_dataset(10, 1, (0..10).map(|i| vec![i as f32 / 10.0]).collect());
+        let targets = create_dataset(10, 1, (0..10).map(|i| vec![3.0 * i as f32 / 10.0]).collect());
+
+        let mut plain = create_network(1, 0, vec![], vec![], 1, None);
+        let mut params = create_parameter_set(features.clone(), targets.clone(), LossFunction::MeanSquaredError, 10, 0.1, 0.0, 0.0, 0.0, 500, false, false);
+        plain.batch_gradient_descent(&mut params).unwrap();
+
+        let mut decayed = create_network(1, 0, vec![], vec![], 1, None);
+        let mut params = create_parameter_set(features.clone(), targets, LossFunction::MeanSquaredError, 10, 0.1, 0.0, 0.0, 0.0, 500, false, false);
+        params.config.layer_regularization = vec![Regularization::L2(5.0)];
+        decayed.batch_gradient_descent(&mut params).unwrap();
+
+        assert!(output_range(&mut plain, features.clone()) > 2.5);
+        assert!(output_range(&mut decayed, features) < 0.5);
+    }
+
+    #[test]
+    fn test_max_norm_constraint() {
+        let features = create_dataset(10, 1, (0..10).map(|i| vec![i as f32 / 10.0]).collect());
+        let targets = create_dataset(10, 1, (0..10).map(|i| vec![3.0 * i as f32 / 10.0]).collect());
+        let mut network = create_network(1, 0, vec![], vec![], 1, None);
+        let mut params = create_parameter_set(features.clone(), targets, LossFunction::MeanSquaredError, 10, 0.1, 0.0, 0.0, 0.0, 500, false, false);
+        params.config.max_norm = Some(1.0);
+        network.batch_gradient_descent(&mut params).unwrap();
+
+        assert!(output_range(&mut network, features) <= 0.9 + 1e-5);
+    }
+
+    // Nine rows of class 0 and one of class 1, all with the same input, so
+    // an unweighted least-squares fit predicts the class frequencies.
+    fn imbalanced() -> (DataSet, DataSet) {
+        let features = create_dataset(10, 1, vec![vec![1.0]; 10]);
+        let labels: Vec<usize> = (0..10).map(|i| if i == 9 { 1 } else { 0 }).collect();
+        (features, one_hot(&labels, 2))
+    }
+
+    fn fit_imbalanced(configure: impl FnOnce(&mut ParameterSet)) -> Vec<f32> {
+        let (features, classes) = imbalanced();
+        let mut network = create_network(1, 0, vec![], vec![], 2, None);
+        let mut params = create_parameter_set(features.clone(), classes, LossFunction::MeanSquaredError, 10, 0.1, 0.0, 0.0, 0.0, 1000, false, false);
+        configure(&mut params);
+        network.batch_gradient_descent(&mut params).unwrap();
+
+        network.forward_pass(Rc::new(RefCell::new(create_dataset(1, 1, vec![vec![1.0]]))));
+        let output = network.get_output().borrow().data[0].clone();
+        output
+    }
+
+    #[test]
+    fn test_class_weights_rebalance_loss() {
+        let plain = fit_imbalanced(|_| {});
+        assert!((plain[0] - 0.9).abs() < 0.02 && (plain[1] - 0.1).abs() < 0.02);
+
+        let (_, classes) = imbalanced();
+        let weights = balanced_class_weights(&classes);
+        let balanced = fit_imbalanced(|params| params.config.class_weights = Some(weights));
+        assert!((balanced[0] - 0.5).abs() < 0.02 && (balanced[1] - 0.5).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_sample_weights_match_class_weights() {
+        let (_, classes) = imbalanced();
+        let weights = class_sample_weights(&classes, &balanced_class_weights(&classes));
+        let output = fit_imbalanced(|params| params.sample_weights = Some(weights));
+        assert!((output[0] - 0.5).abs() < 0.02);
+
+        let ignored = fit_imbalanced(|params| params.sample_weights = Some((0..10).map(|i| if i == 9 { 0.0 } else { 1.0 }).collect()));
+        assert!((ignored[0] - 1.0).abs() < 0.02 && ignored[1].abs() < 0.02);
+    }
+
+    #[test]
+    fn test_balanced_sampler_oversamples() {
+        let output = fit_imbalanced(|params| params.config.sampler = Some(Sampler::Balanced));
+        assert!((output[0] - 0.5).abs() < 0.25);
+        assert!(output[1] > 0.25);
+    }
+
+    #[test]
+    fn test_bad_sampler_weights_return_errors() {
+        let (features, classes) = imbalanced();
+        let mut network = create_network(1, 0, vec![], vec![], 2, None);
+        let mut params = create_parameter_set(features, classes, LossFunction::MeanSquaredError, 10, 0.1, 0.0, 0.0, 0.0, 1, false, false);
+
+        params.config.sampler = Some(Sampler::Weighted(vec![1.0; 9]));
+        assert!(matches!(network.batch_gradient_descent(&mut params), Err(Error::ShapeMismatch {expected: (10, 1), found: (9, 1)})));
+        params.config.sampler = Some(Sampler::Weighted(vec![0.0; 10]));
+        assert!(matches!(network.batch_gradient_descent(&mut params), Err(Error::InvalidConfig(_))));
+        params.config.sampler = Some(Sampler::Weighted((0..10).map(|i| if i == 3 { f32::NAN } else { 1.0 }).collect()));
+        assert!(matches!(network.batch_gradient_descent(&mut params), Err(Error::InvalidConfig(_))));
+        params.config.sampler = Some(Sampler::Weighted((0..10).map(|i| if i == 3 { -1.0 } else { 1.0 }).collect()));
+        assert!(matches!(network.batch_gradient_descent(&mut params), Err(Error::InvalidConfig(_))));
+
+        assert_eq!(params.dataset.rows, 10);
+        params.config.sampler = Some(Sampler::Weighted(vec![1.0; 10]));
+        assert!(network.batch_gradient_descent(&mut params).is_ok());
+    }
+
+    #[test]
+    fn test_malformed_input_returns_errors() {
+        let (features, classes) = blobs();
+        let mut network = create_network(2, 1, vec![4], vec![Some(tanh)], 2, Some(softmax));
+        let wide = Rc::new(RefCell::new(create_dataset(1, 3, vec![vec![1.0, 2.0, 3.0]])));
+        assert!(matches!(network.try_forward_pass(wide.clone()), Err(Error::ShapeMismatch {expected: (1, 2), found: (1, 3)})));
+        assert!(matches!(network.try_forward_pass(Rc::new(RefCell::new(create_dataset(0, 2, vec![])))), Err(Error::EmptyDataset)));
+        assert!(matches!(
+            network.try_accuracy(Rc::new(RefCell::new(features.clone())), Rc::new(RefCell::new(one_hot(&[0, 1], 2)))),
+            Err(Error::ShapeMismatch {expected: (20, 2), found: (2, 2)})));
+        assert!(network.try_accuracy(Rc::new(RefCell::new(features.clone())), Rc::new(RefCell::new(classes.clone()))).is_ok());
+
+        let mut params = create_parameter_set(features.clone(), one_hot(&[0; 20], 3), LossFunction::CrossEntropy, 5, 0.1, 0.0, 0.0, 0.0, 1, false, false);
+        assert!(matches!(network.batch_gradient_descent(&mut params), Err(Error::ShapeMismatch { .. })));
+        let mut params = create_parameter_set(features.clone(), classes.clone(), LossFunction::CrossEntropy, 50, 0.1, 0.0, 0.0, 0.0, 1, false, false);
+        assert!(matches!(network.batch_gradient_descent(&mut params), Err(Error::InvalidConfig(_))));
+        let mut params = create_parameter_set(features.clone(), classes.clone(), LossFunction::CrossEntropy, 5, 0.1, 0.0, 0.0, 1.5, 1, false, false);
+        assert!(matches!(network.batch_gradient_descent(&mut params), Err(Error::InvalidConfig(_))));
+        let mut params = create_parameter_set(features, classes, LossFunction::CrossEntropy, 5, 0.1, 0.0, 0.0, 0.0, 1, false, false);
+        params.sample_weights = Some(vec![1.0; 3]);
+        assert!(matches!(network.batch_gradient_descent(&mut params), Err(Error::ShapeMismatch {expected: (20, 1), found: (3, 1)})));
+        assert_eq!(params.dataset.rows, 20);
+    }
+
+    #[test]
+    fn test_try_create_network() {
+        assert!(matches!(try_create_network::<f32>(0, 0, vec![], vec![], 1, None), Err(Error::InvalidConfig(_))));
+        assert!(matches!(try_create_network::<f32>(2, 2, vec![3], vec![None], 1, None), Err(Error::InvalidConfig(_))));
+        assert!(matches!(try_create_network::<f32>(2, 1, vec![0], vec![None], 1, None), Err(Error::InvalidConfig(_))));
+        assert!(try_create_network(2, 1, vec![3], vec![Some(relu)], 1, None).is_ok());
+    }
+
+    #[test]
+    fn test_f64_network_learns_blobs() {
+        let (features, classes) = blobs();
+        let (features, classes) = (features.cast::<f64>(), classes.cast::<f64>());
+        let mut network = create_network(2, 1, vec![4], vec![Some(double::tanh)], 2, Some(double::softmax));
+        let mut params = create_parameter_set(features.clone(), classes.clone(), LossFunction::CrossEntropy, 20, 0.5, 0.0, 0.0, 0.0, 100, false, false);
+        network.batch_gradient_descent(&mut params).unwrap();
+
+        let accuracy = network.accuracy(Rc::new(RefCell::new(features)), Rc::new(RefCell::new(classes)));
+        assert_eq!(accuracy, 1.0);
+    }
+
+    #[test]
+    fn test_cast_network() {
+        let (features, classes) = blobs();
+        let mut network = create_network(2, 1, vec![4], vec![Some(tanh)], 2, Some(softmax));
+        let mut params = create_parameter_set(features.clone(), classes.clone(), LossFunction::CrossEntropy, 20, 0.5, 0.0, 0.0, 0.0, 20, false, false);
+        network.batch_gradient_descent(&mut params).unwrap();
+        network.forward_pass(Rc::new(RefCell::new(features.clone())));
+        let output = network.get_output().borrow().copy();
+
+        let mut wide = network.cast::<f64>().unwrap();
+        wide.forward_pass(Rc::new(RefCell::new(features.cast())));
+        let wide_output = wide.get_output().borrow().copy();
+        for (x, y) in output.data.iter().flatten().zip(wide_output.data.iter().flatten()) {
+            assert!((*x as f64 - y).abs() < 1e-5);
+        }
+
+        // Training continues in f64 and the result converts back.
+        let mut wide_params = create_parameter_set(features.cast(), classes.cast(), LossFunction::CrossEntropy, 20, 0.5, 0.0, 0.0, 0.0, 20, false, false);
+        wide.batch_gradient_descent(&mut wide_params).unwrap();
+        let mut narrow = wide.cast::<f32>().unwrap();
+        assert_eq!(narrow.accuracy(Rc::new(RefCell::new(features)), Rc::new(RefCell::new(classes))), 1.0);
+    }
+
+    fn doubled(input: Rc<RefCell<Matrix>>) {
+        input.borrow_mut().scalar_multiply(2.0);
+    }
+
+    #[test]
+    fn test_cast_rejects_custom_activations() {
+        let network = create_network(2, 1, vec![3], vec![Some(doubled as fn(_))], 2, Some(softmax));
+        assert!(matches!(network.cast::<f64>(), Err(Error::InvalidConfig(_))));
+        assert_eq!(network.activation_names(), vec!["none", "custom", "softmax"]);
+    }
+
+    #[test]
+    fn test_introspection() {
+        let network = create_network(3, 1, vec![4], vec![Some(relu)], 2, Some(softmax));
+        assert_eq!(network.num_layers(), 3);
+        assert_eq!(network.layer_sizes(), vec![3, 4, 2]);
+        assert_eq!(network.activation_names(), vec!["none", "relu", "softmax"]);
+        assert_eq!(network.connections().len(), 2);
+        assert_eq!((network.weights(0).rows, network.weights(0).cols), (3, 4));
+        assert_eq!((network.bias(1).rows, network.bias(1).cols), (1, 2));
+
+        let shapes: Vec<(usize, usize)> = network.parameters().map(|p| (p.rows, p.cols)).collect();
+        assert_eq!(shapes, vec![(3, 4), (1, 4), (4, 2), (1, 2)]);
+        assert_eq!(network.num_parameters(), 16 + 10);
+    }
+
+    #[test]
+    fn test_summary() {
+        let network = create_network(3, 1, vec![4], vec![Some(relu)], 2, Some(softmax));
+        let summary = network.summary();
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("Layer"));
+        assert_eq!(lines[1].split_whitespace().collect::<Vec<_>>(), vec!["0", "input", "3", "none", "0"]);
+        assert_eq!(lines[2].split_whitespace().collect::<Vec<_>>(), vec!["1", "hidden", "4", "relu", "16"]);
+        assert_eq!(lines[3].split_whitespace().collect::<Vec<_>>(), vec!["2", "output", "2", "softmax", "10"]);
+        assert_eq!(lines[4], "Total parameters: 26");
+    }
+
+    #[test]
+    fn test_cross_entropy_of_saturated_prediction_is_finite() {
+        let network = create_network(2, 0, vec![], vec![], 2, Some(softmax));
+        let prediction: Matrix = Matrix::create_matrix(1, 2, vec![vec![1.0, 0.0]]);
+        let target: Matrix = Matrix::create_matrix(1, 2, vec![vec![1.0, 0.0]]);
+        assert_eq!(network.cross_entropy_loss(&prediction, Rc::new(RefCell::new(target)), 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_output_is_a_snapshot() {
+        let (features, _) = blobs();
+        let mut network = create_network(2, 1, vec![3], vec![Some(tanh)], 2, Some(softmax));
+        network.forward_pass(Rc::new(RefCell::new(features.clone())));
+        let output = network.get_output();
+        let before = output.borrow().clone();
+
+        let first_row = create_dataset(1, 2, vec![features.data[1].clone()]);
+        network.forward_pass(Rc::new(RefCell::new(first_row)));
+        assert_eq!(*output.borrow(), before);
+        assert_eq!(network.get_output().borrow().rows, 1);
+    }
+
+    #[test]
+    fn test_forward_pass_on_a_layer_buffer() {
+        let mut network = create_network(2, 1, vec![2], vec![Some(relu)], 2, None);
+        let input: Matrix = Matrix::create_matrix(1, 2, vec![vec![0.5, -0.5]]);
+        network.forward_pass(Rc::new(RefCell::new(input)));
+
+        let output = network.get_output();
+        let expected = network.infer(&output.borrow()).unwrap();
+        network.forward_pass(output);
+        assert_eq!(*network.get_output().borrow(), expected);
+
+        let hidden = network.layers()[1].input.clone();
+        let expected = network.infer(&hidden.borrow()).unwrap();
+        network.forward_pass(hidden);
+        assert_eq!(*network.get_output().borrow(), expected);
+    }
+
+    #[test]
+    fn test_mean_squared_error_does_not_cancel_opposite_errors() {
+        let network = create_network(2, 0, vec![], vec![], 2, Some(softmax));
+        let prediction: Matrix = Matrix::create_matrix(1, 2, vec![vec![2.0, 0.0]]);
+        let target: Matrix = Matrix::create_matrix(1, 2, vec![vec![1.0, 1.0]]);
+        assert_eq!(network.mean_squared_error(&prediction, Rc::new(RefCell::new(target)), 0.0), 1.0);
+    }
+}
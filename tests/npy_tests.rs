@@ -0,0 +1,162 @@
+#[cfg(test)]
+mod npy_tests {
+    use std::fs::File;
+    use std::io::Cursor;
+    use cranium_rs::dataset::*;
+    use cranium_rs::error::Error;
+    use cranium_rs::function::*;
+    use cranium_rs::network::*;
+    use cranium_rs::npy::*;
+
+    fn fixture(name: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("cranium_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_read_npy_f64() {
+        let dataset = DataSet::from_npy(fixture("matrix.npy")).unwrap();
+
+        assert_eq!(dataset.rows, 3);
+        assert_eq!(dataset.cols, 2);
+        assert_eq!(dataset.data, vec![vec![1.0, 2.5], vec![-3.0, 4.0], vec![0.125, 6.0]]);
+    }
+
+    #[test]
+    fn test_read_npy_labels() {
+        let array = read_npy(File::open(fixture("labels.npy")).unwrap()).unwrap();
+        assert_eq!(array.shape, vec![4]);
+        assert_eq!(array.to_dataset().cols, 1);
+
+        let classes = DataSet::from_npy_labels(fixture("labels.npy"), None).unwrap();
+        assert_eq!(classes.data, vec![vec![0.0, 0.0, 1.0], vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0], vec![0.0, 0.0, 1.0]]);
+        assert!(DataSet::from_npy_labels(fixture("labels.npy"), Some(2)).is_err());
+        assert!(DataSet::from_npy_labels(fixture("matrix.npy"), None).is_err());
+    }
+
+    // A version 1.0 file with the given shape text followed by `data`.
+    fn npy_with_shape(shape: &str, data: &[u8]) -> Vec<u8> {
+        let header = format!("{{'descr': '<f4', 'fortran_order': False, 'shape': {}, }}\n", shape);
+        let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn test_read_npy_errors() {
+        assert!(DataSet::from_npy(fixture("fortran.npy")).is_err());
+        assert!(DataSet::from_npy(fixture("sample.libsvm")).is_err());
+    }
+
+    #[test]
+    fn test_read_npy_rejects_oversized_shapes() {
+        let data = [0u8; 8];
+        assert_eq!(read_npy(npy_with_shape("(2,)", &data).as_slice()).unwrap().data, vec![0.0, 0.0]);
+        assert!(matches!(read_npy(npy_with_shape("(3,)", &data).as_slice()), Err(Error::Parse {..})));
+        assert!(matches!(read_npy(npy_with_shape("(4294967296, 4294967296)", &data).as_slice()), Err(Error::Parse {..})));
+    }
+
+    #[test]
+    fn test_to_one_hot_reports_1_based_rows() {
+        let array = NpyArray {shape: vec![3], data: vec![0.0, 1.0, -1.0]};
+        assert!(matches!(array.to_one_hot(None), Err(Error::Parse {row: 3, column: 1, ..})));
+    }
+
+    #[test]
+    fn test_npy_round_trip() {
+        let dataset = create_dataset(2, 3, vec![vec![1.0, -2.0, 3.5], vec![4.0, 5.25, -6.0]]);
+        for dtype in [NpyDtype::F32, NpyDtype::F64, NpyDtype::I64] {
+            let mut bytes: Vec<u8> = Vec::new();
+            dataset.write_npy(&mut bytes, dtype).unwrap();
+            let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+            assert_eq!((10 + header_len) % 64, 0);
+
+            let array = read_npy(bytes.as_slice()).unwrap();
+            assert_eq!(array.shape, vec![2, 3]);
+            let loaded = array.to_dataset();
+            if dtype == NpyDtype::I64 {
+                assert_eq!(loaded.data, vec![vec![1.0, -2.0, 4.0], vec![4.0, 5.0, -6.0]]);
+            } else {
+                assert_eq!(loaded.data, dataset.data);
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_npz() {
+        let x = DataSet::from_npz(fixture("arrays.npz"), "x").unwrap();
+        assert_eq!(x.data, vec![vec![0.5, 1.5], vec![2.5, 3.5]]);
+
+        let arrays = read_npz(File::open(fixture("arrays.npz")).unwrap()).unwrap();
+        let (_, y) = arrays.iter().find(|(name, _)| name == "y").unwrap();
+        assert_eq!(y.to_one_hot(None).unwrap().data, vec![vec![0.0, 1.0], vec![1.0, 0.0]]);
+        assert!(DataSet::from_npz(fixture("arrays.npz"), "z").is_err());
+    }
+
+    #[test]
+    fn test_npz_round_trip() {
+        let a = create_dataset(1, 2, vec![vec![1.0, 2.0]]);
+        let b = create_dataset(2, 1, vec![vec![3.0], vec![4.0]]);
+        let mut buffer = Cursor::new(Vec::new());
+        write_npz(&mut buffer, &[("a", &a), ("b", &b)]).unwrap();
+
+        buffer.set_position(0);
+        let arrays = read_npz(buffer).unwrap();
+        assert_eq!(arrays.len(), 2);
+        assert_eq!(arrays[0].0, "a");
+        assert_eq!(arrays[1].1.to_dataset().data, b.data);
+    }
+
+    #[test]
+    fn test_network_weights_round_trip() {
+        let path = temp_path("weights.npz");
+        let features = create_dataset(2, 3, vec![vec![0.1, 0.2, 0.3], vec![-0.4, 0.5, 0.6]]);
+        let mut trained = create_network(3, 1, vec![4], vec![Some(relu)], 2, Some(softmax));
+        trained.save_npz(&path).unwrap();
+
+        let mut loaded = create_network(3, 1, vec![4], vec![Some(relu)], 2, Some(softmax));
+        loaded.load_npz(&path).unwrap();
+        let mut other_shape = create_network(3, 1, vec![5], vec![Some(relu)], 2, Some(softmax));
+        assert!(other_shape.load_npz(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        trained.forward_pass(std::rc::Rc::new(std::cell::RefCell::new(features.clone())));
+        loaded.forward_pass(std::rc::Rc::new(std::cell::RefCell::new(features)));
+        assert!(trained.get_output().borrow().equals(&loaded.get_output().borrow()));
+    }
+
+    #[test]
+    fn test_f64_npy_round_trip() {
+        let dataset: DataSet<f64> = DataSet {rows: 1, cols: 2, data: vec![vec![0.1, -1.0 / 3.0]]};
+        let mut bytes: Vec<u8> = Vec::new();
+        dataset.write_npy(&mut bytes, NpyDtype::of::<f64>()).unwrap();
+        let loaded = read_npy(bytes.as_slice()).unwrap().to_dataset_as::<f64>();
+        assert_eq!(loaded.data, dataset.data);
+    }
+
+    #[test]
+    fn test_f64_network_weights_round_trip() {
+        let path = temp_path("weights_f64.npz");
+        let features = create_dataset(4, 2, vec![vec![1.0, 1.0], vec![-1.0, -1.0], vec![1.0, 0.5], vec![-0.5, -1.0]]).cast::<f64>();
+        let classes = one_hot(&[0, 1, 0, 1], 2).cast::<f64>();
+        let mut trained = create_network(2, 1, vec![3], vec![Some(double::tanh)], 2, Some(double::softmax));
+        let mut params = create_parameter_set(features, classes, LossFunction::CrossEntropy, 2, 0.3, 0.0, 0.0, 0.0, 5, false, false);
+        trained.batch_gradient_descent(&mut params).unwrap();
+        // Trained weights use the full f64 precision, which an f32 archive would lose.
+        assert!(trained.weights(0).data.iter().flatten().any(|&w| w as f32 as f64 != w));
+        trained.save_npz(&path).unwrap();
+
+        let mut loaded = create_network(2, 1, vec![3], vec![Some(double::tanh)], 2, Some(double::softmax));
+        loaded.load_npz(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        for i in 0..2 {
+            assert_eq!(loaded.weights(i).data, trained.weights(i).data);
+            assert_eq!(loaded.bias(i).data, trained.bias(i).data);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod precision_tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use half::{bf16, f16};
+    use cranium_rs::dataset::*;
+    use cranium_rs::error::Error;
+    use cranium_rs::function::*;
+    use cranium_rs::matrix::*;
+    use cranium_rs::network::*;
+    use cranium_rs::precision::*;
+    use cranium_rs::workspace::*;
+
+    fn blobs() -> (DataSet, DataSet) {
+        let mut features = Vec::new();
+        let mut labels = Vec::new();
+        for i in 0..40 {
+            let class = i % 2;
+            let offset = if class == 0 { 1.0 } else { -1.0 };
+            let jitter = (i as f32 * 0.7).sin() * 0.3;
+            features.push(vec![offset + jitter, offset - jitter]);
+            labels.push(class);
+        }
+        (create_dataset(40, 2, features), one_hot(&labels, 2))
+    }
+
+    fn outputs(network: &mut Network, features: &DataSet) -> Matrix {
+        network.forward_pass(Rc::new(RefCell::new(features.clone())));
+        let output = network.get_output().borrow().copy();
+        output
+    }
+
+    #[test]
+    fn test_precision_rounding() {
+        assert_eq!(Precision::Full.round(1.0001), 1.0001);
+        assert_eq!(Precision::BF16.round(1.0001), 1.0);
+        assert_eq!(Precision::F16.round(1.0001), 1.0);
+        assert_eq!(Precision::F16.round(1e-8), 0.0);
+        assert!(Precision::BF16.round(1e-8) > 0.0);
+        assert!(Precision::F16.round(1e5).is_infinite());
+    }
+
+    #[test]
+    fn test_half_matrix_round_trip() {
+        let matrix = Matrix::create_matrix(2, 2, vec![vec![0.5, -2.0], vec![1.0 / 3.0, 1000.0]]);
+        let half: HalfMatrix<f16> = HalfMatrix::from_matrix(&matrix);
+        assert_eq!(half.memory_bytes(), 8);
+        let back = half.to_matrix();
+        assert_eq!(back.get(0, 1), -2.0);
+        assert!((back.get(1, 0) - 1.0 / 3.0).abs() < 1e-3);
+        assert_eq!(HalfMatrix::<bf16>::from_matrix(&matrix).get(1, 1), 1000.0);
+    }
+
+    #[test]
+    fn test_multiply_accumulates_in_f32() {
+        // Summing 4096 ones in f16 would stall at 2048.
+        let row = Matrix::create_matrix(1, 4096, vec![vec![1.0; 4096]]);
+        let column = Matrix::create_matrix(4096, 1, vec![vec![1.0]; 4096]);
+        let product = HalfMatrix::<f16>::from_matrix(&row).multiply(&HalfMatrix::from_matrix(&column));
+        assert_eq!(product.get(0, 0), 4096.0);
+    }
+
+    #[test]
+    fn test_dynamic_loss_scaling() {
+        let mut scaling = create_loss_scaling(1024.0, true).unwrap();
+        scaling.growth_interval = 2;
+        scaling.update(true);
+        assert_eq!(scaling.scale, 512.0);
+        scaling.update(false);
+        scaling.update(false);
+        assert_eq!(scaling.scale, 1024.0);
+
+        let mut fixed = create_loss_scaling(8.0, false).unwrap();
+        fixed.update(true);
+        assert_eq!(fixed.scale, 8.0);
+
+        for scale in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert!(matches!(create_loss_scaling(scale, true), Err(Error::InvalidConfig(_))));
+        }
+    }
+
+    #[test]
+    fn test_half_precision_inference_copy() {
+        let (features, classes) = blobs();
+        let mut network = create_network(2, 1, vec![8], vec![Some(tanh)], 2, Some(softmax));
+        let mut params = create_parameter_set(features.clone(), classes.clone(), LossFunction::CrossEntropy, 10, 0.5, 0.0, 0.0, 0.0, 50, false, false);
+        network.batch_gradient_descent(&mut params).unwrap();
+
+        let half = network.to_half_precision::<bf16>();
+        assert_eq!(half.memory_bytes(), (2 * 8 + 8 + 8 * 2 + 2) * 2);
+        let report = compare_precision(&network, &half, &features, &classes).unwrap();
+        assert_eq!(report.full_accuracy, 1.0);
+        assert_eq!(report.accuracy_difference, 0.0);
+        assert!(report.max_output_error < 0.05);
+
+        let half_f16 = network.to_half_precision::<f16>();
+        let report = compare_precision(&network, &half_f16, &features, &classes).unwrap();
+        assert_eq!(report.half_accuracy, 1.0);
+        assert!(report.max_output_error < 0.01);
+
+        let wide = create_dataset(1, 3, vec![vec![0.0; 3]]);
+        assert!(matches!(half.infer(&wide), Err(Error::ShapeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_mixed_precision_training() {
+        let (features, classes) = blobs();
+        for precision in [Precision::BF16, Precision::F16] {
+            let mut network = create_network(2, 1, vec![4], vec![Some(tanh)], 2, Some(softmax));
+            let mut params = create_parameter_set(features.clone(), classes.clone(), LossFunction::CrossEntropy, 10, 0.5, 0.0, 0.0, 0.0, 100, false, false);
+            params.config.precision = precision;
+            params.config.loss_scaling = Some(create_loss_scaling(128.0, true).unwrap());
+            network.batch_gradient_descent(&mut params).unwrap();
+            assert_eq!(network.accuracy(Rc::new(RefCell::new(features.clone())), Rc::new(RefCell::new(classes.clone()))), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_overflowing_scale_skips_steps() {
+        let (features, classes) = blobs();
+        let mut network = create_network(2, 0, vec![], vec![], 2, Some(softmax));
+        let before = outputs(&mut network, &features);
+        let mut params = create_parameter_set(features.clone(), classes.clone(), LossFunction::CrossEntropy, 10, 0.5, 0.0, 0.0, 0.0, 5, false, false);
+        params.config.precision = Precision::F16;
+        params.config.loss_scaling = Some(create_loss_scaling(1e9, false).unwrap());
+        network.batch_gradient_descent(&mut params).unwrap();
+        assert_eq!(outputs(&mut network, &features).data, before.data);
+
+        params.config.loss_scaling = Some(create_loss_scaling(1e9, true).unwrap());
+        params.config.max_iters = 50;
+        network.batch_gradient_descent(&mut params).unwrap();
+        assert!(params.config.loss_scaling.as_ref().unwrap().scale < 1e9);
+        assert_ne!(outputs(&mut network, &features).data, before.data);
+    }
+
+    #[test]
+    fn test_half_inference_rounds_each_layer_once() {
+        let (features, _) = blobs();
+        let network = create_network(2, 0, vec![], vec![], 2, None);
+        let half = network.to_half_precision::<bf16>();
+        let round = |x: f32| bf16::from_f32(x).to_f32();
+
+        let output = half.infer(&features).unwrap();
+        for (row, input) in features.data.iter().enumerate() {
+            for j in 0..2 {
+                let sum: f32 = (0..2).map(|k| round(input[k]) * round(network.weights(0).get(k, j))).sum();
+                assert_eq!(output.get(row, j), round(sum + round(network.bias(0).get(0, j))));
+            }
+        }
+    }
+
+    #[test]
+    fn test_compare_precision_leaves_network_untouched() {
+        let (features, classes) = blobs();
+        let mut network = create_network(2, 1, vec![4], vec![Some(tanh)], 2, Some(softmax));
+        let other = create_dataset(1, 2, vec![vec![0.25, -0.5]]);
+        let before = outputs(&mut network, &other);
+
+        compare_precision(&network, &network.to_half_precision::<f16>(), &features, &classes).unwrap();
+        assert_eq!(network.get_output().borrow().data, before.data);
+    }
+
+    #[test]
+    fn test_reduced_precision_passes_keep_f32_master_weights() {
+        let (features, classes) = blobs();
+        let mut network = create_network(2, 1, vec![4], vec![Some(tanh)], 2, Some(softmax));
+        let weights = network.weights(0).copy();
+        let mut config = create_training_config(LossFunction::CrossEntropy, 40, 0.0, 0.0, 0.0, 0.0, 1, false, false);
+        let mut workspace = create_workspace(&network);
+        let full_loss = network.train_step(&mut workspace, &features, &classes, None, &mut config).unwrap();
+
+        config.precision = Precision::BF16;
+        let half_loss = network.train_step(&mut workspace, &features, &classes, None, &mut config).unwrap();
+        assert_ne!(half_loss, full_loss);
+        assert_eq!(network.weights(0).data, weights.data);
+        assert!(weights.data.iter().flatten().any(|&w| Precision::BF16.round(w) != w));
+    }
+}
@@ -0,0 +1,263 @@
+#[cfg(test)]
+mod preprocessing_tests {
+    use cranium_rs::dataset::*;
+    use cranium_rs::error::Error;
+    use cranium_rs::preprocessing::*;
+
+    fn sample() -> DataSet {
+        create_dataset(4, 2, vec![vec![1.0, 10.0], vec![2.0, 10.0], vec![3.0, 10.0], vec![10.0, 10.0]])
+    }
+
+    fn assert_close(a: &DataSet, b: &DataSet) {
+        assert_eq!(a.rows, b.rows);
+        assert_eq!(a.cols, b.cols);
+        for (x, y) in a.data.iter().flatten().zip(b.data.iter().flatten()) {
+            assert!((x - y).abs() < 1e-5, "{} != {}", x, y);
+        }
+    }
+
+    #[test]
+    fn test_standard_scaler() {
+        let mut scaler = create_scaler(ScalerKind::Standard);
+        let scaled = scaler.fit_transform(&sample()).unwrap();
+
+        let mean: f32 = scaled.data.iter().map(|row| row[0]).sum::<f32>() / 4.0;
+        let variance: f32 = scaled.data.iter().map(|row| (row[0] - mean).powi(2)).sum::<f32>() / 4.0;
+        assert!(mean.abs() < 1e-6);
+        assert!((variance - 1.0).abs() < 1e-5);
+        assert!(scaled.data.iter().all(|row| row[1] == 0.0));
+        assert_close(&scaler.inverse_transform(&scaled).unwrap(), &sample());
+    }
+
+    #[test]
+    fn test_min_max_scaler() {
+        let mut scaler = create_scaler(ScalerKind::MinMax(-1.0, 1.0));
+        let scaled = scaler.fit_transform(&sample()).unwrap();
+
+        assert_eq!(scaled.data[0][0], -1.0);
+        assert_eq!(scaled.data[3][0], 1.0);
+        assert_close(&scaler.inverse_transform(&scaled).unwrap(), &sample());
+    }
+
+    #[test]
+    fn test_robust_scaler() {
+        let mut scaler = create_scaler(ScalerKind::Robust);
+        scaler.fit(&sample()).unwrap();
+
+        assert_eq!(scaler.offset[0], 2.5);
+        assert_eq!(scaler.scale[0], 4.75 - 1.75);
+        let scaled = scaler.transform(&sample()).unwrap();
+        assert_close(&scaler.inverse_transform(&scaled).unwrap(), &sample());
+    }
+
+    #[test]
+    fn test_scaler_errors() {
+        let scaler = create_scaler(ScalerKind::Standard);
+        assert!(scaler.transform(&sample()).is_err());
+
+        let mut scaler = create_scaler(ScalerKind::Standard);
+        assert!(scaler.fit(&create_dataset(0, 2, vec![])).is_err());
+        scaler.fit(&sample()).unwrap();
+        assert!(scaler.transform(&create_dataset(1, 3, vec![vec![1.0, 2.0, 3.0]])).is_err());
+
+        for kind in [ScalerKind::MinMax(1.0, 1.0), ScalerKind::MinMax(1.0, -1.0), ScalerKind::MinMax(f32::NAN, 1.0)] {
+            assert!(matches!(create_scaler(kind).fit(&sample()), Err(Error::InvalidConfig(_))));
+        }
+    }
+
+    #[test]
+    fn test_scaler_save_load() {
+        for kind in [ScalerKind::Standard, ScalerKind::MinMax(0.0, 1.0), ScalerKind::Robust] {
+            let mut scaler = create_scaler(kind);
+            scaler.fit(&sample()).unwrap();
+            let mut bytes: Vec<u8> = Vec::new();
+            scaler.save(&mut bytes).unwrap();
+
+            let loaded = Scaler::load(bytes.as_slice()).unwrap();
+            assert_eq!(loaded, scaler);
+        }
+        assert!(Scaler::load("scaler fancy\n1\n1\n".as_bytes()).is_err());
+        assert!(matches!(Scaler::load("scaler minmax 1 0\n1\n1\n".as_bytes()), Err(Error::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_label_encoder() {
+        let labels = vec![7.0, -1.0, 3.0, 7.0];
+        let mut encoder = create_label_encoder();
+        encoder.fit(&labels).unwrap();
+
+        assert_eq!(encoder.classes, vec![-1.0, 3.0, 7.0]);
+        assert_eq!(encoder.transform(&labels).unwrap(), vec![2, 0, 1, 2]);
+        assert_eq!(encoder.inverse_transform(&[1, 0]).unwrap(), vec![3.0, -1.0]);
+        assert!(encoder.transform(&[5.0]).is_err());
+
+        let classes = encoder.to_classes(&labels).unwrap();
+        assert_eq!(classes.cols, 3);
+        assert_eq!(classes.data[0], vec![0.0, 0.0, 1.0]);
+        assert_eq!(encoder.from_classes(&classes).unwrap(), labels);
+
+        let mut bytes: Vec<u8> = Vec::new();
+        encoder.save(&mut bytes).unwrap();
+        assert_eq!(LabelEncoder::load(bytes.as_slice()).unwrap(), encoder);
+    }
+
+    #[test]
+    fn test_one_hot_encoder() {
+        let categorical = create_dataset(3, 2, vec![vec![0.0, 5.0], vec![2.0, 5.0], vec![0.0, 6.0]]);
+        let mut encoder = create_one_hot_encoder(false);
+        let encoded = encoder.fit_transform(&categorical).unwrap();
+
+        assert_eq!(encoded.cols, 4);
+        assert_eq!(encoded.data[1], vec![0.0, 1.0, 1.0, 0.0]);
+        assert_eq!(encoder.inverse_transform(&encoded).unwrap().data, categorical.data);
+
+        let unseen = create_dataset(1, 2, vec![vec![1.0, 6.0]]);
+        assert!(matches!(encoder.transform(&unseen), Err(Error::Parse {row: 1, column: 1, ..})));
+        let unseen_later = create_dataset(2, 2, vec![vec![0.0, 5.0], vec![2.0, 7.0]]);
+        assert!(matches!(encoder.transform(&unseen_later), Err(Error::Parse {row: 2, column: 2, ..})));
+        encoder.ignore_unknown = true;
+        assert_eq!(encoder.transform(&unseen).unwrap().data[0], vec![0.0, 0.0, 0.0, 1.0]);
+
+        let mut bytes: Vec<u8> = Vec::new();
+        encoder.save(&mut bytes).unwrap();
+        assert_eq!(OneHotEncoder::load(bytes.as_slice()).unwrap(), encoder);
+        assert!(matches!(OneHotEncoder::load("onehot 2 false\n0 2\n\n".as_bytes()), Err(Error::Parse {row: 3, column: 1, ..})));
+    }
+
+    // Mostly along (1, 2, 0), with a little spread in the other directions.
+    fn correlated() -> DataSet {
+        let data: Vec<Vec<f32>> = (0..50).map(|i| {
+            let t = i as f32 / 10.0 - 2.5;
+            let e = ((i * 7) % 11) as f32 / 11.0 - 0.5;
+            let z = ((i * 3) % 5) as f32 * 0.1;
+            vec![t + 0.05 * e, 2.0 * t - 0.1 * e, z]
+        }).collect();
+        create_dataset(50, 3, data)
+    }
+
+    fn covariance(dataset: &DataSet) -> Vec<Vec<f32>> {
+        let n = dataset.rows as f32;
+        let means: Vec<f32> = (0..dataset.cols).map(|j| dataset.data.iter().map(|row| row[j]).sum::<f32>() / n).collect();
+        (0..dataset.cols).map(|a| (0..dataset.cols).map(|b| {
+            dataset.data.iter().map(|row| (row[a] - means[a]) * (row[b] - means[b])).sum::<f32>() / n
+        }).collect()).collect()
+    }
+
+    #[test]
+    fn test_pca_by_count() {
+        let mut pca = create_pca(PcaComponents::Count(1), false);
+        let projected = pca.fit_transform(&correlated()).unwrap();
+        assert_eq!((projected.rows, projected.cols), (50, 1));
+        assert_eq!(pca.num_components(), 1);
+
+        let axis = &pca.axes[0];
+        let expected = [1.0 / 5.0_f32.sqrt(), 2.0 / 5.0_f32.sqrt(), 0.0];
+        assert!(axis.iter().zip(expected.iter()).all(|(a, b)| (a - b).abs() < 0.01), "{:?}", axis);
+        assert!(pca.explained_variance_ratio()[0] > 0.99);
+
+        let restored = pca.inverse_transform(&projected).unwrap();
+        for (x, y) in restored.data.iter().flatten().zip(correlated().data.iter().flatten()) {
+            assert!((x - y).abs() < 0.25);
+        }
+    }
+
+    #[test]
+    fn test_pca_by_variance_ratio() {
+        let mut pca = create_pca(PcaComponents::VarianceRatio(0.99), false);
+        pca.fit(&correlated()).unwrap();
+        assert_eq!(pca.num_components(), 1);
+
+        pca.components = PcaComponents::VarianceRatio(1.0);
+        pca.fit(&correlated()).unwrap();
+        assert_eq!(pca.num_components(), 3);
+        let ratios = pca.explained_variance_ratio();
+        assert!((ratios.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        assert!(ratios.windows(2).all(|w| w[0] >= w[1]));
+        let projected = pca.transform(&correlated()).unwrap();
+        assert_close(&pca.inverse_transform(&projected).unwrap(), &correlated());
+    }
+
+    #[test]
+    fn test_pca_whiten() {
+        let mut pca = create_pca(PcaComponents::Count(2), true);
+        let projected = pca.fit_transform(&correlated()).unwrap();
+        let covariance = covariance(&projected);
+        for (i, row) in covariance.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                assert!((value - if i == j { 1.0 } else { 0.0 }).abs() < 1e-3, "{:?}", covariance);
+            }
+        }
+    }
+
+    #[test]
+    fn test_zca_whitening() {
+        let mut whitening = create_whitening(0.0);
+        let whitened = whitening.fit_transform(&correlated()).unwrap();
+        let covariance = covariance(&whitened);
+        for (i, row) in covariance.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                assert!((value - if i == j { 1.0 } else { 0.0 }).abs() < 1e-3, "{:?}", covariance);
+            }
+        }
+        // ZCA's matrix is symmetric, unlike other whitening rotations.
+        for i in 0..3 {
+            for j in 0..3 {
+                assert!((whitening.matrix[i][j] - whitening.matrix[j][i]).abs() < 1e-3);
+            }
+        }
+        let restored = whitening.inverse_transform(&whitened).unwrap();
+        for (x, y) in restored.data.iter().flatten().zip(correlated().data.iter().flatten()) {
+            assert!((x - y).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_pca_errors() {
+        let pca = create_pca(PcaComponents::Count(1), false);
+        assert!(pca.transform(&correlated()).is_err());
+        for components in [PcaComponents::Count(0), PcaComponents::Count(4), PcaComponents::VarianceRatio(1.5)] {
+            assert!(matches!(create_pca(components, false).fit(&correlated()), Err(Error::InvalidConfig(_))));
+        }
+
+        let mut pca = create_pca(PcaComponents::Count(2), false);
+        pca.fit(&correlated()).unwrap();
+        assert!(matches!(pca.transform(&sample()), Err(Error::ShapeMismatch { .. })));
+        assert!(matches!(create_whitening(-1.0).fit(&correlated()), Err(Error::InvalidConfig(_))));
+        assert!(matches!(create_whitening(0.0).fit(&create_dataset(0, 2, vec![])), Err(Error::EmptyDataset)));
+
+        let empty = create_dataset(0, correlated().cols, vec![]);
+        assert!(matches!(pca.transform(&empty), Err(Error::EmptyDataset)));
+        assert!(matches!(pca.inverse_transform(&create_dataset(0, 2, vec![])), Err(Error::EmptyDataset)));
+        let mut whitening = create_whitening(1e-5);
+        whitening.fit(&correlated()).unwrap();
+        assert!(matches!(whitening.transform(&empty), Err(Error::EmptyDataset)));
+        assert!(matches!(whitening.inverse_transform(&empty), Err(Error::EmptyDataset)));
+
+        let mut bytes: Vec<u8> = Vec::new();
+        pca.save(&mut bytes).unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        let mut header: Vec<&str> = lines[0].split_whitespace().collect();
+        header[4] = "0";
+        let no_axes = format!("{}\n{}\n\n", header.join(" "), lines[1]);
+        assert!(matches!(Pca::load(no_axes.as_bytes()), Err(Error::Parse {row: 1, column: 5, ..})));
+    }
+
+    #[test]
+    fn test_pca_whitening_save_load() {
+        let mut pca = create_pca(PcaComponents::VarianceRatio(0.9), true);
+        pca.fit(&correlated()).unwrap();
+        let mut bytes: Vec<u8> = Vec::new();
+        pca.save(&mut bytes).unwrap();
+        let loaded = Pca::load(bytes.as_slice()).unwrap();
+        assert_eq!(loaded, pca);
+        assert_eq!(loaded.transform(&correlated()).unwrap().data, pca.transform(&correlated()).unwrap().data);
+
+        let mut whitening = create_whitening(1e-5);
+        whitening.fit(&correlated()).unwrap();
+        let mut bytes: Vec<u8> = Vec::new();
+        whitening.save(&mut bytes).unwrap();
+        assert_eq!(Whitening::load(bytes.as_slice()).unwrap(), whitening);
+        assert!(Pca::load("pca count 1 false\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,233 @@
+#[cfg(test)]
+mod source_tests {
+    use cranium_rs::csv::*;
+    use cranium_rs::dataset::*;
+    use cranium_rs::error::Error;
+    use cranium_rs::function::*;
+    use cranium_rs::network::*;
+    use cranium_rs::npy::*;
+    use cranium_rs::source::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("cranium_source_{}_{}", std::process::id(), name))
+    }
+
+    fn numbered(rows: usize) -> (DataSet, DataSet) {
+        let features = create_dataset(rows, 2, (0..rows).map(|i| vec![i as f32, -(i as f32)]).collect());
+        let targets = create_dataset(rows, 1, (0..rows).map(|i| vec![(i % 2) as f32]).collect());
+        (features, targets)
+    }
+
+    // Drains one epoch and returns the first feature of every row.
+    fn drain<S: DataSource>(source: &mut S) -> Vec<f32> {
+        source.reset().unwrap();
+        let mut seen = Vec::new();
+        while let Some((features, targets)) = source.next_batch(3).unwrap() {
+            assert!(features.rows <= 3);
+            assert_eq!(features.rows, targets.rows);
+            seen.extend(features.data.iter().map(|row| row[0]));
+        }
+        seen
+    }
+
+    fn sorted(mut values: Vec<f32>) -> Vec<f32> {
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        values
+    }
+
+    #[test]
+    fn test_memory_source_batches() {
+        let (features, targets) = numbered(7);
+        let mut source = create_memory_source(features, targets, false);
+        source.reset().unwrap();
+        let (first, first_targets) = source.next_batch(3).unwrap().unwrap();
+        assert_eq!(first.data, vec![vec![0.0, -0.0], vec![1.0, -1.0], vec![2.0, -2.0]]);
+        assert_eq!(first_targets.data, vec![vec![0.0], vec![1.0], vec![0.0]]);
+        assert_eq!(source.next_batch(3).unwrap().unwrap().0.rows, 3);
+        assert_eq!(source.next_batch(3).unwrap().unwrap().0.rows, 1);
+        assert!(source.next_batch(3).unwrap().is_none());
+
+        assert_eq!(drain(&mut source), (0..7).map(|i| i as f32).collect::<Vec<f32>>());
+    }
+
+    #[test]
+    fn test_shuffled_memory_source_keeps_pairs() {
+        let (features, targets) = numbered(20);
+        let mut source = create_memory_source(features, targets, true);
+        source.reset().unwrap();
+        while let Some((features, targets)) = source.next_batch(6).unwrap() {
+            for (row, target) in features.data.iter().zip(targets.data.iter()) {
+                assert_eq!(row[1], -row[0]);
+                assert_eq!(target[0], (row[0] as usize % 2) as f32);
+            }
+        }
+        assert_eq!(sorted(drain(&mut source)), (0..20).map(|i| i as f32).collect::<Vec<f32>>());
+    }
+
+    #[test]
+    fn test_generator_source() {
+        let mut source = create_generator_source(1, 1, |i| if i < 5 { Some((vec![i as f32], vec![2.0 * i as f32])) } else { None });
+        assert_eq!(drain(&mut source), vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(drain(&mut source).len(), 5);
+
+        let mut wrong = create_generator_source(2, 1, |_| Some((vec![1.0], vec![1.0])));
+        assert!(wrong.next_sample().is_err());
+    }
+
+    #[test]
+    fn test_csv_source_streams_rows() {
+        let path = temp_path("stream.csv");
+        std::fs::write(&path, "a,label,b\n1,0,10\n2,1,NA\n\n3,0,30\n").unwrap();
+        let options = CsvOptions {has_header: true, missing: MissingValues::SkipRow, ..CsvOptions::default()};
+        let mut source = CsvSource::open(&path, &options, &[Column::Name("label".to_string())]).unwrap();
+        assert_eq!(source.num_features(), 2);
+        assert_eq!(source.num_targets(), 1);
+
+        source.reset().unwrap();
+        let (features, targets) = source.next_batch(10).unwrap().unwrap();
+        assert_eq!(features.data, vec![vec![1.0, 10.0], vec![3.0, 30.0]]);
+        assert_eq!(targets.data, vec![vec![0.0], vec![0.0]]);
+        assert_eq!(drain(&mut source), vec![1.0, 3.0]);
+
+        let strict = CsvOptions {has_header: true, ..CsvOptions::default()};
+        let mut source: CsvSource = CsvSource::open(&path, &strict, &[Column::Index(1)]).unwrap();
+        source.next_sample().unwrap();
+        assert!(source.next_sample().is_err());
+
+        let mean = CsvOptions {missing: MissingValues::ColumnMean, ..CsvOptions::default()};
+        assert!(CsvSource::<f32>::open(&path, &mean, &[]).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_mmap_source_reads_npy() {
+        let (features, targets) = numbered(5);
+        let features_path = temp_path("features.npy");
+        let targets_path = temp_path("targets.npy");
+        features.to_npy(&features_path, NpyDtype::F32).unwrap();
+        targets.to_npy(&targets_path, NpyDtype::F32).unwrap();
+
+        let mut source = MmapSource::open(&features_path, &targets_path).unwrap();
+        assert_eq!(source.rows(), 5);
+        assert_eq!((source.num_features(), source.num_targets()), (2, 1));
+        source.reset().unwrap();
+        assert_eq!(source.next_sample().unwrap(), Some((vec![0.0, -0.0], vec![0.0])));
+        assert_eq!(source.next_sample().unwrap(), Some((vec![1.0, -1.0], vec![1.0])));
+        assert_eq!(drain(&mut source), vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+
+        targets.to_npy(&targets_path, NpyDtype::F64).unwrap();
+        assert!(MmapSource::<f32>::open(&features_path, &targets_path).is_err());
+        std::fs::remove_file(&features_path).unwrap();
+        std::fs::remove_file(&targets_path).unwrap();
+    }
+
+    #[test]
+    fn test_shuffle_buffer_is_a_seeded_permutation() {
+        let (features, targets) = numbered(30);
+        let mut first = create_shuffle_buffer(create_memory_source(features.clone(), targets.clone(), false), 8, 11);
+        let mut second = create_shuffle_buffer(create_memory_source(features, targets, false), 8, 11);
+        let order = drain(&mut first);
+        assert_eq!(order, drain(&mut second));
+        assert_ne!(order, (0..30).map(|i| i as f32).collect::<Vec<f32>>());
+        assert_eq!(sorted(order.clone()), (0..30).map(|i| i as f32).collect::<Vec<f32>>());
+        assert_ne!(drain(&mut first), order);
+    }
+
+    #[test]
+    fn test_prefetch_matches_source() {
+        let (features, targets) = numbered(25);
+        let mut prefetch = create_prefetch(create_memory_source(features, targets, false), 4, 2);
+        let expected: Vec<f32> = (0..25).map(|i| i as f32).collect();
+        assert_eq!(drain(&mut prefetch), expected);
+        assert!(prefetch.next_sample().unwrap().is_none());
+        assert_eq!(drain(&mut prefetch), expected);
+
+        // Resetting part way through an epoch starts over.
+        prefetch.reset().unwrap();
+        prefetch.next_batch(5).unwrap();
+        assert_eq!(drain(&mut prefetch), expected);
+    }
+
+    #[test]
+    fn test_mmap_source_checks_shapes() {
+        let (features, _) = numbered(5);
+        let features_path = temp_path("checked_features.npy");
+        let targets_path = temp_path("checked_targets.npy");
+        features.to_npy(&features_path, NpyDtype::F32).unwrap();
+        create_dataset(4, 3, vec![vec![0.0; 3]; 4]).to_npy(&targets_path, NpyDtype::F32).unwrap();
+        let result = MmapSource::<f32>::open(&features_path, &targets_path);
+        assert!(matches!(result, Err(Error::ShapeMismatch {expected: (5, 2), found: (4, 3)})));
+
+        let header = "{'descr': '<f4', 'fortran_order': False, 'shape': (4611686018427387904, 4), }\n";
+        let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        std::fs::write(&targets_path, bytes).unwrap();
+        let result = MmapSource::<f32>::open(&features_path, &targets_path);
+        assert!(matches!(result, Err(Error::Parse {..})));
+        std::fs::remove_file(&features_path).unwrap();
+        std::fs::remove_file(&targets_path).unwrap();
+    }
+
+    #[test]
+    fn test_sources_in_f64() {
+        let path = temp_path("wide.csv");
+        std::fs::write(&path, "0.1,1\n0.2,0\n").unwrap();
+        let mut csv: CsvSource<f64> = CsvSource::open(&path, &CsvOptions::default(), &[Column::Index(1)]).unwrap();
+        assert_eq!(csv.next_sample().unwrap(), Some((vec![0.1], vec![1.0])));
+        std::fs::remove_file(&path).unwrap();
+
+        let generator = create_generator_source(1, 2, |i| if i < 8 {
+            let class = (i % 2) as f64;
+            Some((vec![2.0 * class - 1.0], vec![1.0 - class, class]))
+        } else { None });
+        let mut source = create_prefetch(create_shuffle_buffer(generator, 4, 5), 2, 2);
+        let mut network = create_network(1, 0, vec![], vec![], 2, Some(double::softmax));
+        let mut config = create_training_config(LossFunction::CrossEntropy, 4, 0.5, 0.0, 0.0, 0.0, 50, false, false);
+        network.train_from_source(&mut source, &mut config).unwrap();
+
+        let features = create_dataset(2, 1, vec![vec![-1.0], vec![1.0]]).cast::<f64>();
+        assert_eq!(network.predict_classes(&features).unwrap(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_training_from_source() {
+        let blob = |i: usize| {
+            let offset = if i.is_multiple_of(2) { 1.0 } else { -1.0 };
+            let jitter = (i as f32 * 0.7).sin() * 0.3;
+            (vec![offset + jitter, offset - jitter], if i.is_multiple_of(2) { vec![1.0, 0.0] } else { vec![0.0, 1.0] })
+        };
+        let generator = create_generator_source(2, 2, move |i| if i < 20 { Some(blob(i)) } else { None });
+        let mut source = create_prefetch(create_shuffle_buffer(generator, 10, 3), 5, 2);
+        let (features, classes) = (0..20).map(blob).unzip::<_, _, Vec<Vec<f32>>, Vec<Vec<f32>>>();
+        let features = create_dataset(20, 2, features);
+        let classes = create_dataset(20, 2, classes);
+
+        let mut network = create_network(2, 1, vec![4], vec![Some(tanh)], 2, Some(softmax));
+        let mut config = create_training_config(LossFunction::CrossEntropy, 10, 0.5, 0.0, 0.0, 0.0, 100, false, false);
+        network.train_from_source(&mut source, &mut config).unwrap();
+
+        let accuracy = network.accuracy(std::rc::Rc::new(std::cell::RefCell::new(features)), std::rc::Rc::new(std::cell::RefCell::new(classes)));
+        assert_eq!(accuracy, 1.0);
+    }
+
+    #[test]
+    fn test_weights_pass_through_wrappers() {
+        let (features, targets) = numbered(12);
+        let mut memory = create_memory_source(features, targets, false);
+        memory.weights = Some((0..12).map(|i| i as f32 * 10.0).collect());
+        let mut source = create_prefetch(create_shuffle_buffer(memory, 4, 1), 3, 2);
+        source.reset().unwrap();
+        let mut rows = 0;
+        while let Some((features, _, weights)) = source.next_weighted_batch(5).unwrap() {
+            for (row, weight) in features.data.iter().zip(weights.iter()) {
+                assert_eq!(*weight, row[0] * 10.0);
+                rows += 1;
+            }
+        }
+        assert_eq!(rows, 12);
+
+        let mut unweighted = create_generator_source(1, 1, |i| if i < 2 { Some((vec![0.0], vec![0.0])) } else { None });
+        assert_eq!(unweighted.next_weighted_batch(5).unwrap().unwrap().2, vec![1.0, 1.0]);
+    }
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod transformer_tests {
+    use cranium_rs::matrix::*;
+    use cranium_rs::attention::*;
+    use cranium_rs::transformer::*;
+
+    fn weighted_sum(output: &Matrix, weights: &Matrix) -> f32 {
+        output.hadamard(weights).data.iter().flatten().sum()
+    }
+
+    fn sample_matrix(rows: usize, cols: usize, seed: f32) -> Matrix {
+        let mut matrix = Matrix::create_zero_matrix(rows, cols);
+        for i in 0..rows {
+            for j in 0..cols {
+                matrix.set(i, j, ((i * cols + j) as f32 * 0.37 + seed).sin());
+            }
+        }
+        matrix
+    }
+
+    #[test]
+    fn test_layer_norm_forward() {
+        let mut norm = create_layer_norm(4);
+        let output = norm.forward(&sample_matrix(2, 4, 0.3));
+        for row in output.data.iter() {
+            let mean = row.iter().sum::<f32>() / 4.0;
+            let variance = row.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / 4.0;
+            assert!(mean.abs() < 1e-5);
+            assert!((variance - 1.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_layer_norm_backward() {
+        let mut norm = create_layer_norm(4);
+        norm.gamma = sample_matrix(1, 4, 2.0);
+        let input = sample_matrix(2, 4, 0.3);
+        let d_output = sample_matrix(2, 4, 1.1);
+        norm.forward(&input);
+        let d_input = norm.backward(&d_output);
+
+        let epsilon = 1e-2;
+        for i in 0..2 {
+            for j in 0..4 {
+                let mut plus = input.copy();
+                plus.set(i, j, input.get(i, j) + epsilon);
+                let mut minus = input.copy();
+                minus.set(i, j, input.get(i, j) - epsilon);
+                let numeric = (weighted_sum(&norm.forward(&plus), &d_output) - weighted_sum(&norm.forward(&minus), &d_output)) / (2.0 * epsilon);
+                assert!((numeric - d_input.get(i, j)).abs() < 2e-2 * (1.0 + numeric.abs()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_encoder_block_backward() {
+        // Fixed weights make the check deterministic; random ones occasionally straddle ReLU's kink.
+        let mut block = create_encoder_block(4, 2, 8);
+        block.attention.query.weights = sample_matrix(4, 4, 0.1);
+        block.attention.key.weights = sample_matrix(4, 4, 0.8);
+        block.attention.value.weights = sample_matrix(4, 4, 1.5);
+        block.attention.output.weights = sample_matrix(4, 4, 3.1);
+        block.feed_forward.hidden.weights = sample_matrix(4, 8, 0.4);
+        block.feed_forward.output.weights = sample_matrix(8, 4, 2.3);
+        let input = add_positional_encoding(&sample_matrix(3, 4, 0.6));
+        let d_output = sample_matrix(3, 4, 1.7);
+        block.forward(&input, None);
+        let d_input = block.backward(&d_output);
+
+        let epsilon = 1e-2;
+        for i in 0..3 {
+            for j in 0..4 {
+                let mut plus = input.copy();
+                plus.set(i, j, input.get(i, j) + epsilon);
+                let mut minus = input.copy();
+                minus.set(i, j, input.get(i, j) - epsilon);
+                let numeric = (weighted_sum(&block.forward(&plus, None), &d_output) - weighted_sum(&block.forward(&minus, None), &d_output)) / (2.0 * epsilon);
+                assert!((numeric - d_input.get(i, j)).abs() < 5e-2 * (1.0 + numeric.abs()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_encoder_block_training() {
+        let mut block = create_encoder_block(4, 2, 8);
+        let input = add_positional_encoding(&sample_matrix(3, 4, 0.2));
+        let target = sample_matrix(3, 4, 0.9);
+        let mask = causal_mask(3);
+
+        let mut losses = Vec::new();
+        for _ in 0..100 {
+            let output = block.forward(&input, Some(&mask));
+            let mut d_output = Matrix::create_zero_matrix(3, 4);
+            let mut loss = 0.0;
+            for i in 0..3 {
+                for j in 0..4 {
+                    let diff = output.get(i, j) - target.get(i, j);
+                    loss += 0.5 * diff * diff;
+                    d_output.set(i, j, diff);
+                }
+            }
+            losses.push(loss);
+            block.zero_gradients();
+            block.backward(&d_output);
+            block.update(0.05);
+        }
+
+        assert!(losses[losses.len() - 1] < 0.5 * losses[0]);
+    }
+}
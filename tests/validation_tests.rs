@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod validation_tests {
+    use cranium_rs::dataset::*;
+    use cranium_rs::function::*;
+    use cranium_rs::network::*;
+    use cranium_rs::validation::*;
+
+    // Row i has feature i and class i % 3, with class 2 twice as rare.
+    fn labelled(rows: usize) -> (DataSet, DataSet) {
+        let labels: Vec<usize> = (0..rows).map(|i| if i % 4 == 3 { 2 } else { i % 2 }).collect();
+        let features = create_dataset(rows, 1, (0..rows).map(|i| vec![i as f32]).collect());
+        (features, one_hot(&labels, 3))
+    }
+
+    fn count_class(classes: &DataSet, class: usize) -> usize {
+        class_indices(classes).iter().filter(|&&c| c == class).count()
+    }
+
+    fn row_ids(subset: &Subset) -> Vec<usize> {
+        subset.features.data.iter().map(|row| row[0] as usize).collect()
+    }
+
+    #[test]
+    fn test_train_test_split_partitions_rows() {
+        let (features, classes) = labelled(20);
+        let (train, test) = train_test_split(&features, &classes, 0.25, 7).unwrap();
+        assert_eq!(train.features.rows, 15);
+        assert_eq!(test.features.rows, 5);
+        assert_eq!(test.classes.rows, 5);
+
+        let mut all = row_ids(&train);
+        all.extend(row_ids(&test));
+        all.sort();
+        assert_eq!(all, (0..20).collect::<Vec<usize>>());
+        for (row, class) in test.features.data.iter().zip(class_indices(&test.classes)) {
+            assert_eq!(class_indices(&classes)[row[0] as usize], class);
+        }
+    }
+
+    #[test]
+    fn test_split_is_reproducible() {
+        let (features, classes) = labelled(30);
+        let (_, first) = train_test_split(&features, &classes, 0.3, 42).unwrap();
+        let (_, second) = train_test_split(&features, &classes, 0.3, 42).unwrap();
+        let (_, other) = train_test_split(&features, &classes, 0.3, 43).unwrap();
+        assert_eq!(row_ids(&first), row_ids(&second));
+        assert_ne!(row_ids(&first), row_ids(&other));
+    }
+
+    #[test]
+    fn test_stratified_split_keeps_proportions() {
+        let (features, classes) = labelled(40);
+        let (train, test) = stratified_train_test_split(&features, &classes, 0.5, 3).unwrap();
+        for class in 0..3 {
+            assert_eq!(count_class(&train.classes, class), count_class(&classes, class) / 2);
+            assert_eq!(count_class(&test.classes, class), count_class(&classes, class) / 2);
+        }
+    }
+
+    #[test]
+    fn test_train_validation_test_split() {
+        let (features, classes) = labelled(20);
+        let (train, validation, test) = train_validation_test_split(&features, &classes, 0.2, 0.2, 1).unwrap();
+        assert_eq!((train.features.rows, validation.features.rows, test.features.rows), (12, 4, 4));
+        assert!(train_validation_test_split(&features, &classes, 0.6, 0.5, 1).is_err());
+    }
+
+    #[test]
+    fn test_split_rejects_mismatched_rows() {
+        let (features, _) = labelled(10);
+        let (_, classes) = labelled(8);
+        assert!(train_test_split(&features, &classes, 0.2, 0).is_err());
+    }
+
+    #[test]
+    fn test_k_fold_covers_every_row_once() {
+        let folds: Vec<Fold> = k_fold(10, 3, Some(5)).unwrap().collect();
+        assert_eq!(folds.len(), 3);
+        let sizes: Vec<usize> = folds.iter().map(|fold| fold.test.len()).collect();
+        assert_eq!(sizes, vec![4, 3, 3]);
+
+        let mut tested: Vec<usize> = folds.iter().flat_map(|fold| fold.test.clone()).collect();
+        tested.sort();
+        assert_eq!(tested, (0..10).collect::<Vec<usize>>());
+        for fold in &folds {
+            assert_eq!(fold.train.len() + fold.test.len(), 10);
+            assert!(fold.train.iter().all(|row| !fold.test.contains(row)));
+        }
+    }
+
+    #[test]
+    fn test_k_fold_without_seed_is_ordered() {
+        let first = k_fold(6, 2, None).unwrap().next().unwrap();
+        assert_eq!(first.test, vec![0, 1, 2]);
+        assert_eq!(first.train, vec![3, 4, 5]);
+        assert!(k_fold(3, 4, None).is_err());
+        assert!(k_fold(3, 1, None).is_err());
+    }
+
+    #[test]
+    fn test_stratified_k_fold_balances_classes() {
+        let (_, classes) = labelled(24);
+        for fold in stratified_k_fold(&classes, 3, 9).unwrap() {
+            let test = select_rows(&classes, &fold.test);
+            assert_eq!(count_class(&test, 0), 4);
+            assert_eq!(count_class(&test, 1), 2);
+            assert_eq!(count_class(&test, 2), 2);
+        }
+    }
+
+    #[test]
+    fn test_cross_validate_trains_each_fold() {
+        let rows = 20;
+        let features = create_dataset(rows, 2, (0..rows).map(|i| {
+            let offset = if i % 2 == 0 { 1.0 } else { -1.0 };
+            vec![offset + (i as f32).sin() * 0.2, offset]
+        }).collect());
+        let labels: Vec<usize> = (0..rows).map(|i| i % 2).collect();
+        let classes = one_hot(&labels, 2);
+
+        let folds = stratified_k_fold(&classes, 4, 0).unwrap();
+        let mut created = 0;
+        let result = cross_validate(&features, &classes, folds, || {
+            created += 1;
+            create_network(2, 1, vec![4], vec![Some(tanh)], 2, Some(softmax))
+        }, |features, classes| {
+            create_parameter_set(features, classes, LossFunction::CrossEntropy, 15, 0.5, 0.0, 0.0, 0.0, 100, false, false)
+        }).unwrap();
+
+        assert_eq!(created, 4);
+        assert_eq!(result.fold_accuracy.len(), 4);
+        assert_eq!(result.mean_accuracy(), 1.0);
+        assert_eq!(result.std_accuracy(), 0.0);
+        assert!(result.mean_loss() < 0.5);
+    }
+}
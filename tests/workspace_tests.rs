@@ -0,0 +1,113 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+// Counts allocations made on the current thread while `COUNTING` is set.
+struct CountingAllocator;
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.with(|counting| counting.get()) {
+            ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn count_allocations<F: FnOnce()>(f: F) -> usize {
+    ALLOCATIONS.with(|allocations| allocations.set(0));
+    COUNTING.with(|counting| counting.set(true));
+    f();
+    COUNTING.with(|counting| counting.set(false));
+    ALLOCATIONS.with(|allocations| allocations.get())
+}
+
+#[cfg(test)]
+mod workspace_tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use cranium_rs::dataset::*;
+    use cranium_rs::error::Error;
+    use cranium_rs::function::*;
+    use cranium_rs::network::*;
+    use cranium_rs::workspace::*;
+    use super::count_allocations;
+
+    fn blobs() -> (DataSet, DataSet) {
+        let mut features = Vec::new();
+        let mut classes = Vec::new();
+        for i in 0..16 {
+            let offset = if i % 2 == 0 { 1.0 } else { -1.0 };
+            let jitter = (i as f32 * 0.7).sin() * 0.3;
+            features.push(vec![offset + jitter, offset - jitter, jitter]);
+            classes.push(if i % 2 == 0 { vec![1.0, 0.0] } else { vec![0.0, 1.0] });
+        }
+        (create_dataset(16, 3, features), create_dataset(16, 2, classes))
+    }
+
+    #[test]
+    fn test_steady_state_steps_do_not_allocate() {
+        let (features, classes) = blobs();
+        let mut network = create_network(3, 2, vec![8, 4], vec![Some(relu), Some(tanh)], 2, Some(softmax));
+        let mut config = create_training_config(LossFunction::CrossEntropy, 16, 0.1, 0.0, 0.01, 0.9, 1, false, false);
+        config.clipping = Some(GradientClipping::GlobalNorm(5.0));
+        config.divergence = DivergencePolicy::Rollback;
+        config.class_weights = Some(vec![1.0, 2.0]);
+        let weights = vec![1.0; 16];
+        let mut workspace = create_workspace(&network);
+        network.train_step(&mut workspace, &features, &classes, Some(&weights), &mut config).unwrap();
+
+        let allocations = count_allocations(|| {
+            for _ in 0..10 {
+                network.train_step(&mut workspace, &features, &classes, Some(&weights), &mut config).unwrap();
+            }
+        });
+        assert_eq!(allocations, 0);
+    }
+
+    #[test]
+    fn test_train_step_matches_batch_gradient_descent() {
+        let (features, classes) = blobs();
+        let mut trained = create_network(3, 1, vec![5], vec![Some(sigmoid)], 2, Some(softmax));
+        let mut stepped = trained.cast::<f32>().unwrap();
+        let mut params = create_parameter_set(features.clone(), classes.clone(), LossFunction::CrossEntropy, 8, 0.3, 0.0, 0.0, 0.5, 3, false, false);
+        trained.batch_gradient_descent(&mut params).unwrap();
+
+        let mut workspace = create_workspace(&stepped);
+        for epoch in 1..=3 {
+            workspace.epoch = epoch;
+            for start in [0, 8] {
+                let rows = |set: &DataSet| select_rows(set, &(start..start + 8).collect::<Vec<_>>());
+                stepped.train_step(&mut workspace, &rows(&features), &rows(&classes), None, &mut params.config).unwrap();
+            }
+        }
+
+        trained.forward_pass(Rc::new(RefCell::new(features.clone())));
+        stepped.forward_pass(Rc::new(RefCell::new(features)));
+        assert_eq!(trained.get_output().borrow().data, stepped.get_output().borrow().data);
+    }
+
+    #[test]
+    fn test_workspace_for_other_network_is_rejected() {
+        let (features, classes) = blobs();
+        let small = create_network(3, 0, vec![], vec![], 2, Some(softmax));
+        let mut network = create_network(3, 1, vec![4], vec![Some(relu)], 2, Some(softmax));
+        let mut config = create_training_config(LossFunction::CrossEntropy, 16, 0.1, 0.0, 0.0, 0.0, 1, false, false);
+        let mut workspace = create_workspace(&small);
+        assert!(!workspace.fits(&network));
+
+        let result = network.train_step(&mut workspace, &features, &classes, None, &mut config);
+        assert!(matches!(result, Err(Error::InvalidConfig(_))));
+    }
+}